@@ -1,14 +1,15 @@
 #[doc(hidden)]
+#[cfg(windows)]
 #[allow(non_snake_case)]
 #[unsafe(no_mangle)]
-unsafe extern "system" fn DllMain(_: usize, reason: u32, _: isize) -> bool {
+unsafe extern "system" fn DllMain(_: usize, reason: u32, reserved: isize) -> bool {
     unsafe {
         match reason {
             1 => {
                 cauldron::handle_dll_attach();
             }
             0 => {
-                cauldron::handle_dll_detach();
+                cauldron::handle_dll_detach(reserved != 0);
             }
             _ => {}
         }
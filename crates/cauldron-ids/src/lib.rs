@@ -0,0 +1,20 @@
+//! Ids shared by the loader and `cauldron_plugin`, the macro crate can't depend on cauldron.
+
+/// Ids of the games in `cauldron::version::CauldronGameType::ALL`, in the same order.
+pub const GAME_IDS: [&str; 3] = ["hfw", "hzd", "hzdr"];
+
+/// Reserved dependency id for requiring a loader version, e.g. `cauldron = ">=0.2"`.
+pub const LOADER_ID: &str = "cauldron";
+
+/// Longest id allowed, including the leading letter.
+pub const MAX_ID_LENGTH: usize = 64;
+
+/// Returns `true` if `id` is usable as a plugin id or dependency key.
+///
+/// Ids start with a lowercase letter, followed by 1 to 63 lowercase letters, digits, `-` or `_`.
+pub fn is_valid_id(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && (2..=MAX_ID_LENGTH).contains(&id.len())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
@@ -0,0 +1,202 @@
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, LitBool, LitStr, Token, parenthesized};
+
+/// A value from the attribute, along with where it was written for error reporting.
+#[derive(Clone)]
+pub struct Spanned {
+    pub value: String,
+    pub span: Span,
+}
+
+impl Spanned {
+    pub fn call_site(value: String) -> Self {
+        Spanned {
+            value,
+            span: Span::call_site(),
+        }
+    }
+}
+
+impl From<LitStr> for Spanned {
+    fn from(value: LitStr) -> Self {
+        Spanned {
+            value: value.value(),
+            span: value.span(),
+        }
+    }
+}
+
+/// Ids can be written as `"some-id"` or, if they are valid rust identifiers, `some_id`.
+impl Parse for Spanned {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            Ok(input.parse::<LitStr>()?.into())
+        } else {
+            let ident = Ident::parse_any(input)?;
+            Ok(Spanned {
+                value: ident.to_string(),
+                span: ident.span(),
+            })
+        }
+    }
+}
+
+/// A dependency or conflict, either `id = "req"` or `id(version = "req", ...)`.
+pub struct Requirement {
+    pub id: Spanned,
+    pub version: Spanned,
+    pub reason: Option<Spanned>,
+    pub optional: Option<(bool, Span)>,
+}
+
+impl Parse for Requirement {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let id = input.parse::<Spanned>()?;
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            return Ok(Requirement {
+                id,
+                version: input.parse::<LitStr>()?.into(),
+                reason: None,
+                optional: None,
+            });
+        }
+
+        let content;
+        parenthesized!(content in input);
+        let mut version = None;
+        let mut reason = None;
+        let mut optional = None;
+        while !content.is_empty() {
+            let key = Ident::parse_any(&content)?;
+            match key.to_string().as_str() {
+                "version" if version.is_none() => {
+                    content.parse::<Token![=]>()?;
+                    version = Some(content.parse::<LitStr>()?.into());
+                }
+                "reason" if reason.is_none() => {
+                    content.parse::<Token![=]>()?;
+                    reason = Some(content.parse::<LitStr>()?.into());
+                }
+                "optional" if optional.is_none() => {
+                    if content.peek(Token![=]) {
+                        content.parse::<Token![=]>()?;
+                        let value = content.parse::<LitBool>()?;
+                        optional = Some((value.value, value.span));
+                    } else {
+                        optional = Some((true, key.span()));
+                    }
+                }
+                "version" | "reason" | "optional" => {
+                    return Err(syn::Error::new(key.span(), format!("duplicate `{}`", key)));
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown key `{}`, expected `version`, `reason` or `optional`",
+                            key
+                        ),
+                    ));
+                }
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        let Some(version) = version else {
+            return Err(syn::Error::new(
+                id.span,
+                format!("`{}` is missing `version = \"...\"`", id.value),
+            ));
+        };
+        Ok(Requirement {
+            id,
+            version,
+            reason,
+            optional,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct PluginArgs {
+    pub id: Option<Spanned>,
+    pub version: Option<Spanned>,
+    pub name: Option<Spanned>,
+    pub description: Option<Spanned>,
+    pub contributors: Option<Vec<Spanned>>,
+    pub license: Option<Spanned>,
+    pub homepage: Option<Spanned>,
+    pub source: Option<Spanned>,
+    pub dependencies: Vec<Requirement>,
+    pub conflicts: Vec<Requirement>,
+    pub load_before: Vec<Spanned>,
+    pub load_after: Vec<Spanned>,
+    pub supported_games: Option<Vec<Spanned>>,
+    pub minimum_cauldron_version: Option<Spanned>,
+    pub init_phase: Option<Spanned>,
+}
+
+fn set<T>(field: &mut Option<T>, key: &Ident, value: T) -> syn::Result<()> {
+    if field.is_some() {
+        return Err(syn::Error::new(key.span(), format!("duplicate `{}`", key)));
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+fn list<T: Parse>(input: ParseStream) -> syn::Result<Vec<T>> {
+    let content;
+    parenthesized!(content in input);
+    Ok(Punctuated::<T, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+impl Parse for PluginArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = PluginArgs::default();
+        while !input.is_empty() {
+            let key = Ident::parse_any(input)?;
+            let name = key.to_string();
+            let string = |input: ParseStream| -> syn::Result<Spanned> {
+                input.parse::<Token![=]>()?;
+                Ok(input.parse::<LitStr>()?.into())
+            };
+            match name.as_str() {
+                "id" => set(&mut args.id, &key, string(input)?)?,
+                "version" => set(&mut args.version, &key, string(input)?)?,
+                "name" => set(&mut args.name, &key, string(input)?)?,
+                "description" => set(&mut args.description, &key, string(input)?)?,
+                "license" => set(&mut args.license, &key, string(input)?)?,
+                "homepage" => set(&mut args.homepage, &key, string(input)?)?,
+                "source" => set(&mut args.source, &key, string(input)?)?,
+                "minimum_cauldron_version" => {
+                    set(&mut args.minimum_cauldron_version, &key, string(input)?)?
+                }
+                "init_phase" => set(&mut args.init_phase, &key, string(input)?)?,
+                "contributors" => set(&mut args.contributors, &key, list(input)?)?,
+                "supported_games" => set(&mut args.supported_games, &key, list(input)?)?,
+                "deps" | "dependencies" => args.dependencies.extend(list(input)?),
+                "conflicts" => args.conflicts.extend(list(input)?),
+                "load_before" => args.load_before.extend(list(input)?),
+                "load_after" => args.load_after.extend(list(input)?),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("unknown key `{}`", key),
+                    ));
+                }
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
@@ -0,0 +1,66 @@
+//! Procedural macros for Cauldron plugins, use them through the re-exports in `cauldron`.
+
+mod args;
+mod plugin;
+
+use crate::args::PluginArgs;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Item, parse_macro_input};
+
+/// Declares a Cauldron plugin, generating its metadata and the exports the loader looks for.
+///
+/// The metadata is checked while compiling, so a bad id or version requirement is a build error
+/// rather than a rejected plugin in-game. Anything left out falls back to the crate's
+/// `Cargo.toml`:
+///
+/// | key           | default                                      |
+/// |---------------|----------------------------------------------|
+/// | `id`          | `package.name`                               |
+/// | `version`     | `package.version`                            |
+/// | `description` | `package.description`                        |
+/// | `contributors`| `package.authors`, without email addresses   |
+/// | `license`     | `package.license`                            |
+/// | `homepage`    | `package.homepage`                           |
+/// | `source`      | `package.repository`                         |
+///
+/// The rest of the keys match the `[cauldron]` table of a `.cauldron.toml` (schema version 1).
+/// The generated toml is available as `<Plugin>::CAULDRON_METADATA`, e.g. for
+/// `cauldron::test_host::TestHost::with_plugin`.
+///
+/// ```no_run
+/// use cauldron::context::PluginContext;
+/// use cauldron::{CauldronPlugin, cauldron_plugin};
+///
+/// #[cauldron_plugin(
+///     name = "Example",
+///     deps(hfw = "*", base_plugin(version = "^1.2", optional, reason = "Adds a settings page.")),
+///     conflicts("old-example" = "*"),
+///     load_after(pulse),
+///     supported_games(hfw),
+///     init_phase = "late",
+/// )]
+/// struct Example;
+///
+/// impl CauldronPlugin for Example {
+///     fn new() -> Self {
+///         Example
+///     }
+///
+///     fn on_init(&self, _context: &PluginContext) {}
+/// }
+/// # fn main() {}
+/// ```
+#[proc_macro_attribute]
+pub fn cauldron_plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as Item);
+    let expanded = syn::parse::<PluginArgs>(attr).and_then(|args| plugin::expand(args, &item));
+    match expanded {
+        Ok(expanded) => expanded.into(),
+        // keep the item so errors in the metadata don't cascade into everything using it.
+        Err(error) => {
+            let error = error.into_compile_error();
+            quote!(#item #error).into()
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use crate::args::{PluginArgs, Requirement, Spanned};
+use cauldron_ids::{GAME_IDS, LOADER_ID, is_valid_id};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use semver::{Version, VersionReq};
+use std::collections::HashSet;
+use std::env;
+use syn::Item;
+use toml::{Table, Value};
+
+const INIT_PHASES: [&str; 3] = ["early", "default", "late"];
+
+/// Reads a `CARGO_PKG_*` variable of the crate being compiled.
+fn package(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+/// Collects every problem so they're all reported in one build.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, span: Span, message: impl std::fmt::Display) {
+        let error = syn::Error::new(span, message);
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    fn finish(self) -> syn::Result<()> {
+        match self.0 {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn expand(args: PluginArgs, item: &Item) -> syn::Result<TokenStream> {
+    let (ident, generics) = match item {
+        Item::Struct(item) => (&item.ident, &item.generics),
+        Item::Enum(item) => (&item.ident, &item.generics),
+        item => {
+            return Err(syn::Error::new_spanned(
+                item,
+                "#[cauldron_plugin] can only be used on a struct or enum",
+            ));
+        }
+    };
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            generics,
+            "cauldron plugins can't be generic",
+        ));
+    }
+
+    let metadata = toml::to_string(&metadata(args)?).unwrap();
+    Ok(quote! {
+        #item
+
+        impl #ident {
+            /// The plugin's `.cauldron.toml`, generated by `#[cauldron_plugin]`.
+            pub const CAULDRON_METADATA: &'static str = #metadata;
+        }
+
+        ::cauldron::define_cauldron_plugin!(#ident, #ident::CAULDRON_METADATA);
+    })
+}
+
+fn metadata(args: PluginArgs) -> syn::Result<Table> {
+    let mut errors = Errors::default();
+
+    let id = match args.id {
+        Some(id) if id.value == LOADER_ID => {
+            errors.push(id.span, "`cauldron` is reserved for the loader");
+            id
+        }
+        Some(id) => {
+            check_id(&mut errors, &id);
+            id
+        }
+        None => {
+            let id = Spanned::call_site(package("CARGO_PKG_NAME").unwrap_or_default());
+            if !is_valid_id(&id.value) {
+                errors.push(
+                    id.span,
+                    format!(
+                        "package name `{}` is not a valid plugin id, set one with `id = \"...\"`",
+                        id.value
+                    ),
+                );
+            }
+            id
+        }
+    };
+    let version = args
+        .version
+        .unwrap_or_else(|| Spanned::call_site(package("CARGO_PKG_VERSION").unwrap_or_default()));
+    check_version(&mut errors, &version);
+
+    let mut cauldron = Table::new();
+    cauldron.insert("id".into(), id.value.into());
+    cauldron.insert("version".into(), version.value.into());
+
+    let mut metadata = Table::new();
+    let strings = [
+        ("name", args.name.map(|name| name.value)),
+        (
+            "description",
+            args.description
+                .map(|description| description.value)
+                .or_else(|| package("CARGO_PKG_DESCRIPTION")),
+        ),
+        (
+            "license",
+            args.license
+                .map(|license| license.value)
+                .or_else(|| package("CARGO_PKG_LICENSE")),
+        ),
+        (
+            "homepage",
+            args.homepage
+                .map(|homepage| homepage.value)
+                .or_else(|| package("CARGO_PKG_HOMEPAGE")),
+        ),
+        (
+            "source",
+            args.source
+                .map(|source| source.value)
+                .or_else(|| package("CARGO_PKG_REPOSITORY")),
+        ),
+    ];
+    for (key, value) in strings {
+        if let Some(value) = value {
+            metadata.insert(key.into(), value.into());
+        }
+    }
+    let contributors = match args.contributors {
+        Some(contributors) => contributors.into_iter().map(|c| c.value).collect(),
+        // cargo authors are `Name <email>`, only the name is shown in cauldron.
+        None => package("CARGO_PKG_AUTHORS")
+            .unwrap_or_default()
+            .split(':')
+            .map(|author| author.split(" <").next().unwrap().trim().to_string())
+            .filter(|author| !author.is_empty())
+            .collect::<Vec<_>>(),
+    };
+    if !contributors.is_empty() {
+        metadata.insert("contributors".into(), contributors.into());
+    }
+    if !metadata.is_empty() {
+        cauldron.insert("metadata".into(), metadata.into());
+    }
+
+    if !args.dependencies.is_empty() {
+        let dependencies = requirements(&mut errors, args.dependencies, true);
+        cauldron.insert("dependencies".into(), dependencies.into());
+    }
+    if !args.conflicts.is_empty() {
+        let conflicts = requirements(&mut errors, args.conflicts, false);
+        cauldron.insert("conflicts".into(), conflicts.into());
+    }
+    for (key, ids) in [
+        ("load_before", args.load_before),
+        ("load_after", args.load_after),
+    ] {
+        if ids.is_empty() {
+            continue;
+        }
+        ids.iter().for_each(|id| check_id(&mut errors, id));
+        let ids = ids.into_iter().map(|id| id.value).collect::<Vec<_>>();
+        cauldron.insert(key.into(), ids.into());
+    }
+    if let Some(games) = args.supported_games {
+        for game in &games {
+            if !GAME_IDS.contains(&game.value.as_str()) {
+                errors.push(
+                    game.span,
+                    format!(
+                        "unknown game `{}`, expected one of {}",
+                        game.value,
+                        GAME_IDS.join(", ")
+                    ),
+                );
+            }
+        }
+        let games = games.into_iter().map(|game| game.value).collect::<Vec<_>>();
+        cauldron.insert("supported_games".into(), games.into());
+    }
+    if let Some(version) = args.minimum_cauldron_version {
+        check_version(&mut errors, &version);
+        cauldron.insert("minimum_cauldron_version".into(), version.value.into());
+    }
+    if let Some(phase) = args.init_phase {
+        if !INIT_PHASES.contains(&phase.value.as_str()) {
+            errors.push(
+                phase.span,
+                format!(
+                    "unknown init phase `{}`, expected one of {}",
+                    phase.value,
+                    INIT_PHASES.join(", ")
+                ),
+            );
+        }
+        cauldron.insert("init_phase".into(), phase.value.into());
+    }
+
+    errors.finish()?;
+
+    let mut document = Table::new();
+    document.insert("schema_version".into(), 1.into());
+    document.insert("cauldron".into(), cauldron.into());
+    Ok(document)
+}
+
+fn check_id(errors: &mut Errors, id: &Spanned) {
+    if !is_valid_id(&id.value) {
+        errors.push(
+            id.span,
+            format!(
+                "`{}` is not a valid plugin id, ids must be 2-64 characters of a-z, 0-9, - or _ and start with a letter",
+                id.value
+            ),
+        );
+    }
+}
+
+fn check_version(errors: &mut Errors, version: &Spanned) {
+    if let Err(error) = Version::parse(&version.value) {
+        errors.push(
+            version.span,
+            format!(
+                "`{}` is not a valid semver version: {}",
+                version.value, error
+            ),
+        );
+    }
+}
+
+fn requirements(errors: &mut Errors, requirements: Vec<Requirement>, dependencies: bool) -> Table {
+    let mut seen = HashSet::new();
+    let mut table = Table::new();
+    for requirement in requirements {
+        check_id(errors, &requirement.id);
+        if !seen.insert(requirement.id.value.clone()) {
+            errors.push(
+                requirement.id.span,
+                format!("`{}` is listed more than once", requirement.id.value),
+            );
+        }
+        if let Err(error) = VersionReq::parse(&requirement.version.value) {
+            errors.push(
+                requirement.version.span,
+                format!(
+                    "`{}` is not a valid version requirement: {}",
+                    requirement.version.value, error
+                ),
+            );
+        }
+
+        let value = match (requirement.reason, requirement.optional) {
+            (None, None) => Value::String(requirement.version.value),
+            (reason, optional) => {
+                let mut detailed = Table::new();
+                detailed.insert("version".into(), requirement.version.value.into());
+                if let Some(reason) = reason {
+                    detailed.insert("reason".into(), reason.value.into());
+                }
+                match optional {
+                    Some((_, span)) if !dependencies => {
+                        errors.push(span, "conflicts can't be optional")
+                    }
+                    Some((optional, _)) => {
+                        detailed.insert("optional".into(), optional.into());
+                    }
+                    None => {}
+                }
+                detailed.into()
+            }
+        };
+        table.insert(requirement.id.value, value);
+    }
+    table
+}
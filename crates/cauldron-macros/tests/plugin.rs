@@ -0,0 +1,81 @@
+use cauldron::metadata::validator::validate;
+use cauldron::metadata::{PluginInitPhase, PluginMetadataDependency, parse_plugin_metadata};
+use cauldron::{CauldronPlugin, cauldron_plugin};
+
+#[cauldron_plugin]
+struct Defaults;
+
+impl CauldronPlugin for Defaults {
+    fn new() -> Self {
+        Defaults
+    }
+}
+
+#[cauldron_plugin(
+    id = "example",
+    version = "1.2.3-beta.1",
+    name = "Example",
+    contributors("alice", "bob"),
+    deps(
+        hfw = "*",
+        base_plugin(version = "^1.2", optional, reason = "Adds a settings page."),
+        "other-plugin" = ">=0.3, <0.5",
+    ),
+    conflicts("old-example"(version = "*", reason = "Replaced by this plugin.")),
+    load_before(late_plugin),
+    load_after("early-plugin"),
+    supported_games(hfw, "hzdr"),
+    minimum_cauldron_version = "0.1.0",
+    init_phase = "early",
+)]
+struct Everything;
+
+impl CauldronPlugin for Everything {
+    fn new() -> Self {
+        Everything
+    }
+}
+
+#[test]
+fn defaults_come_from_cargo() {
+    assert_eq!(validate(Defaults::CAULDRON_METADATA), Ok(()));
+
+    let metadata = parse_plugin_metadata(Defaults::CAULDRON_METADATA).unwrap();
+    assert_eq!(metadata.cauldron.id, env!("CARGO_PKG_NAME"));
+    assert_eq!(metadata.cauldron.version, env!("CARGO_PKG_VERSION"));
+    let metadata = metadata.cauldron.metadata.unwrap();
+    assert_eq!(
+        metadata.description.as_deref(),
+        Some(env!("CARGO_PKG_DESCRIPTION"))
+    );
+    assert_eq!(metadata.license.as_deref(), Some(env!("CARGO_PKG_LICENSE")));
+    assert!(metadata.contributors.is_some());
+}
+
+#[test]
+fn every_key_is_written() {
+    assert_eq!(validate(Everything::CAULDRON_METADATA), Ok(()));
+
+    let cauldron = parse_plugin_metadata(Everything::CAULDRON_METADATA)
+        .unwrap()
+        .cauldron;
+    assert_eq!(cauldron.id, "example");
+    assert_eq!(cauldron.version, "1.2.3-beta.1");
+    assert_eq!(cauldron.metadata.unwrap().name.as_deref(), Some("Example"));
+
+    let dependencies = cauldron.dependencies.unwrap();
+    assert_eq!(dependencies.len(), 3);
+    match &dependencies["base_plugin"] {
+        PluginMetadataDependency::Detailed(dependency) => {
+            assert_eq!(dependency.version, "^1.2");
+            assert!(dependency.optional);
+        }
+        PluginMetadataDependency::Plain(_) => panic!("expected a detailed dependency"),
+    }
+    assert!(cauldron.conflicts.unwrap().contains_key("old-example"));
+    assert_eq!(cauldron.load_before, ["late_plugin"]);
+    assert_eq!(cauldron.load_after, ["early-plugin"]);
+    assert_eq!(cauldron.supported_games.unwrap(), ["hfw", "hzdr"]);
+    assert_eq!(cauldron.minimum_cauldron_version.as_deref(), Some("0.1.0"));
+    assert_eq!(cauldron.init_phase, PluginInitPhase::Early);
+}
@@ -0,0 +1,6 @@
+use cauldron::cauldron_plugin;
+
+#[cauldron_plugin]
+struct Example<T>(T);
+
+fn main() {}
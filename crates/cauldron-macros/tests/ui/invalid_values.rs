@@ -0,0 +1,20 @@
+use cauldron::{CauldronPlugin, cauldron_plugin};
+
+#[cauldron_plugin(
+    id = "cauldron",
+    version = "1.0",
+    name = "Example",
+    deps(hfw = "~>1", "Base" = "*"),
+    conflicts(old(version = "*", optional)),
+    supported_games(gow),
+    init_phase = "first",
+)]
+struct Example;
+
+impl CauldronPlugin for Example {
+    fn new() -> Self {
+        Example
+    }
+}
+
+fn main() {}
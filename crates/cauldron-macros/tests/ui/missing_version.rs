@@ -0,0 +1,12 @@
+use cauldron::{CauldronPlugin, cauldron_plugin};
+
+#[cauldron_plugin(deps(base(optional)))]
+struct Example;
+
+impl CauldronPlugin for Example {
+    fn new() -> Self {
+        Example
+    }
+}
+
+fn main() {}
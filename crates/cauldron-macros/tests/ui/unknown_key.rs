@@ -0,0 +1,12 @@
+use cauldron::{CauldronPlugin, cauldron_plugin};
+
+#[cauldron_plugin(id = "example", dependencies(hfw = "*"), authors("alice"))]
+struct Example;
+
+impl CauldronPlugin for Example {
+    fn new() -> Self {
+        Example
+    }
+}
+
+fn main() {}
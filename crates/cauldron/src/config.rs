@@ -1,9 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::env::current_dir;
-use std::fs::File;
-use std::io::Write;
+use std::collections::BTreeMap;
 
 use crate::version::CauldronGameType;
+#[cfg(windows)]
+use {crate::util::game_root, std::fs::File, std::io::Write};
 
 /// Used to determine the config version before fully deserializing.
 #[derive(Debug, Deserialize)]
@@ -23,6 +23,10 @@ pub struct CauldronConfig {
     pub ui: CauldronConfigUiSeciton,
     #[serde(default)]
     pub game: Option<CauldronConfigGameSection>,
+    /// Built-in patches turned on or off by name, e.g. `CrashLogger = false`. Patches not listed
+    /// use their default from the patch database.
+    #[serde(default)]
+    pub patches: BTreeMap<String, bool>,
 }
 
 impl Default for CauldronConfig {
@@ -32,6 +36,7 @@ impl Default for CauldronConfig {
             logging: CauldronConfigLoggingSection::default(),
             ui: CauldronConfigUiSeciton::default(),
             game: None,
+            patches: BTreeMap::new(),
         }
     }
 }
@@ -77,6 +82,7 @@ pub struct CauldronConfigLoggingSection {
     pub console_level: LogLevelConfig,
 
     pub file_level: LogLevelConfig,
+    /// Relative to the game directory.
     pub file_path: String,
 }
 
@@ -127,9 +133,9 @@ impl Default for CauldronConfigUiSeciton {
     }
 }
 
+#[cfg(windows)]
 pub(crate) fn load_config() -> CauldronConfig {
-    let dir = current_dir().unwrap();
-    let dir = dir.join("cauldron");
+    let dir = game_root().join("cauldron");
     let file = dir.join("cauldron.toml");
 
     let config = if file.exists() {
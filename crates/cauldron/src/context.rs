@@ -0,0 +1,80 @@
+//! What plugins are handed when they're initialized.
+
+use crate::metadata::PluginMetadata;
+use crate::{CauldronLoader, PluginContainer};
+use libdecima::mem::patches::{Patch, PatchError, PatchRegistry};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub struct PluginContext<'a> {
+    pub loader: &'a CauldronLoader,
+    pub metadata: &'a PluginMetadata,
+    install_dir: &'a Path,
+}
+
+impl<'a> PluginContext<'a> {
+    pub(crate) fn new(loader: &'a CauldronLoader, plugin: &'a PluginContainer) -> Self {
+        PluginContext {
+            loader,
+            metadata: &plugin.metadata,
+            install_dir: &plugin.install_dir,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.metadata.cauldron.id
+    }
+
+    /// The directory the plugin's library is in. In-process plugins get
+    /// `cauldron/plugins/<id>`, which isn't created for them.
+    pub fn install_dir(&self) -> &Path {
+        self.install_dir
+    }
+
+    /// `cauldron/data/<id>`, for anything the plugin keeps between runs.
+    pub fn data_dir(&self) -> io::Result<PathBuf> {
+        self.plugin_dir("data")
+    }
+
+    /// `cauldron/cache/<id>`, for files that can be regenerated. Users may clear it at any time.
+    pub fn cache_dir(&self) -> io::Result<PathBuf> {
+        self.plugin_dir("cache")
+    }
+
+    /// `cauldron/logs/<id>`.
+    pub fn log_dir(&self) -> io::Result<PathBuf> {
+        self.plugin_dir("logs")
+    }
+
+    /// Patches `bytes` over `address`, owned by the plugin, after checking `expected` is there if
+    /// given. Refused if it overlaps another plugin's patch. See [PatchRegistry].
+    ///
+    /// # Safety
+    /// `address` must point at `bytes.len()` bytes of mapped memory of the game that nothing
+    /// holds a reference into, and code there must still be valid to run once patched, see
+    /// [PatchRegistry::enable].
+    pub unsafe fn patch(
+        &self,
+        name: &str,
+        address: usize,
+        bytes: &[u8],
+        expected: Option<&[u8]>,
+    ) -> Result<(), PatchError> {
+        let patch = Patch {
+            name: name.to_string(),
+            owner: self.id().to_string(),
+            address,
+            bytes: bytes.to_vec(),
+            expected: expected.map(|expected| expected.to_vec()),
+        };
+        unsafe { PatchRegistry::global().apply(patch) }
+    }
+
+    /// Resolves `cauldron/<kind>/<id>` against the game root, creating it if needed.
+    fn plugin_dir(&self, kind: &str) -> io::Result<PathBuf> {
+        let dir = self.loader.cauldron_dir().join(kind).join(self.id());
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+}
@@ -4,39 +4,62 @@
 #![doc = include_str!("../README.md")]
 
 pub mod config;
+pub mod context;
 pub mod metadata;
+pub mod package;
+pub mod platform;
+pub mod report;
+mod resolve;
+pub mod services;
+#[cfg(feature = "test-host")]
+pub mod test_host;
 pub mod util;
 pub mod version;
 
-use crate::config::load_config;
+pub use cauldron_macros::cauldron_plugin;
+
+use crate::context::PluginContext;
 use crate::metadata::{
-    ContributorsList, PluginMetadataDependency, PluginMetadataSchemaVersionOnly, PluginMetadataV0,
+    ContributorsList, LOADER_ID, PluginMetadata, PluginMetadataConflict, PluginMetadataDependency,
+    parse_plugin_metadata, validator,
 };
-use crate::util::message_box;
+use crate::report::{LoadError, LoadReport, PluginReport, RejectedPlugin};
+use crate::services::{HookService, PlatformHookService};
 use crate::version::{CauldronGameType, GameVersion};
 // use focus::egui_d3d12::pipeline::Pipeline;
 use libdecima::log;
-use libdecima::mem::patch_reporting_loggers;
-use libdecima::types::nixxes::log::NxLogImpl;
-use minhook::{MH_ApplyQueued, MH_EnableHook, MH_Initialize, MH_STATUS, MhHook};
-use once_cell::sync::OnceCell;
+use libdecima::mem::patches::PatchRegistry;
+#[cfg(windows)]
+use minhook::MhHook;
 use semver::{Version, VersionReq};
-use simplelog::{ColorChoice, Config, SharedLogger, TerminalMode};
-use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::env::{current_dir, current_exe};
-use std::ffi::{CStr, c_char};
-use std::fs;
-use std::fs::File;
-use std::path::PathBuf;
-use windows::Win32::UI::WindowsAndMessaging::{MB_ICONERROR, MB_OK};
-use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AllocConsole, AttachConsole};
+use std::path::{Path, PathBuf};
+#[cfg(windows)]
+use {
+    crate::config::{CauldronConfig, load_config},
+    crate::platform::{CurrentPlatform, Platform},
+    crate::util::message_box,
+    libdecima::mem::builtin_patches::{BuiltinPatchState, BuiltinPatchStatus, PatchDatabase},
+    libdecima::mem::cache::OffsetCache,
+    libdecima::mem::offsets::Offsets,
+    libdecima::mem::signatures::{SignatureDatabase, SignatureReport},
+    once_cell::sync::OnceCell,
+    simplelog::{ColorChoice, Config, SharedLogger, TerminalMode},
+    std::env::current_exe,
+    std::fs,
+    std::fs::File,
+};
+#[cfg(all(feature = "nixxes", windows))]
+use {
+    libdecima::types::nixxes::log::NxLogImpl,
+    std::ffi::{CStr, c_char},
+};
 
 pub trait CauldronPlugin {
     fn new() -> Self
     where
         Self: Sized;
-    fn on_init(&self, _loader: &CauldronLoader) {}
+    fn on_init(&self, _context: &PluginContext) {}
     fn on_deinit(&self) {}
 }
 
@@ -44,38 +67,64 @@ pub type PluginBox = Box<dyn CauldronPlugin + Send + Sync>;
 
 pub struct PluginContainer {
     pub plugin: PluginBox,
-    pub handle: libloading::Library,
-    pub metadata: PluginMetadataV0,
+    /// The library the plugin was loaded from, `None` for plugins registered in-process.
+    pub handle: Option<libloading::Library>,
+    pub metadata: PluginMetadata,
+    /// See [PluginContext::install_dir].
+    pub install_dir: PathBuf,
 }
 
 pub struct GameInfo {
     pub game_type: CauldronGameType,
     pub version: GameVersion,
+    /// The directory containing the game executable, `cauldron/` is resolved against it.
+    pub root: PathBuf,
 }
 
 pub struct CauldronLoader {
     pub plugins: Vec<PluginContainer>,
+    #[cfg(windows)]
     pub hooks: Vec<MhHook>,
     pub game: GameInfo,
+    pub report: LoadReport,
+    hook_service: Box<dyn HookService + Send + Sync>,
 }
 
 impl CauldronLoader {
     pub fn new() -> Self {
-        CauldronLoader {
-            plugins: Vec::new(),
-            hooks: Vec::new(), // todo: maybe move this to [PluginContainer]?
-            game: GameInfo {
+        Self::with_services(
+            GameInfo {
                 game_type: CauldronGameType::find_from_exe().unwrap(),
                 version: version::version(),
+                root: util::game_root(),
             },
+            Box::new(PlatformHookService),
+        )
+    }
+
+    /// Creates a loader for an already known game, with `hook_service` used to initialize and
+    /// apply plugin hooks.
+    pub fn with_services(game: GameInfo, hook_service: Box<dyn HookService + Send + Sync>) -> Self {
+        CauldronLoader {
+            plugins: Vec::new(),
+            #[cfg(windows)]
+            hooks: Vec::new(), // todo: maybe move this to [PluginContainer]?
+            game,
+            report: LoadReport::default(),
+            hook_service,
         }
     }
 
+    /// The `cauldron` directory in the game root, holding plugins, config and plugin data.
+    pub fn cauldron_dir(&self) -> PathBuf {
+        self.game.root.join("cauldron")
+    }
+
+    #[cfg(windows)]
     unsafe fn try_find_plugins(&self) -> Vec<PathBuf> {
         let mut paths = Vec::new();
 
-        let dir = current_dir().expect("CauldronLoader::try_find_plugins current_dir failed");
-        let dir = dir.join("cauldron").join("plugins");
+        let dir = self.cauldron_dir().join("plugins");
 
         if !dir.exists() {
             let _ = fs::create_dir_all(&dir);
@@ -101,7 +150,11 @@ impl CauldronLoader {
                         paths.push(entry);
                     }
                 }
-            } else if entry.to_str().unwrap().ends_with(".dll") {
+            } else if entry.to_str().unwrap().ends_with(".dll")
+                || entry
+                    .extension()
+                    .is_some_and(|ext| ext == package::EXTENSION)
+            {
                 paths.push(entry);
             }
         }
@@ -109,250 +162,342 @@ impl CauldronLoader {
         paths
     }
 
-    unsafe fn try_load_plugin(&mut self, plugin_path: &PathBuf) {
+    /// Loads a plugin library and registers it, see [CauldronLoader::add_plugin].
+    pub unsafe fn try_load_plugin(&mut self, plugin_path: &Path) {
+        let source = plugin_path.display().to_string();
+        unsafe { self.load_library(source, plugin_path, |_| Ok(())) }
+    }
+
+    /// Verifies and extracts a `.cauldron` package into `cauldron/packages`, then loads the
+    /// library inside it, see [package]. The library's metadata has to be for the id and version
+    /// in the manifest.
+    pub unsafe fn try_load_package(&mut self, package_path: &Path) {
+        let source = package_path.display().to_string();
+        let package = match package::extract(package_path, &self.cauldron_dir().join("packages")) {
+            Ok(package) => package,
+            Err(error) => {
+                self.reject(source, format!("invalid package: {}", error).as_str());
+                return;
+            }
+        };
+
+        let manifest = &package.manifest;
+        let check = |metadata: &PluginMetadata| {
+            let cauldron = &metadata.cauldron;
+            if cauldron.id != manifest.id {
+                Err(format!(
+                    "package is for {} but contains {}",
+                    manifest.id, cauldron.id
+                ))
+            } else if cauldron.version != manifest.version {
+                Err(format!(
+                    "package is for {} {} but contains {}",
+                    manifest.id, manifest.version, cauldron.version
+                ))
+            } else {
+                Ok(())
+            }
+        };
+        unsafe { self.load_library(source, &package.library(), check) };
+    }
+
+    /// Loads the plugin library at `path` and registers it if `check` accepts its metadata.
+    unsafe fn load_library(
+        &mut self,
+        source: String,
+        path: &Path,
+        check: impl FnOnce(&PluginMetadata) -> Result<(), String>,
+    ) {
         unsafe {
-            let handle = libloading::Library::new(&plugin_path);
+            let handle = libloading::Library::new(path);
             let Ok(handle) = handle else {
-                log!(
-                    "Cauldron",
-                    "Failed to load plugin: {}",
-                    plugin_path.display()
-                );
+                self.reject(source, "failed to load library");
                 return;
             };
             let metadata =
                 handle.get::<extern "C" fn() -> &'static str>(b"__cauldron_plugin__metadata\0");
             let Ok(metadata) = metadata else {
-                log!(
-                    "Cauldron",
-                    "Not a valid plugin (missing metadata export): {}",
-                    plugin_path.display()
-                );
+                self.reject(source, "not a valid plugin (missing metadata export)");
                 return;
             };
             let plugin = handle.get::<extern "C" fn() -> PluginBox>(b"__cauldron_plugin__new\0");
             let Ok(plugin) = plugin else {
-                log!(
-                    "Cauldron",
-                    "Not a valid plugin (missing create instance export): {}",
-                    plugin_path.display()
-                );
-                return;
-            };
-            let metadata_str = metadata();
-            let metadata = toml::from_str::<PluginMetadataSchemaVersionOnly>(metadata_str);
-            let Ok(metadata) = metadata else {
-                log!(
-                    "Cauldron",
-                    "Failed to parse plugin metadata: {}",
-                    plugin_path.display()
+                self.reject(
+                    source,
+                    "not a valid plugin (missing create instance export)",
                 );
                 return;
             };
-            let metadata = match metadata.schema_version {
-                0 => toml::from_str::<PluginMetadataV0>(metadata_str),
-                // when adding new plugin meta versions, do migrations from old to new here.
-                _ => {
-                    return;
-                }
-            };
-            let Ok(metadata) = metadata else {
-                log!(
-                    "Cauldron",
-                    "Failed to parse plugin metadata: {}",
-                    plugin_path.display()
-                );
-                return;
-            };
-            let plugin = plugin();
-            self.plugins.push(PluginContainer {
-                plugin,
-                handle,
-                metadata,
-            });
-
-            self.sort_and_validate_plugins();
+            let metadata = metadata();
+            let plugin = *plugin;
+            if self.register(source, metadata, || plugin(), Some(handle), check) {
+                let install_dir = path.parent().unwrap_or(Path::new(""));
+                self.plugins.last_mut().unwrap().install_dir = install_dir.to_path_buf();
+            }
         }
     }
 
-    fn sort_and_validate_plugins(&mut self) {
-        self.plugins.sort_by(|a, b| {
-            let a_id = &a.metadata.cauldron.id.clone();
-            let b_id = &b.metadata.cauldron.id.clone();
-            let a_deps = a.metadata.cauldron.dependencies.clone();
-            let b_deps = b.metadata.cauldron.dependencies.clone();
+    /// Registers a plugin from its metadata, `new` is only called if the metadata is valid.
+    ///
+    /// Returns false if the plugin was rejected, the reason is recorded in [LoadReport::rejected].
+    pub fn add_plugin(
+        &mut self,
+        source: String,
+        metadata: &str,
+        new: impl FnOnce() -> PluginBox,
+        handle: Option<libloading::Library>,
+    ) -> bool {
+        self.register(source, metadata, new, handle, |_| Ok(()))
+    }
 
-            if a_deps.as_ref().is_some_and(|a| a.contains_key(b_id))
-                && b_deps.as_ref().is_some_and(|b| b.contains_key(a_id))
-            {
-                log!(
-                    "Cauldron",
-                    "Circular dependencies detected. ({} and {} depend on each other.)",
-                    a_id,
-                    b_id
-                );
-                message_box(
-                    "cauldron: plugin error",
-                    format!(
-                        "Circular dependencies detected.\n{} and {} depend on each other.",
-                        a_id, b_id
-                    )
-                    .as_str(),
-                    MB_OK | MB_ICONERROR,
-                );
-                std::process::exit(0);
-            } else if a_deps.as_ref().is_some_and(|a| a.contains_key(b_id)) {
-                Ordering::Greater
-            } else if b_deps.as_ref().is_some_and(|b| b.contains_key(a_id)) {
-                Ordering::Less
-            } else {
-                Ordering::Equal
+    /// [CauldronLoader::add_plugin], also rejecting the plugin if `check` fails on its metadata.
+    fn register(
+        &mut self,
+        source: String,
+        metadata: &str,
+        new: impl FnOnce() -> PluginBox,
+        handle: Option<libloading::Library>,
+        check: impl FnOnce(&PluginMetadata) -> Result<(), String>,
+    ) -> bool {
+        if let Err(errors) = validator::validate(metadata) {
+            let reason = errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ");
+            self.reject(source, format!("invalid metadata: {}", reason).as_str());
+            return false;
+        }
+        let metadata = match parse_plugin_metadata(metadata) {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                self.reject(source, error.to_string().as_str());
+                return false;
             }
+        };
+        if let Err(reason) = check(&metadata) {
+            self.reject(source, reason.as_str());
+            return false;
+        }
+        let game_id = self.game.game_type.id();
+        let supported_games = metadata.cauldron.supported_games.as_ref();
+        if let Some(games) = supported_games.filter(|games| !games.contains(&game_id)) {
+            self.reject(
+                source,
+                format!(
+                    "{} does not support {} (supports {})",
+                    metadata.cauldron.id,
+                    game_id,
+                    games.join(", ")
+                )
+                .as_str(),
+            );
+            return false;
+        }
+
+        let install_dir = self
+            .cauldron_dir()
+            .join("plugins")
+            .join(&metadata.cauldron.id);
+        self.plugins.push(PluginContainer {
+            plugin: new(),
+            handle,
+            metadata,
+            install_dir,
         });
+        true
+    }
+
+    fn reject(&mut self, source: String, reason: &str) {
+        log!("Cauldron", "Rejected plugin {}: {}", source, reason);
+        self.report.rejected.push(RejectedPlugin {
+            source,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Resolves the load order and initializes all registered plugins, the outcome is recorded
+    /// in [CauldronLoader::report].
+    pub fn load_plugins(&mut self) -> &LoadReport {
+        match self.sort_and_validate_plugins() {
+            Ok(()) => self.do_plugin_init(),
+            Err(error) => {
+                log!("Cauldron", "{}", error);
+                self.report.error = Some(error);
+            }
+        }
+
+        &self.report
+    }
+
+    #[allow(clippy::result_large_err)] // built at most once per load
+    fn sort_and_validate_plugins(&mut self) -> Result<(), LoadError> {
+        let depends_on = |plugin: &PluginContainer, id: &String| {
+            plugin
+                .metadata
+                .cauldron
+                .dependencies
+                .as_ref()
+                .is_some_and(|deps| deps.contains_key(id))
+        };
 
-        let mut ids = self
+        let metadata = self
             .plugins
             .iter()
-            .map(|p| p.metadata.cauldron.id.clone())
+            .map(|plugin| &plugin.metadata)
             .collect::<Vec<_>>();
-        ids.push(self.game.game_type.id().clone());
-        let mut versions: HashMap<String, Version> = HashMap::new();
-        self.plugins.iter().for_each(|p| {
-            let Ok(version) = Version::parse(p.metadata.cauldron.version.clone().as_str()) else {
-                log!(
-                    "Cauldron",
-                    "{}'s version ({}) does not match semver requirements. exiting...",
-                    p.metadata.cauldron.id,
-                    p.metadata.cauldron.version
-                );
-                message_box(
-                    "cauldron: plugin error",
-                    format!(
-                        "{}'s version ({}) does not match semver requirements.",
-                        p.metadata.cauldron.id, p.metadata.cauldron.version
-                    )
-                    .as_str(),
-                    MB_OK | MB_ICONERROR,
+        let order = resolve::init_order(&metadata)?;
+        let mut plugins = std::mem::take(&mut self.plugins)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.plugins = order
+            .into_iter()
+            .map(|index| plugins[index].take().unwrap())
+            .collect();
+
+        for plugin in &self.plugins {
+            for other in &self.plugins {
+                if depends_on(plugin, &other.metadata.cauldron.id)
+                    && other.metadata.cauldron.init_phase > plugin.metadata.cauldron.init_phase
+                {
+                    return Err(LoadError::InitPhaseConflict {
+                        plugin: plugin.metadata.cauldron.id.clone(),
+                        dependency: other.metadata.cauldron.id.clone(),
+                    });
+                }
+            }
+
+            if let Some(requirement) = plugin.metadata.cauldron.loader_requirement() {
+                let Ok(version_req) = VersionReq::parse(requirement.as_str()) else {
+                    return Err(LoadError::MalformedRequirement {
+                        plugin: plugin.metadata.cauldron.id.clone(),
+                        requirement,
+                    });
+                };
+                let loader_version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+                // pre-release loaders count as the release they lead up to, otherwise nothing
+                // could require `0.1` while the loader is `0.1.0-alpha`.
+                let release = Version::new(
+                    loader_version.major,
+                    loader_version.minor,
+                    loader_version.patch,
                 );
-                std::process::exit(0);
+                if !version_req.matches(&release) {
+                    return Err(LoadError::IncompatibleLoader {
+                        plugin: plugin.metadata.cauldron.id.clone(),
+                        requirement,
+                        found: loader_version,
+                    });
+                }
+            }
+        }
+
+        let mut versions: HashMap<String, Version> = HashMap::new();
+        for plugin in &self.plugins {
+            let Ok(version) = Version::parse(plugin.metadata.cauldron.version.as_str()) else {
+                return Err(LoadError::InvalidVersion {
+                    plugin: plugin.metadata.cauldron.id.clone(),
+                    version: plugin.metadata.cauldron.version.clone(),
+                });
             };
-            versions.insert(p.metadata.cauldron.id.clone(), version);
-        });
+            versions.insert(plugin.metadata.cauldron.id.clone(), version);
+        }
 
         for plugin in &self.plugins {
-            if plugin.metadata.cauldron.dependencies.is_some() {
-                for (dep, constraints) in &plugin.metadata.cauldron.dependencies.clone().unwrap() {
-                    if dep.as_str() == self.game.game_type.id().as_str() {
-                        // todo: validate version requirements for game version
-                        continue;
+            let Some(dependencies) = &plugin.metadata.cauldron.dependencies else {
+                continue;
+            };
+            for (dep, constraints) in dependencies {
+                if dep.as_str() == self.game.game_type.id().as_str() {
+                    // todo: validate version requirements for game version
+                    continue;
+                }
+                if dep.as_str() == LOADER_ID {
+                    // checked along with minimum_cauldron_version above
+                    continue;
+                }
+                let (requirement, optional) = match constraints {
+                    PluginMetadataDependency::Plain(version) => (version, false),
+                    PluginMetadataDependency::Detailed(detailed) => {
+                        (&detailed.version, detailed.optional)
                     }
-                    match constraints {
-                        PluginMetadataDependency::Plain(version) => {
-                            let Ok(version_req) = VersionReq::parse(version.as_str()) else {
-                                log!(
-                                    "Cauldron",
-                                    "Malformed dependency version requirement constraint {} in {}",
-                                    version,
-                                    plugin.metadata.cauldron.id
-                                );
-                                message_box("cauldron: plugin error", format!("malformed dependency version requirement constraint {} in {}.", version, plugin.metadata.cauldron.id).as_str(), MB_OK | MB_ICONERROR);
-                                std::process::exit(0);
-                            };
-
-                            if versions.contains_key(dep) {
-                                if !version_req.matches(&versions[dep]) {
-                                    log!(
-                                        "Cauldron",
-                                        "{} {} {}",
-                                        version_req.to_string(),
-                                        &versions[dep].to_string(),
-                                        dep
-                                    );
-
-                                    log!(
-                                        "Cauldron",
-                                        "Plugin {} is missing dependency {} {} (version mismatch)",
-                                        plugin.metadata.cauldron.id,
-                                        dep,
-                                        version
-                                    );
-                                    message_box("cauldron: plugin error", format!("plugin {} is missing dependency {} {} (version mismatch)", plugin.metadata.cauldron.id, dep, version).as_str(), MB_OK | MB_ICONERROR);
-                                    std::process::exit(0);
-                                }
-                            } else {
-                                log!(
-                                    "Cauldron",
-                                    "Plugin {} is missing dependency {} {}",
-                                    plugin.metadata.cauldron.id,
-                                    dep,
-                                    version
-                                );
-                                message_box(
-                                    "cauldron: plugin error",
-                                    format!(
-                                        "plugin {} is missing dependency {} {}",
-                                        plugin.metadata.cauldron.id, dep, version
-                                    )
-                                    .as_str(),
-                                    MB_OK | MB_ICONERROR,
-                                );
-                                std::process::exit(0);
-                            }
-                        }
-                        PluginMetadataDependency::Detailed(detailed) => {
-                            let Ok(version_req) = VersionReq::parse(detailed.version.as_str())
-                            else {
-                                log!(
-                                    "Cauldron",
-                                    "Malformed dependency version requirement constraint {} in {}",
-                                    detailed.version,
-                                    plugin.metadata.cauldron.id
-                                );
-                                message_box("cauldron: plugin error", format!("malformed dependency version requirement constraint {} in {}.", detailed.version, plugin.metadata.cauldron.id).as_str(), MB_OK | MB_ICONERROR);
-                                std::process::exit(0);
-                            };
-
-                            if versions.contains_key(dep) {
-                                if !version_req.matches(&versions[dep]) {
-                                    log!(
-                                        "Cauldron",
-                                        "Plugin {} is missing dependency {} {} (version mismatch)",
-                                        plugin.metadata.cauldron.id,
-                                        dep,
-                                        detailed.version
-                                    );
-                                    message_box("cauldron: plugin error", format!("plugin {} is missing dependency {} {} (version mismatch)", plugin.metadata.cauldron.id, dep, detailed.version).as_str(), MB_OK | MB_ICONERROR);
-                                    std::process::exit(0);
-                                }
-                            } else if !detailed.optional {
-                                log!(
-                                    "Cauldron",
-                                    "Plugin {} is missing dependency {} ({})",
-                                    plugin.metadata.cauldron.id,
-                                    dep,
-                                    detailed.version
-                                );
-                                message_box(
-                                    "cauldron: plugin error",
-                                    format!(
-                                        "plugin {} is missing dependency {} {}",
-                                        plugin.metadata.cauldron.id, dep, detailed.version
-                                    )
-                                    .as_str(),
-                                    MB_OK | MB_ICONERROR,
-                                );
-                                std::process::exit(0);
-                            }
-                        }
+                };
+                let Ok(version_req) = VersionReq::parse(requirement.as_str()) else {
+                    return Err(LoadError::MalformedRequirement {
+                        plugin: plugin.metadata.cauldron.id.clone(),
+                        requirement: requirement.clone(),
+                    });
+                };
+
+                match versions.get(dep) {
+                    Some(version) if !version_req.matches(version) => {
+                        return Err(LoadError::DependencyVersionMismatch {
+                            plugin: plugin.metadata.cauldron.id.clone(),
+                            dependency: dep.clone(),
+                            requirement: requirement.clone(),
+                            found: version.clone(),
+                        });
                     }
+                    None if !optional => {
+                        return Err(LoadError::MissingDependency {
+                            plugin: plugin.metadata.cauldron.id.clone(),
+                            dependency: dep.clone(),
+                            requirement: requirement.clone(),
+                        });
+                    }
+                    _ => {}
                 }
             }
         }
+
+        for plugin in &self.plugins {
+            let Some(conflicts) = &plugin.metadata.cauldron.conflicts else {
+                continue;
+            };
+            for (conflict, constraints) in conflicts {
+                let (requirement, reason) = match constraints {
+                    PluginMetadataConflict::Plain(version) => (version, None),
+                    PluginMetadataConflict::Detailed(detailed) => {
+                        (&detailed.version, detailed.reason.clone())
+                    }
+                };
+                let Ok(version_req) = VersionReq::parse(requirement.as_str()) else {
+                    return Err(LoadError::MalformedRequirement {
+                        plugin: plugin.metadata.cauldron.id.clone(),
+                        requirement: requirement.clone(),
+                    });
+                };
+
+                match versions.get(conflict) {
+                    Some(version) if version_req.matches(version) => {
+                        return Err(LoadError::Conflict {
+                            plugin: plugin.metadata.cauldron.id.clone(),
+                            conflict: conflict.clone(),
+                            requirement: requirement.clone(),
+                            found: version.clone(),
+                            reason,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
     }
 
     fn do_plugin_init(&mut self) {
         let mut table = tabled::builder::Builder::new();
-        table.push_record(["Order", "Id", "Version", "Name", "Description", "Authors"]);
+        table.push_record([
+            "Order",
+            "Id",
+            "Version",
+            "Name",
+            "Description",
+            "Authors",
+            "Requires",
+        ]);
         self.plugins.iter().enumerate().for_each(|(index, plugin)| {
             let mut name = String::new();
             let mut description = String::new();
@@ -386,6 +531,12 @@ impl CauldronLoader {
                 format!("{}", name),
                 format!("{}", description),
                 format!("{}", authors),
+                plugin
+                    .metadata
+                    .cauldron
+                    .loader_requirement()
+                    .map(|requirement| format!("cauldron {}", requirement))
+                    .unwrap_or_default(),
             ]);
         });
         log!(
@@ -395,30 +546,68 @@ impl CauldronLoader {
             table.build()
         );
 
+        self.report.plugins = self
+            .plugins
+            .iter()
+            .map(|plugin| PluginReport {
+                id: plugin.metadata.cauldron.id.clone(),
+                version: plugin.metadata.cauldron.version.clone(),
+                loader_requirement: plugin.metadata.cauldron.loader_requirement(),
+                initialized: false,
+            })
+            .collect();
+
         log!("Cauldron", "initializing plugins...");
-        match unsafe { MH_Initialize() } {
-            MH_STATUS::MH_ERROR_ALREADY_INITIALIZED | MH_STATUS::MH_OK => {}
-            status @ MH_STATUS::MH_ERROR_MEMORY_ALLOC => panic!("MH_Initialize: {status:?}"),
-            _ => unreachable!(),
-        }
+        self.hook_service.initialize();
 
         self.plugins.iter().for_each(|plugin| {
-            plugin.plugin.on_init(&self);
+            plugin.plugin.on_init(&PluginContext::new(self, plugin));
         });
+        self.report
+            .plugins
+            .iter_mut()
+            .for_each(|plugin| plugin.initialized = true);
 
-        unsafe {
-            // enable all
-            MH_EnableHook(std::ptr::null_mut())
-                .ok()
-                .expect("cauldron: failed to queue enable hooks");
-            MH_ApplyQueued()
-                .ok()
-                .expect("cauldron: failed to apply queued hooks");
-        };
+        self.hook_service.apply();
         log!("Cauldron", "Plugins initialized.");
+
+        self.report.patches = PatchRegistry::global().entries().to_vec();
+        log_patches(&self.report);
+    }
+}
+
+fn log_patches(report: &LoadReport) {
+    for entry in &report.patches {
+        let patch = &entry.patch;
+        if let Some(error) = &entry.error {
+            log!(
+                "Cauldron",
+                "Patch {} by {} failed: {}.",
+                patch.name,
+                patch.owner,
+                error
+            );
+        } else if entry.enabled {
+            log!(
+                "Cauldron",
+                "Patched {} bytes at {:#x} for {} ({}).",
+                patch.bytes.len(),
+                patch.address,
+                patch.name,
+                patch.owner
+            );
+        } else {
+            log!(
+                "Cauldron",
+                "Patch {} by {} is disabled.",
+                patch.name,
+                patch.owner
+            );
+        }
     }
 }
 
+/// Exports a plugin with handwritten metadata, see [cauldron_plugin] for generating it instead.
 #[macro_export]
 macro_rules! define_cauldron_plugin {
     ($plugin:ty, $meta:expr) => {
@@ -440,15 +629,16 @@ macro_rules! define_cauldron_plugin {
 }
 
 #[doc(hidden)]
+#[cfg(windows)]
 static mut INSTANCE: OnceCell<CauldronLoader> = OnceCell::new();
 
 #[doc(hidden)]
-#[cfg(feature = "nixxes")]
+#[cfg(all(feature = "nixxes", windows))]
 static NIXXES_PRINTLN: OnceCell<unsafe extern "C" fn(*mut NxLogImpl, *const c_char)> =
     OnceCell::new();
 
 #[doc(hidden)]
-#[cfg(feature = "nixxes")]
+#[cfg(all(feature = "nixxes", windows))]
 unsafe fn nxlogimpl_println_impl(this: *mut NxLogImpl, text: *const c_char) {
     unsafe {
         // strip nixxes log prefix eg "01:40:32:458 (00041384) > "
@@ -467,46 +657,216 @@ unsafe fn nxlogimpl_println_impl(this: *mut NxLogImpl, text: *const c_char) {
     }
 }
 
+/// Loads the signatures built into libdecima merged with `cauldron/signatures/<game id>.toml` if
+/// it exists, so offsets can be fixed or added for a game update without a new release, then
+/// checks them all.
+#[cfg(windows)]
+fn load_signatures() -> SignatureReport {
+    let Some(game_type) = CauldronGameType::find_from_exe() else {
+        return SignatureReport::default();
+    };
+    let mut database = SignatureDatabase::builtin(&game_type.id());
+    let path = util::game_root()
+        .join("cauldron")
+        .join("signatures")
+        .join(format!("{}.toml", game_type.id()));
+    if let Ok(source) = fs::read_to_string(&path) {
+        match SignatureDatabase::parse(&source) {
+            Ok(signatures) => match &mut database {
+                Some(database) => database.merge(signatures),
+                None => database = Some(signatures),
+            },
+            Err(error) => {
+                log!(
+                    "Cauldron",
+                    "Ignoring invalid signatures {}: {}",
+                    path.display(),
+                    error.message()
+                );
+            }
+        }
+    }
+    let Some(database) = database else {
+        log!("Cauldron", "No signatures for {}.", game_type);
+        return SignatureReport::default();
+    };
+    Offsets::load(&database, &version::version().to_string());
+    check_signatures()
+}
+
+/// Checks the loaded signatures, reusing the offsets a previous launch of the same executable
+/// cached in `cauldron/cache`. A game update changes the executable's hash, so everything is
+/// scanned for again.
+#[cfg(windows)]
+fn check_signatures() -> SignatureReport {
+    let executable = match current_exe().and_then(fs::read) {
+        Ok(executable) => OffsetCache::hash(&executable),
+        Err(error) => {
+            log!(
+                "Cauldron",
+                "Not caching offsets, can't read the executable: {}",
+                error
+            );
+            return Offsets::check();
+        }
+    };
+    let dir = util::game_root().join("cauldron").join("cache");
+    let mut cache = OffsetCache::load(&dir, &executable);
+    let cached = cache.clone();
+    let report = Offsets::check_cached(&mut cache);
+    if cache == cached {
+        return report;
+    }
+    if let Err(error) = cache.save(&dir) {
+        log!("Cauldron", "Failed to cache offsets: {}", error);
+    }
+    report
+}
+
+#[cfg(windows)]
+fn log_signatures(report: &SignatureReport) {
+    log!(
+        "Cauldron",
+        "Found {}/{} signatures.",
+        report.checks.len() - report.failed().count(),
+        report.checks.len()
+    );
+    for check in report.failed() {
+        if let Err(error) = &check.result {
+            log!("Cauldron", "Signature {} failed: {}.", check.name, error);
+        }
+    }
+    for check in report.ambiguous() {
+        log!(
+            "Cauldron",
+            "Signature {} matched {} times, using the first match.",
+            check.name,
+            check.matches
+        );
+    }
+}
+
+/// Applies the built-in patches for the game, those of libdecima merged with
+/// `cauldron/patches/<game id>.toml` if it exists, skipping the ones turned off in `[patches]` of
+/// `cauldron.toml`.
+#[cfg(windows)]
+fn apply_builtin_patches(config: &CauldronConfig) -> Vec<BuiltinPatchStatus> {
+    let Some(game_type) = CauldronGameType::find_from_exe() else {
+        return Vec::new();
+    };
+    let mut database = PatchDatabase::builtin(&game_type.id()).unwrap_or_else(|| PatchDatabase {
+        game: game_type.id(),
+        patches: Vec::new(),
+    });
+    let path = util::game_root()
+        .join("cauldron")
+        .join("patches")
+        .join(format!("{}.toml", game_type.id()));
+    if let Ok(source) = fs::read_to_string(&path) {
+        match PatchDatabase::parse(&source) {
+            Ok(patches) => database.merge(patches),
+            Err(error) => {
+                log!(
+                    "Cauldron",
+                    "Ignoring invalid patches {}: {}",
+                    path.display(),
+                    error.message()
+                );
+            }
+        }
+    }
+    // the signatures point at the game code the patches were written for.
+    unsafe {
+        database.apply(
+            &mut PatchRegistry::global(),
+            &version::version().to_string(),
+            |patch| {
+                config
+                    .patches
+                    .get(&patch.name)
+                    .copied()
+                    .unwrap_or(patch.enabled)
+            },
+            |signature| Offsets::find(signature).map(|offset| offset.as_ptr::<u8>() as usize),
+        )
+    }
+}
+
+#[cfg(windows)]
+fn log_builtin_patches(patches: &[BuiltinPatchStatus]) {
+    for patch in patches {
+        match &patch.state {
+            BuiltinPatchState::Applied => {
+                log!(
+                    "Cauldron",
+                    "Applied patch {}: {}",
+                    patch.name,
+                    patch.description
+                );
+            }
+            BuiltinPatchState::Disabled => {
+                log!("Cauldron", "Patch {} is disabled.", patch.name);
+            }
+            BuiltinPatchState::Failed(error) => {
+                log!("Cauldron", "Patch {} failed: {}.", patch.name, error);
+            }
+        }
+    }
+}
+
+/// Whether every signature `feature` depends on was found, warning that it's disabled if not.
+#[cfg(all(feature = "nixxes", windows))]
+fn feature_available(report: &SignatureReport, feature: &str, signatures: &[&str]) -> bool {
+    let missing = report.missing(signatures);
+    if !missing.is_empty() {
+        log!(
+            "Cauldron",
+            "Disabling {}, missing signatures: {}.",
+            feature,
+            missing.join(", ")
+        );
+    }
+    missing.is_empty()
+}
+
 #[doc(hidden)]
+#[cfg(windows)]
 pub unsafe fn handle_dll_attach() {
     unsafe {
         std::thread::spawn(|| {
-            patch_reporting_loggers();
+            let signatures = load_signatures();
+            let config = load_config();
+            let patches = apply_builtin_patches(&config);
 
             #[cfg(feature = "nixxes")]
-            {
-                let log =
-                    NxLogImpl::__vftable(NxLogImpl::get_instance().unwrap() as *const _ as *mut _);
+            if feature_available(&signatures, "the Nixxes log hook", &["nx::NxLogImpl::Instance"]) {
+                match NxLogImpl::get_instance() {
+                    Some(instance) => {
+                        let log = NxLogImpl::__vftable(instance as *const _ as *mut _);
+
+                        CurrentPlatform::initialize_hooks();
+
+                        let nxlogimpl_println =
+                            MhHook::new(log.fn_println as *mut _, nxlogimpl_println_impl as *mut _)
+                                .unwrap();
+
+                        NIXXES_PRINTLN
+                            .set(std::mem::transmute(nxlogimpl_println.trampoline()))
+                            .unwrap();
 
-                match MH_Initialize() {
-                    MH_STATUS::MH_ERROR_ALREADY_INITIALIZED | MH_STATUS::MH_OK => {}
-                    status @ MH_STATUS::MH_ERROR_MEMORY_ALLOC => {
-                        panic!("MH_Initialize: {status:?}")
+                        CurrentPlatform::apply_hooks();
+                    }
+                    None => {
+                        log!(
+                            "Cauldron",
+                            "Disabling the Nixxes log hook, the log hasn't been created yet."
+                        );
                     }
-                    _ => unreachable!(),
                 }
-
-                let nxlogimpl_println =
-                    MhHook::new(log.fn_println as *mut _, nxlogimpl_println_impl as *mut _)
-                        .unwrap();
-
-                NIXXES_PRINTLN
-                    .set(std::mem::transmute(nxlogimpl_println.trampoline()))
-                    .unwrap();
-
-                // enable all
-                MH_EnableHook(std::ptr::null_mut())
-                    .ok()
-                    .expect("cauldron: failed to queue enable hooks");
-                MH_ApplyQueued()
-                    .ok()
-                    .expect("cauldron: failed to apply queued hooks");
             }
-            let config = load_config();
             let mut loggers: Vec<Box<dyn SharedLogger>> = Vec::new();
             if config.logging.show_console {
-                AllocConsole();
-                AttachConsole(ATTACH_PARENT_PROCESS);
+                CurrentPlatform::attach_console();
 
                 loggers.push(simplelog::TermLogger::new(
                     config.logging.console_level.to_log(),
@@ -518,7 +878,7 @@ pub unsafe fn handle_dll_attach() {
             loggers.push(simplelog::WriteLogger::new(
                 config.logging.file_level.to_log(),
                 Config::default(),
-                File::create(config.logging.file_path).unwrap(),
+                File::create(util::game_root().join(config.logging.file_path)).unwrap(),
             ));
             simplelog::CombinedLogger::init(loggers).unwrap();
             let Some(game_type) = CauldronGameType::find_from_exe() else {
@@ -535,7 +895,6 @@ pub unsafe fn handle_dll_attach() {
                 message_box(
                     "Game Unknown",
                     "Cauldron as detected an unknown game type and will now exit.",
-                    MB_OK | MB_ICONERROR,
                 );
                 std::process::exit(0);
             };
@@ -546,18 +905,37 @@ pub unsafe fn handle_dll_attach() {
                 env!("CARGO_PKG_VERSION"),
                 game_type.id()
             );
+            log_signatures(&signatures);
+            log_builtin_patches(&patches);
 
             #[cfg(feature = "nixxes")]
-            focus::internal::attach();
+            if feature_available(
+                &signatures,
+                "the focus overlay",
+                &["nx::NxDXGIImpl::fn_present", "nx::NxD3DImpl::Instance"],
+            ) {
+                focus::internal::attach();
+            }
 
             #[allow(static_mut_refs)]
             INSTANCE.get_or_init(|| {
                 let mut instance = CauldronLoader::new();
+                instance.report.signatures = signatures;
                 let paths = instance.try_find_plugins();
                 for path in paths {
-                    instance.try_load_plugin(&path);
+                    if path
+                        .extension()
+                        .is_some_and(|ext| ext == package::EXTENSION)
+                    {
+                        instance.try_load_package(&path);
+                    } else {
+                        instance.try_load_plugin(&path);
+                    }
+                }
+                if let Some(error) = &instance.load_plugins().error {
+                    message_box("cauldron: plugin error", error.to_string().as_str());
+                    std::process::exit(0);
                 }
-                instance.do_plugin_init();
 
                 instance
             });
@@ -565,7 +943,20 @@ pub unsafe fn handle_dll_attach() {
     }
 }
 
+/// `process_terminating` is whether `DllMain` got a non-null `lpReserved`, the process is exiting
+/// and nothing needs undoing.
 #[doc(hidden)]
-pub unsafe fn handle_dll_detach() {
-    // todo
+pub unsafe fn handle_dll_detach(process_terminating: bool) {
+    if process_terminating {
+        return;
+    }
+    // todo: deinit and unload plugins, the game keeps running without their patches.
+    // the loader lock is held here, waiting on a thread that may need it would deadlock.
+    let Some(mut registry) = PatchRegistry::try_global() else {
+        log!("Cauldron", "Not reverting patches, the registry is in use.");
+        return;
+    };
+    for error in registry.revert_all() {
+        log!("Cauldron", "Failed to revert: {}.", error);
+    }
 }
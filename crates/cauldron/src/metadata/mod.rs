@@ -0,0 +1,258 @@
+pub mod schema;
+pub mod validator;
+
+pub use cauldron_ids::LOADER_ID;
+
+use crate::metadata::schema::{ID_PATTERN, SEMVER_PATTERN};
+use crate::version::CauldronGameType;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use toml::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum StringOrStringVec {
+    String(String),
+    Vec(Vec<String>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ContributorsList {
+    Plain(Vec<String>),
+    WithRoles(HashMap<String, StringOrStringVec>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataV0 {
+    #[schemars(extend("const" = 0))]
+    pub schema_version: u32,
+    pub cauldron: PluginMetadataCauldron,
+
+    #[serde(flatten)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataCauldron {
+    #[schemars(regex(pattern = ID_PATTERN), extend("not" = { "const": LOADER_ID }))]
+    pub id: String,
+    #[schemars(regex(pattern = SEMVER_PATTERN))]
+    pub version: String,
+    pub metadata: Option<PluginMetadataCauldronMetadata>,
+    #[schemars(extend("propertyNames" = { "pattern": ID_PATTERN }))]
+    pub dependencies: Option<HashMap<String, PluginMetadataDependency>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataCauldronMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub contributors: Option<ContributorsList>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PluginMetadataDependency {
+    Plain(String),
+    Detailed(PluginMetadataDetailedDependency),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataDetailedDependency {
+    pub version: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginMetadataSchemaVersionOnly {
+    pub schema_version: u32,
+}
+
+/// The metadata version the loader works with, older versions are migrated to this on load.
+pub type PluginMetadata = PluginMetadataV1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataV1 {
+    #[schemars(extend("const" = 1))]
+    pub schema_version: u32,
+    pub cauldron: PluginMetadataCauldronV1,
+
+    #[serde(flatten)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataCauldronV1 {
+    #[schemars(regex(pattern = ID_PATTERN), extend("not" = { "const": LOADER_ID }))]
+    pub id: String,
+    #[schemars(regex(pattern = SEMVER_PATTERN))]
+    pub version: String,
+    pub metadata: Option<PluginMetadataCauldronMetadataV1>,
+    #[schemars(extend("propertyNames" = { "pattern": ID_PATTERN }))]
+    pub dependencies: Option<HashMap<String, PluginMetadataDependency>>,
+    /// Plugins that can't be loaded alongside this one.
+    #[schemars(extend("propertyNames" = { "pattern": ID_PATTERN }))]
+    pub conflicts: Option<HashMap<String, PluginMetadataConflict>>,
+    /// Plugins that should be initialized after this one, if they are present.
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = ID_PATTERN)))]
+    pub load_before: Vec<String>,
+    /// Plugins that should be initialized before this one, if they are present.
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = ID_PATTERN)))]
+    pub load_after: Vec<String>,
+    /// Game ids this plugin can run on, any game if unset.
+    #[schemars(with = "Option<Vec<CauldronGameType>>")]
+    pub supported_games: Option<Vec<String>>,
+    /// Oldest cauldron release this plugin works with.
+    #[schemars(regex(pattern = SEMVER_PATTERN))]
+    pub minimum_cauldron_version: Option<String>,
+    #[serde(default)]
+    pub init_phase: PluginInitPhase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataCauldronMetadataV1 {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub contributors: Option<ContributorsList>,
+    /// SPDX license expression.
+    pub license: Option<String>,
+    #[schemars(url)]
+    pub homepage: Option<String>,
+    #[schemars(url)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PluginMetadataConflict {
+    Plain(String),
+    Detailed(PluginMetadataDetailedConflict),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginMetadataDetailedConflict {
+    pub version: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl PluginMetadataCauldronV1 {
+    /// The loader versions this plugin accepts, combining `minimum_cauldron_version` and a
+    /// dependency on [LOADER_ID]. `None` if it doesn't care.
+    pub fn loader_requirement(&self) -> Option<String> {
+        let minimum = self
+            .minimum_cauldron_version
+            .as_ref()
+            .map(|minimum| format!(">={}", minimum));
+        let dependency = self
+            .dependencies
+            .as_ref()
+            .and_then(|deps| deps.get(LOADER_ID))
+            .map(|dependency| match dependency {
+                PluginMetadataDependency::Plain(version) => version.clone(),
+                PluginMetadataDependency::Detailed(detailed) => detailed.version.clone(),
+            });
+        let requirements = minimum.into_iter().chain(dependency).collect::<Vec<_>>();
+        (!requirements.is_empty()).then(|| requirements.join(", "))
+    }
+}
+
+/// When a plugin is initialized relative to others, dependencies are always initialized first
+/// so a plugin can't be in an earlier phase than something it depends on.
+#[derive(
+    Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginInitPhase {
+    /// For plugins that set up things other plugins use during their init.
+    Early,
+    #[default]
+    Default,
+    Late,
+}
+
+impl From<PluginMetadataV0> for PluginMetadataV1 {
+    fn from(value: PluginMetadataV0) -> Self {
+        let cauldron = value.cauldron;
+        // v0 plugins could only limit which game they run on by depending on it.
+        let supported_games = cauldron.dependencies.as_ref().and_then(|deps| {
+            let games = deps
+                .keys()
+                .filter(|id| CauldronGameType::from_id(id).is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            (!games.is_empty()).then_some(games)
+        });
+
+        PluginMetadataV1 {
+            schema_version: 1,
+            cauldron: PluginMetadataCauldronV1 {
+                id: cauldron.id,
+                version: cauldron.version,
+                metadata: cauldron
+                    .metadata
+                    .map(|metadata| PluginMetadataCauldronMetadataV1 {
+                        name: metadata.name,
+                        description: metadata.description,
+                        contributors: metadata.contributors,
+                        license: None,
+                        homepage: None,
+                        source: None,
+                    }),
+                dependencies: cauldron.dependencies,
+                conflicts: None,
+                load_before: Vec::new(),
+                load_after: Vec::new(),
+                supported_games,
+                minimum_cauldron_version: None,
+                init_phase: PluginInitPhase::Default,
+            },
+            extra: value.extra,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MetadataError {
+    Parse(toml::de::Error),
+    UnsupportedSchemaVersion(u32),
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::Parse(error) => write!(f, "failed to parse plugin metadata: {}", error),
+            MetadataError::UnsupportedSchemaVersion(version) => {
+                write!(f, "unsupported metadata schema version {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MetadataError {}
+
+impl From<toml::de::Error> for MetadataError {
+    fn from(value: toml::de::Error) -> Self {
+        MetadataError::Parse(value)
+    }
+}
+
+/// Parses plugin metadata of any supported schema version, migrating it to the latest.
+pub fn parse_plugin_metadata(metadata: &str) -> Result<PluginMetadata, MetadataError> {
+    let schema = toml::from_str::<PluginMetadataSchemaVersionOnly>(metadata)?;
+    match schema.schema_version {
+        0 => Ok(toml::from_str::<PluginMetadataV0>(metadata)?.into()),
+        1 => Ok(toml::from_str::<PluginMetadataV1>(metadata)?),
+        version => Err(MetadataError::UnsupportedSchemaVersion(version)),
+    }
+}
@@ -0,0 +1,143 @@
+//! JSON schemas for plugin metadata, generated from the types in [crate::metadata].
+//!
+//! The files in `schemas/` are written by `cargo xtask schemas`, `tests/metadata_schema.rs` fails
+//! if they're out of date.
+
+use crate::metadata::{PluginMetadataV0, PluginMetadataV1};
+use crate::version::CauldronGameType;
+use schemars::generate::SchemaSettings;
+use schemars::transform::RecursiveTransform;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Where the schemas are published, schemas reference each other through this.
+pub const SCHEMA_BASE_URL: &str =
+    "https://raw.githubusercontent.com/JustPyrrha/cauldron/main/schemas";
+
+/// Plugin ids and dependency keys, see [crate::metadata::validator::is_valid_id].
+pub const ID_PATTERN: &str = "^[a-z][a-z0-9-_]{1,63}$";
+
+/// Strict semver, from <https://semver.org/#is-there-a-suggested-regular-expression-regex-to-check-a-semver-string>.
+pub const SEMVER_PATTERN: &str = r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-((?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+([0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$";
+
+impl JsonSchema for CauldronGameType {
+    fn schema_name() -> Cow<'static, str> {
+        "CauldronGameType".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let ids = CauldronGameType::ALL
+            .iter()
+            .map(CauldronGameType::id)
+            .collect::<Vec<_>>();
+        json_schema!({
+            "type": "string",
+            "enum": ids,
+        })
+    }
+}
+
+/// Every schema file, as `(file name, schema)`.
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        ("cauldron-main.json", main_schema()),
+        ("cauldron-v0.json", v0_schema()),
+        (
+            "cauldron-v1.json",
+            versioned_schema::<PluginMetadataV1>(
+                "cauldron-v1.json",
+                "Cauldron Plugin Metadata v1",
+                "Current version of the Cauldron plugin metadata. v0 metadata is migrated to this when loaded.",
+            ),
+        ),
+    ]
+}
+
+/// Serializes a schema the way it's stored in `schemas/`.
+pub fn to_file_contents(schema: &Schema) -> String {
+    serde_json::to_string_pretty(schema).unwrap() + "\n"
+}
+
+fn v0_schema() -> Schema {
+    let mut schema = versioned_schema::<PluginMetadataV0>(
+        "cauldron-v0.json",
+        "Cauldron Plugin Metadata v0",
+        "Initial version of the Cauldron plugin metadata, superseded by v1. Loaders migrate v0 metadata to v1.",
+    );
+    schema.insert(
+        "$comment".to_string(),
+        "the initial draft of this is heavily inspired by <https://github.com/QuiltMC/quilt-json-schema> <3".into(),
+    );
+    schema
+}
+
+fn versioned_schema<T: JsonSchema>(file_name: &str, title: &str, description: &str) -> Schema {
+    let mut schema = SchemaSettings::draft2020_12()
+        .with_transform(RecursiveTransform(remove_null))
+        .into_generator()
+        .into_root_schema_for::<T>();
+    schema.insert(
+        "$id".to_string(),
+        format!("{}/{}", SCHEMA_BASE_URL, file_name).into(),
+    );
+    schema.insert("title".to_string(), title.into());
+    schema.insert("description".to_string(), description.into());
+    schema
+}
+
+/// TOML has no null, an `Option` is just a field that can be left out.
+fn remove_null(schema: &mut Schema) {
+    let is_null = |value: &Value| value.get("type").is_some_and(|t| t == "null");
+
+    if let Some(Value::Array(types)) = schema.get_mut("type") {
+        types.retain(|t| t != "null");
+        if types.len() == 1 {
+            let single = types.remove(0);
+            schema.insert("type".to_string(), single);
+        }
+    }
+    if schema.get("default").is_some_and(Value::is_null) {
+        schema.remove("default");
+    }
+
+    let Some(Value::Array(variants)) = schema.get_mut("anyOf") else {
+        return;
+    };
+    variants.retain(|variant| !is_null(variant));
+    if variants.len() == 1 {
+        let Some(Value::Object(variant)) = variants.pop() else {
+            return;
+        };
+        schema.remove("anyOf");
+        for (key, value) in variant {
+            schema.insert(key, value);
+        }
+    }
+}
+
+fn main_schema() -> Schema {
+    let version = |version: u32| {
+        json_schema!({
+            "allOf": [
+                { "properties": { "schema_version": { "const": version } } },
+                { "$ref": format!("{}/cauldron-v{}.json", SCHEMA_BASE_URL, version) },
+            ]
+        })
+    };
+
+    json_schema!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("{}/cauldron-main.json", SCHEMA_BASE_URL),
+        "title": "Cauldron Plugin Metadata (Version Agnostic)",
+        "type": "object",
+        "properties": {
+            "schema_version": {
+                "enum": [0, 1],
+                "default": 1,
+            }
+        },
+        "oneOf": [version(0), version(1)],
+        "required": ["schema_version"],
+    })
+}
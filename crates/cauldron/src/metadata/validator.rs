@@ -0,0 +1,288 @@
+//! Checks raw plugin metadata against the rules in `schemas/`, so plugins are rejected with
+//! every problem listed instead of just the first one serde trips over.
+//!
+//! The checks here are kept in step with the JSON schemas, `tests/metadata_schema.rs` runs both
+//! over the same set of files.
+
+pub use cauldron_ids::is_valid_id;
+
+use crate::metadata::LOADER_ID;
+use crate::version::CauldronGameType;
+use semver::Version;
+use std::fmt::{Display, Formatter};
+use toml::{Table, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Location of the offending field, e.g. `cauldron.dependencies.base.version`. Empty if the
+    /// problem is with the document as a whole.
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Validates the contents of a `.cauldron.toml`, returning every problem found.
+pub fn validate(metadata: &str) -> Result<(), Vec<ValidationError>> {
+    let document = match metadata.parse::<Table>() {
+        Ok(document) => document,
+        Err(error) => {
+            return Err(vec![ValidationError {
+                path: String::new(),
+                message: format!("invalid toml: {}", error.message()),
+            }]);
+        }
+    };
+
+    let errors = validate_table(&document);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validates an already parsed metadata document.
+pub fn validate_table(document: &Table) -> Vec<ValidationError> {
+    let mut validator = Validator::default();
+    validator.document(document);
+    validator.errors
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn document(&mut self, document: &Table) {
+        let schema_version = match document.get("schema_version") {
+            None => {
+                self.error("schema_version", "missing required field");
+                None
+            }
+            Some(Value::Integer(version @ (0 | 1))) => Some(*version),
+            Some(Value::Integer(version)) => {
+                self.error(
+                    "schema_version",
+                    format!("unsupported schema version {}", version),
+                );
+                None
+            }
+            Some(value) => {
+                self.expected("schema_version", "an integer", value);
+                None
+            }
+        };
+
+        match document.get("cauldron") {
+            None => self.error("cauldron", "missing required field"),
+            Some(Value::Table(cauldron)) => {
+                // without a known schema version only the fields shared by every version can be
+                // checked.
+                self.cauldron(cauldron, schema_version.unwrap_or(0));
+            }
+            Some(value) => self.expected("cauldron", "a table", value),
+        }
+    }
+
+    fn cauldron(&mut self, cauldron: &Table, schema_version: i64) {
+        match cauldron.get("id") {
+            None => self.error("cauldron.id", "missing required field"),
+            Some(Value::String(id)) if id == LOADER_ID => self.error(
+                "cauldron.id",
+                format!("{} is reserved for the loader", LOADER_ID),
+            ),
+            Some(id) => self.id("cauldron.id", id),
+        }
+        match cauldron.get("version") {
+            None => self.error("cauldron.version", "missing required field"),
+            Some(version) => self.version("cauldron.version", version),
+        }
+        if let Some(metadata) = cauldron.get("metadata") {
+            self.metadata(metadata, schema_version);
+        }
+        if let Some(dependencies) = cauldron.get("dependencies") {
+            self.requirements("cauldron.dependencies", dependencies, true);
+        }
+
+        if schema_version < 1 {
+            return;
+        }
+
+        if let Some(conflicts) = cauldron.get("conflicts") {
+            self.requirements("cauldron.conflicts", conflicts, false);
+        }
+        for field in ["load_before", "load_after"] {
+            if let Some(ids) = cauldron.get(field) {
+                let path = format!("cauldron.{}", field);
+                self.array(&path, ids, |validator, path, id| validator.id(path, id));
+            }
+        }
+        if let Some(games) = cauldron.get("supported_games") {
+            self.array(
+                "cauldron.supported_games",
+                games,
+                |validator, path, game| match game {
+                    Value::String(game) if CauldronGameType::from_id(game).is_some() => {}
+                    Value::String(game) => validator.error(path, format!("unknown game {}", game)),
+                    value => validator.expected(path, "a string", value),
+                },
+            );
+        }
+        if let Some(version) = cauldron.get("minimum_cauldron_version") {
+            self.version("cauldron.minimum_cauldron_version", version);
+        }
+        match cauldron.get("init_phase") {
+            None => {}
+            Some(Value::String(phase))
+                if matches!(phase.as_str(), "early" | "default" | "late") => {}
+            Some(Value::String(phase)) => self.error(
+                "cauldron.init_phase",
+                format!(
+                    "unknown init phase {}, expected one of early, default or late",
+                    phase
+                ),
+            ),
+            Some(value) => self.expected("cauldron.init_phase", "a string", value),
+        }
+    }
+
+    fn metadata(&mut self, metadata: &Value, schema_version: i64) {
+        let Value::Table(metadata) = metadata else {
+            return self.expected("cauldron.metadata", "a table", metadata);
+        };
+
+        let mut strings = vec!["name", "description"];
+        if schema_version >= 1 {
+            strings.extend(["license", "homepage", "source"]);
+        }
+        for field in strings {
+            if let Some(value) = metadata.get(field) {
+                self.string(&format!("cauldron.metadata.{}", field), value);
+            }
+        }
+
+        match metadata.get("contributors") {
+            None => {}
+            Some(contributors @ Value::Array(_)) => {
+                self.array("cauldron.metadata.contributors", contributors, Self::string);
+            }
+            Some(Value::Table(contributors)) => {
+                for (name, roles) in contributors {
+                    let path = format!("cauldron.metadata.contributors.{}", name);
+                    match roles {
+                        Value::String(_) => {}
+                        Value::Array(_) => self.array(&path, roles, Self::string),
+                        value => self.expected(&path, "a role or a list of roles", value),
+                    }
+                }
+            }
+            Some(value) => self.expected(
+                "cauldron.metadata.contributors",
+                "a list of names or a table of names to roles",
+                value,
+            ),
+        }
+    }
+
+    /// Shared by dependencies and conflicts, only dependencies can be optional.
+    fn requirements(&mut self, path: &str, requirements: &Value, dependencies: bool) {
+        let Value::Table(requirements) = requirements else {
+            return self.expected(path, "a table", requirements);
+        };
+
+        for (id, requirement) in requirements {
+            let path = format!("{}.{}", path, id);
+            if !is_valid_id(id) {
+                self.error(&path, format!("{} is not a valid plugin id", id));
+            }
+            match requirement {
+                Value::String(_) => {}
+                Value::Table(detailed) => {
+                    match detailed.get("version") {
+                        None => self.error(&format!("{}.version", path), "missing required field"),
+                        Some(version) => self.string(&format!("{}.version", path), version),
+                    }
+                    if let Some(reason) = detailed.get("reason") {
+                        self.string(&format!("{}.reason", path), reason);
+                    }
+                    match detailed.get("optional") {
+                        Some(optional) if dependencies && !optional.is_bool() => {
+                            self.expected(&format!("{}.optional", path), "a boolean", optional)
+                        }
+                        _ => {}
+                    }
+                }
+                value => self.expected(&path, "a version requirement or a table", value),
+            }
+        }
+    }
+
+    fn id(&mut self, path: &str, id: &Value) {
+        match id {
+            Value::String(id) if is_valid_id(id) => {}
+            Value::String(id) => self.error(
+                path,
+                format!(
+                    "{} is not a valid id, ids must be 2-64 characters of a-z, 0-9, - or _ and start with a letter",
+                    id
+                ),
+            ),
+            value => self.expected(path, "a string", value),
+        }
+    }
+
+    fn version(&mut self, path: &str, version: &Value) {
+        match version {
+            Value::String(version) => {
+                if let Err(error) = Version::parse(version) {
+                    self.error(
+                        path,
+                        format!("{} is not a valid semver version ({})", version, error),
+                    );
+                }
+            }
+            value => self.expected(path, "a string", value),
+        }
+    }
+
+    fn string(&mut self, path: &str, value: &Value) {
+        if !value.is_str() {
+            self.expected(path, "a string", value);
+        }
+    }
+
+    fn array(&mut self, path: &str, value: &Value, item: impl Fn(&mut Self, &str, &Value)) {
+        let Value::Array(items) = value else {
+            return self.expected(path, "an array", value);
+        };
+        for (index, value) in items.iter().enumerate() {
+            item(self, &format!("{}[{}]", path, index), value);
+        }
+    }
+
+    fn expected(&mut self, path: &str, expected: &str, found: &Value) {
+        self.error(
+            path,
+            format!("expected {}, found {}", expected, found.type_str()),
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! `.cauldron` packages, a zip of a plugin library and its assets plus a manifest listing the
+//! SHA-256 hash of every file.
+//!
+//! A package is verified as a whole before anything is written, then extracted into
+//! `cauldron/packages/<id>/<version>` and loaded from there. Packages with missing, extra or
+//! modified files are rejected.
+
+use crate::metadata::validator::is_valid_id;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// File extension of packages, without the dot.
+pub const EXTENSION: &str = "cauldron";
+
+/// Name of the manifest at the root of every package.
+pub const MANIFEST_NAME: &str = "cauldron-package.toml";
+
+pub const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub manifest_version: u32,
+    /// Id of the plugin in the package, has to match the library's metadata.
+    pub id: String,
+    pub version: String,
+    /// Path of the plugin library inside the package, also listed in `files`.
+    pub library: String,
+    /// Every file in the package apart from the manifest, mapped to its lowercase hex SHA-256.
+    pub files: BTreeMap<String, String>,
+}
+
+impl PackageManifest {
+    fn validate(&self) -> Result<(), PackageError> {
+        if self.manifest_version != MANIFEST_VERSION {
+            return Err(PackageError::UnsupportedManifestVersion(
+                self.manifest_version,
+            ));
+        }
+        if !is_valid_id(&self.id) {
+            return Err(PackageError::InvalidManifest(format!(
+                "{} is not a valid plugin id",
+                self.id
+            )));
+        }
+        if Version::parse(&self.version).is_err() {
+            return Err(PackageError::InvalidManifest(format!(
+                "{} is not a valid version",
+                self.version
+            )));
+        }
+        if !self.files.contains_key(&self.library) {
+            return Err(PackageError::InvalidManifest(format!(
+                "library {} is not listed in files",
+                self.library
+            )));
+        }
+        match self.files.keys().find(|path| !is_package_path(path)) {
+            Some(path) => Err(PackageError::InvalidPath(path.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PackageError {
+    Io(io::Error),
+    Zip(ZipError),
+    MissingManifest,
+    InvalidManifest(String),
+    UnsupportedManifestVersion(u32),
+    /// A path that could end up outside the package directory.
+    InvalidPath(String),
+    /// Listed in the manifest but not in the package, usually a partial download.
+    MissingFile(String),
+    /// In the package but not in the manifest.
+    UnlistedFile(String),
+    HashMismatch(String),
+}
+
+impl Display for PackageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageError::Io(error) => write!(f, "{}", error),
+            PackageError::Zip(error) => write!(f, "failed to read package: {}", error),
+            PackageError::MissingManifest => write!(f, "missing {}", MANIFEST_NAME),
+            PackageError::InvalidManifest(reason) => write!(f, "invalid manifest: {}", reason),
+            PackageError::UnsupportedManifestVersion(version) => {
+                write!(f, "unsupported manifest version {}", version)
+            }
+            PackageError::InvalidPath(path) => write!(f, "invalid file path {}", path),
+            PackageError::MissingFile(path) => write!(f, "missing file {}", path),
+            PackageError::UnlistedFile(path) => write!(f, "{} is not listed in the manifest", path),
+            PackageError::HashMismatch(path) => {
+                write!(f, "{} does not match its hash in the manifest", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackageError {}
+
+impl From<io::Error> for PackageError {
+    fn from(value: io::Error) -> Self {
+        PackageError::Io(value)
+    }
+}
+
+impl From<ZipError> for PackageError {
+    fn from(value: ZipError) -> Self {
+        PackageError::Zip(value)
+    }
+}
+
+/// A package's files, read into memory and checked against its manifest.
+pub struct Package {
+    pub manifest: PackageManifest,
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+/// A package extracted on disk, see [extract].
+pub struct ExtractedPackage {
+    pub manifest: PackageManifest,
+    pub dir: PathBuf,
+}
+
+impl ExtractedPackage {
+    pub fn library(&self) -> PathBuf {
+        self.dir.join(&self.manifest.library)
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`, as used in manifests.
+pub fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Relative `/` separated paths without `.` or `..` components, so nothing is written outside
+/// the package directory whatever OS it's extracted on.
+fn is_package_path(path: &str) -> bool {
+    path != MANIFEST_NAME
+        && path.split('/').all(|component| {
+            !matches!(component, "" | "." | "..") && !component.contains(['\\', ':'])
+        })
+}
+
+/// Reads a package and verifies every file against the manifest.
+pub fn read(reader: impl Read + Seek) -> Result<Package, PackageError> {
+    let mut archive = ZipArchive::new(reader)?;
+    let manifest = match archive.by_name(MANIFEST_NAME) {
+        Ok(mut file) => {
+            let mut manifest = String::new();
+            file.read_to_string(&mut manifest)?;
+            toml::from_str::<PackageManifest>(&manifest)
+                .map_err(|error| PackageError::InvalidManifest(error.message().to_string()))?
+        }
+        Err(ZipError::FileNotFound) => return Err(PackageError::MissingManifest),
+        Err(error) => return Err(error.into()),
+    };
+    manifest.validate()?;
+
+    let mut files = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let name = file.name().to_string();
+        if file.is_dir() || name == MANIFEST_NAME {
+            continue;
+        }
+        let Some(expected) = manifest.files.get(&name) else {
+            return Err(PackageError::UnlistedFile(name));
+        };
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        if hash(&contents) != *expected {
+            return Err(PackageError::HashMismatch(name));
+        }
+        files.insert(name, contents);
+    }
+    if let Some(missing) = manifest
+        .files
+        .keys()
+        .find(|path| !files.contains_key(*path))
+    {
+        return Err(PackageError::MissingFile(missing.clone()));
+    }
+
+    Ok(Package { manifest, files })
+}
+
+/// Writes a package containing `files`, returning its manifest. `library` has to be one of the
+/// files.
+pub fn write(
+    writer: impl Write + Seek,
+    id: &str,
+    version: &str,
+    library: &str,
+    files: &BTreeMap<String, Vec<u8>>,
+) -> Result<PackageManifest, PackageError> {
+    let manifest = PackageManifest {
+        manifest_version: MANIFEST_VERSION,
+        id: id.to_string(),
+        version: version.to_string(),
+        library: library.to_string(),
+        files: files
+            .iter()
+            .map(|(path, contents)| (path.clone(), hash(contents)))
+            .collect(),
+    };
+    manifest.validate()?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(writer);
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(toml::to_string(&manifest).unwrap().as_bytes())?;
+    for (path, contents) in files {
+        zip.start_file(path, options)?;
+        zip.write_all(contents)?;
+    }
+    zip.finish()?;
+
+    Ok(manifest)
+}
+
+/// Verifies the package at `path` and extracts it into `<cache>/<id>/<version>`.
+///
+/// An earlier extraction of the same package is reused if its files are still intact, otherwise
+/// it's replaced. Nothing is written unless the whole package checks out.
+pub fn extract(path: &Path, cache: &Path) -> Result<ExtractedPackage, PackageError> {
+    let package = read(File::open(path)?)?;
+    let plugin_dir = cache.join(&package.manifest.id);
+    let dir = plugin_dir.join(&package.manifest.version);
+    if is_extracted(&dir, &package.manifest) {
+        return Ok(ExtractedPackage {
+            manifest: package.manifest,
+            dir,
+        });
+    }
+
+    // written next to the final location first so a crash can't leave a partial package behind.
+    let staging = plugin_dir.join(format!(".{}.partial", package.manifest.version));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    for (name, contents) in &package.files {
+        let file = staging.join(name);
+        fs::create_dir_all(file.parent().unwrap())?;
+        fs::write(file, contents)?;
+    }
+    fs::write(
+        staging.join(MANIFEST_NAME),
+        toml::to_string(&package.manifest).unwrap(),
+    )?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::rename(&staging, &dir)?;
+
+    Ok(ExtractedPackage {
+        manifest: package.manifest,
+        dir,
+    })
+}
+
+fn is_extracted(dir: &Path, manifest: &PackageManifest) -> bool {
+    let extracted = fs::read_to_string(dir.join(MANIFEST_NAME))
+        .ok()
+        .and_then(|extracted| toml::from_str::<PackageManifest>(&extracted).ok());
+    extracted.as_ref() == Some(manifest)
+        && manifest.files.iter().all(|(name, expected)| {
+            fs::read(dir.join(name)).is_ok_and(|contents| hash(&contents) == *expected)
+        })
+}
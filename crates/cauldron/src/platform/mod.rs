@@ -0,0 +1,42 @@
+//! OS specific services used by the loader.
+//!
+//! The windows implementation is what runs in-game, the stub implementation lets the loader
+//! logic (metadata, dependency resolution, config) be built and tested on other hosts.
+
+#[cfg(not(windows))]
+mod stub;
+#[cfg(windows)]
+mod win32;
+
+use crate::version::GameVersion;
+use std::path::Path;
+
+#[cfg(not(windows))]
+pub use stub::StubPlatform;
+#[cfg(windows)]
+pub use win32::WindowsPlatform;
+
+pub trait Platform {
+    /// Shows a blocking error dialog.
+    fn message_box(title: &str, text: &str);
+
+    /// Reads the file version resource of the executable at `path`.
+    fn file_version(path: &Path) -> Option<GameVersion>;
+
+    /// Allocates a console window and attaches it to the parent process if there is one.
+    fn attach_console();
+
+    /// Initializes the hooking library, safe to call multiple times.
+    fn initialize_hooks();
+
+    /// Enables all created hooks.
+    fn apply_hooks();
+}
+
+/// The [Platform] implementation for the current target.
+#[cfg(windows)]
+pub type CurrentPlatform = WindowsPlatform;
+
+/// The [Platform] implementation for the current target.
+#[cfg(not(windows))]
+pub type CurrentPlatform = StubPlatform;
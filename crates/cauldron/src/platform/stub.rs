@@ -0,0 +1,22 @@
+use crate::platform::Platform;
+use crate::version::GameVersion;
+use std::path::Path;
+
+/// Used on non-windows hosts, dialogs are written to stderr and hooking is a no-op.
+pub struct StubPlatform;
+
+impl Platform for StubPlatform {
+    fn message_box(title: &str, text: &str) {
+        eprintln!("{title}: {text}");
+    }
+
+    fn file_version(_path: &Path) -> Option<GameVersion> {
+        None
+    }
+
+    fn attach_console() {}
+
+    fn initialize_hooks() {}
+
+    fn apply_hooks() {}
+}
@@ -0,0 +1,94 @@
+use crate::platform::Platform;
+use crate::version::GameVersion;
+use minhook::{MH_ApplyQueued, MH_EnableHook, MH_Initialize, MH_STATUS};
+use std::path::Path;
+use std::ptr;
+use windows::Win32::Storage::FileSystem::{
+    GetFileVersionInfoSizeW, GetFileVersionInfoW, VS_FIXEDFILEINFO, VerQueryValueW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{MB_ICONERROR, MB_OK, MessageBoxW};
+use windows::core::{HSTRING, PCWSTR, w};
+use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AllocConsole, AttachConsole};
+
+pub struct WindowsPlatform;
+
+impl Platform for WindowsPlatform {
+    fn message_box(title: &str, text: &str) {
+        unsafe {
+            MessageBoxW(
+                None,
+                PCWSTR::from_raw(HSTRING::from(text).as_ptr()),
+                PCWSTR::from_raw(HSTRING::from(title).as_ptr()),
+                MB_OK | MB_ICONERROR,
+            );
+        }
+    }
+
+    fn file_version(path: &Path) -> Option<GameVersion> {
+        let path = HSTRING::from(path);
+        let mut version_info_size =
+            unsafe { GetFileVersionInfoSizeW(PCWSTR::from_raw(path.as_ptr()), None) };
+        if version_info_size == 0 {
+            return None;
+        }
+        let mut version_info_buf = vec![0u8; version_info_size as usize];
+        unsafe {
+            GetFileVersionInfoW(
+                PCWSTR::from_raw(path.as_ptr()),
+                None,
+                version_info_size,
+                version_info_buf.as_mut_ptr() as _,
+            )
+            .ok()?
+        };
+
+        let mut version_info: *mut VS_FIXEDFILEINFO = ptr::null_mut();
+        unsafe {
+            let _ = VerQueryValueW(
+                version_info_buf.as_ptr() as _,
+                w!("\\\\\0"),
+                &mut version_info as *mut *mut _ as _,
+                &mut version_info_size,
+            );
+        };
+        let version_info = unsafe { version_info.as_ref()? };
+        let major = (version_info.dwFileVersionMS >> 16) & 0xffff;
+        let minor = (version_info.dwFileVersionMS) & 0xffff;
+        let patch = (version_info.dwFileVersionLS >> 16) & 0xffff;
+        let build = (version_info.dwFileVersionLS) & 0xffff;
+
+        Some(GameVersion {
+            major,
+            minor,
+            patch,
+            build,
+        })
+    }
+
+    fn attach_console() {
+        unsafe {
+            AllocConsole();
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+
+    fn initialize_hooks() {
+        match unsafe { MH_Initialize() } {
+            MH_STATUS::MH_ERROR_ALREADY_INITIALIZED | MH_STATUS::MH_OK => {}
+            status @ MH_STATUS::MH_ERROR_MEMORY_ALLOC => panic!("MH_Initialize: {status:?}"),
+            _ => unreachable!(),
+        }
+    }
+
+    fn apply_hooks() {
+        unsafe {
+            // enable all
+            MH_EnableHook(std::ptr::null_mut())
+                .ok()
+                .expect("cauldron: failed to queue enable hooks");
+            MH_ApplyQueued()
+                .ok()
+                .expect("cauldron: failed to apply queued hooks");
+        };
+    }
+}
@@ -0,0 +1,163 @@
+use libdecima::mem::patches::PatchEntry;
+use libdecima::mem::signatures::SignatureReport;
+use semver::Version;
+use std::fmt::{Display, Formatter};
+
+/// Outcome of a loader run, what got loaded, what didn't and why.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    /// Plugins that passed resolution, in initialization order.
+    pub plugins: Vec<PluginReport>,
+    /// Plugins that were found but could not be loaded.
+    pub rejected: Vec<RejectedPlugin>,
+    /// Set if dependency resolution failed, no plugins are initialized when this is present.
+    pub error: Option<LoadError>,
+    /// Game signatures checked at startup, plugins can use it to turn off features whose
+    /// signatures are missing. Empty outside of the game.
+    pub signatures: SignatureReport,
+    /// Every patch made through the [patch registry](libdecima::mem::patches::PatchRegistry) by
+    /// the time plugins were initialized, including the ones that were refused.
+    pub patches: Vec<PatchEntry>,
+}
+
+impl LoadReport {
+    pub fn plugin(&self, id: &str) -> Option<&PluginReport> {
+        self.plugins.iter().find(|p| p.id == id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginReport {
+    pub id: String,
+    pub version: String,
+    /// Loader versions the plugin accepts, see [crate::metadata::PluginMetadataCauldronV1::loader_requirement].
+    pub loader_requirement: Option<String>,
+    pub initialized: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RejectedPlugin {
+    /// Where the plugin came from, usually a file path.
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    CircularDependency {
+        a: String,
+        b: String,
+    },
+    InvalidVersion {
+        plugin: String,
+        version: String,
+    },
+    MalformedRequirement {
+        plugin: String,
+        requirement: String,
+    },
+    MissingDependency {
+        plugin: String,
+        dependency: String,
+        requirement: String,
+    },
+    DependencyVersionMismatch {
+        plugin: String,
+        dependency: String,
+        requirement: String,
+        found: Version,
+    },
+    InitPhaseConflict {
+        plugin: String,
+        dependency: String,
+    },
+    IncompatibleLoader {
+        plugin: String,
+        requirement: String,
+        found: Version,
+    },
+    Conflict {
+        plugin: String,
+        conflict: String,
+        requirement: String,
+        found: Version,
+        reason: Option<String>,
+    },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::CircularDependency { a, b } => write!(
+                f,
+                "Circular dependencies detected. ({} and {} depend on each other.)",
+                a, b
+            ),
+            LoadError::InvalidVersion { plugin, version } => write!(
+                f,
+                "{}'s version ({}) does not match semver requirements.",
+                plugin, version
+            ),
+            LoadError::MalformedRequirement {
+                plugin,
+                requirement,
+            } => write!(
+                f,
+                "Malformed dependency version requirement constraint {} in {}.",
+                requirement, plugin
+            ),
+            LoadError::MissingDependency {
+                plugin,
+                dependency,
+                requirement,
+            } => write!(
+                f,
+                "Plugin {} is missing dependency {} {}",
+                plugin, dependency, requirement
+            ),
+            LoadError::DependencyVersionMismatch {
+                plugin,
+                dependency,
+                requirement,
+                found,
+            } => write!(
+                f,
+                "Plugin {} is missing dependency {} {} (version mismatch, found {})",
+                plugin, dependency, requirement, found
+            ),
+            LoadError::InitPhaseConflict { plugin, dependency } => write!(
+                f,
+                "Plugin {} depends on {} which is initialized in a later phase.",
+                plugin, dependency
+            ),
+            LoadError::IncompatibleLoader {
+                plugin,
+                requirement,
+                found,
+            } => write!(
+                f,
+                "Plugin {} requires cauldron {} (running {}).",
+                plugin, requirement, found
+            ),
+            LoadError::Conflict {
+                plugin,
+                conflict,
+                requirement,
+                found,
+                reason,
+            } => {
+                write!(
+                    f,
+                    "Plugin {} conflicts with {} {} (found {})",
+                    plugin, conflict, requirement, found
+                )?;
+                match reason {
+                    Some(reason) => write!(f, ": {}", reason),
+                    None => write!(f, "."),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
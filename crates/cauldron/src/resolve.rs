@@ -0,0 +1,122 @@
+//! Works out the order plugins are initialized in.
+
+use crate::metadata::PluginMetadata;
+use crate::report::LoadError;
+use libdecima::log;
+use std::collections::{BTreeSet, HashMap};
+
+/// Returns indices into `plugins` in initialization order.
+///
+/// Dependencies are always initialized first and earlier init phases before later ones.
+/// `load_before`/`load_after` hints are followed as long as they don't contradict either of
+/// those, or an earlier hint. Ties are broken by registration order so the result is stable.
+#[allow(clippy::result_large_err)] // built at most once per load
+pub(crate) fn init_order(plugins: &[&PluginMetadata]) -> Result<Vec<usize>, LoadError> {
+    let index = plugins
+        .iter()
+        .enumerate()
+        .map(|(index, plugin)| (plugin.cauldron.id.as_str(), index))
+        .collect::<HashMap<_, _>>();
+    let id = |index: usize| plugins[index].cauldron.id.clone();
+    let phase = |index: usize| plugins[index].cauldron.init_phase;
+
+    // edges[a] contains b if a has to be initialized before b.
+    let mut edges = vec![Vec::new(); plugins.len()];
+    for (plugin, metadata) in plugins.iter().enumerate() {
+        let dependencies = metadata.cauldron.dependencies.iter().flatten();
+        for dependency in dependencies.filter_map(|(id, _)| index.get(id.as_str())) {
+            edges[*dependency].push(plugin);
+        }
+    }
+    if let Err((a, b)) = sort(&edges, phase) {
+        return Err(LoadError::CircularDependency { a: id(a), b: id(b) });
+    }
+
+    for (plugin, metadata) in plugins.iter().enumerate() {
+        let after = metadata
+            .cauldron
+            .load_after
+            .iter()
+            .filter_map(|id| index.get(id.as_str()))
+            .map(|other| (*other, plugin));
+        let before = metadata
+            .cauldron
+            .load_before
+            .iter()
+            .filter_map(|id| index.get(id.as_str()))
+            .map(|other| (plugin, *other));
+
+        for (first, second) in after.chain(before) {
+            if phase(first) > phase(second) || reachable(&edges, second, first) {
+                log!(
+                    "Cauldron",
+                    "Ignoring {}'s hint to load {} before {}, it contradicts another requirement.",
+                    metadata.cauldron.id,
+                    id(first),
+                    id(second)
+                );
+                continue;
+            }
+            edges[first].push(second);
+        }
+    }
+
+    Ok(sort(&edges, phase).expect("hints are only added when they don't create a cycle"))
+}
+
+/// Topological sort preferring earlier phases then lower indices, returns two plugins in a cycle
+/// if there is one.
+fn sort<P: Ord>(
+    edges: &[Vec<usize>],
+    phase: impl Fn(usize) -> P,
+) -> Result<Vec<usize>, (usize, usize)> {
+    let mut incoming = vec![0; edges.len()];
+    edges.iter().flatten().for_each(|to| incoming[*to] += 1);
+
+    let mut ready = (0..edges.len())
+        .filter(|node| incoming[*node] == 0)
+        .map(|node| (phase(node), node))
+        .collect::<BTreeSet<_>>();
+    let mut order = Vec::with_capacity(edges.len());
+    while let Some((_, node)) = ready.pop_first() {
+        order.push(node);
+        for to in &edges[node] {
+            incoming[*to] -= 1;
+            if incoming[*to] == 0 {
+                ready.insert((phase(*to), *to));
+            }
+        }
+    }
+
+    if order.len() == edges.len() {
+        return Ok(order);
+    }
+
+    // everything left over is in or behind a cycle, walking backwards always stays in one.
+    let predecessor = |node: usize| {
+        (0..edges.len())
+            .find(|from| incoming[*from] > 0 && edges[*from].contains(&node))
+            .unwrap()
+    };
+    let mut visited = vec![false; edges.len()];
+    let mut node = (0..edges.len()).find(|node| incoming[*node] > 0).unwrap();
+    while !visited[node] {
+        visited[node] = true;
+        node = predecessor(node);
+    }
+    Err((node, predecessor(node)))
+}
+
+fn reachable(edges: &[Vec<usize>], from: usize, to: usize) -> bool {
+    let mut visited = vec![false; edges.len()];
+    let mut stack = vec![from];
+    while let Some(node) = stack.pop() {
+        if node == to {
+            return true;
+        }
+        if !std::mem::replace(&mut visited[node], true) {
+            stack.extend(&edges[node]);
+        }
+    }
+    false
+}
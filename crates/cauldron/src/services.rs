@@ -0,0 +1,34 @@
+//! Services the loader depends on while initializing plugins, swappable so the loader can run
+//! outside of the game (see `test_host`, behind the `test-host` feature).
+
+use crate::platform::{CurrentPlatform, Platform};
+
+pub trait HookService {
+    /// Called before any plugin is initialized.
+    fn initialize(&self);
+
+    /// Called once every plugin has been initialized, enables the hooks they created.
+    fn apply(&self);
+}
+
+/// Hooks through minhook, used in-game.
+pub struct PlatformHookService;
+
+impl HookService for PlatformHookService {
+    fn initialize(&self) {
+        CurrentPlatform::initialize_hooks();
+    }
+
+    fn apply(&self) {
+        CurrentPlatform::apply_hooks();
+    }
+}
+
+/// Does nothing, for running plugins without a game process to hook into.
+pub struct NoopHookService;
+
+impl HookService for NoopHookService {
+    fn initialize(&self) {}
+
+    fn apply(&self) {}
+}
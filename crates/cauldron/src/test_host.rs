@@ -0,0 +1,133 @@
+//! A headless loader for exercising plugins from `cargo test`, without booting the game.
+//!
+//! Only built with the `test-host` feature, so plugins enable it on their dev-dependency:
+//! `cauldron = { workspace = true, features = ["test-host"] }`.
+//!
+//! ```no_run
+//! # use cauldron::CauldronPlugin;
+//! # struct HelloCauldron;
+//! # impl CauldronPlugin for HelloCauldron { fn new() -> Self { HelloCauldron } }
+//! use cauldron::test_host::TestHost;
+//! use cauldron::version::CauldronGameType;
+//!
+//! let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+//!     .with_plugin::<HelloCauldron>(
+//!         r#"
+//!         schema_version = 0
+//!
+//!         [cauldron]
+//!         id = "hello-cauldron"
+//!         version = "0.1.0"
+//!         "#,
+//!     )
+//!     .run();
+//! assert!(report.error.is_none());
+//! assert!(report.plugin("hello-cauldron").unwrap().initialized);
+//! ```
+
+use crate::report::LoadReport;
+use crate::services::{HookService, NoopHookService};
+use crate::version::{CauldronGameType, GameVersion};
+use crate::{CauldronLoader, CauldronPlugin, GameInfo, PluginBox};
+use std::env;
+use std::path::PathBuf;
+
+enum HostPlugin {
+    InProcess {
+        name: &'static str,
+        metadata: String,
+        new: fn() -> PluginBox,
+    },
+    Library(PathBuf),
+    Package(PathBuf),
+}
+
+pub struct TestHost {
+    game: GameInfo,
+    plugins: Vec<HostPlugin>,
+    hook_service: Box<dyn HookService + Send + Sync>,
+}
+
+impl TestHost {
+    pub fn new(game_type: CauldronGameType) -> Self {
+        TestHost {
+            game: GameInfo {
+                game_type,
+                version: GameVersion::default(),
+                root: env::temp_dir().join("cauldron-test-host"),
+            },
+            plugins: Vec::new(),
+            hook_service: Box::new(NoopHookService),
+        }
+    }
+
+    pub fn game_version(mut self, version: GameVersion) -> Self {
+        self.game.version = version;
+        self
+    }
+
+    /// Sets where plugin directories are created, defaults to `cauldron-test-host` in the
+    /// system temp directory.
+    pub fn game_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.game.root = root.into();
+        self
+    }
+
+    /// Replaces the default [NoopHookService].
+    pub fn hook_service(mut self, hook_service: impl HookService + Send + Sync + 'static) -> Self {
+        self.hook_service = Box::new(hook_service);
+        self
+    }
+
+    /// Adds an in-process plugin, `metadata` is the contents of its `.cauldron.toml`.
+    pub fn with_plugin<P: CauldronPlugin + Send + Sync + 'static>(
+        mut self,
+        metadata: &str,
+    ) -> Self {
+        self.plugins.push(HostPlugin::InProcess {
+            name: std::any::type_name::<P>(),
+            metadata: metadata.to_string(),
+            new: || Box::new(P::new()),
+        });
+        self
+    }
+
+    /// Adds a plugin built as a dynamic library, loaded the same way the game loads it.
+    pub fn with_library(mut self, path: impl Into<PathBuf>) -> Self {
+        self.plugins.push(HostPlugin::Library(path.into()));
+        self
+    }
+
+    /// Adds a `.cauldron` package, extracted into the game root's `cauldron/packages`.
+    pub fn with_package(mut self, path: impl Into<PathBuf>) -> Self {
+        self.plugins.push(HostPlugin::Package(path.into()));
+        self
+    }
+
+    /// Registers all plugins, then runs resolution and initialization. The loader is returned so
+    /// plugin state can be inspected, see [CauldronLoader::report] for the outcome.
+    pub fn load(self) -> CauldronLoader {
+        let mut loader = CauldronLoader::with_services(self.game, self.hook_service);
+        for plugin in self.plugins {
+            match plugin {
+                HostPlugin::InProcess {
+                    name,
+                    metadata,
+                    new,
+                } => {
+                    loader.add_plugin(name.to_string(), &metadata, new, None);
+                }
+                HostPlugin::Library(path) => unsafe { loader.try_load_plugin(&path) },
+                HostPlugin::Package(path) => unsafe { loader.try_load_package(&path) },
+            }
+        }
+        loader.load_plugins();
+        loader
+    }
+
+    /// Same as [TestHost::load], only keeping the report.
+    pub fn run(self) -> LoadReport {
+        let mut loader = self.load();
+        std::mem::take(&mut loader.report)
+    }
+}
@@ -1,13 +1,16 @@
-use windows::Win32::UI::WindowsAndMessaging::{MESSAGEBOX_STYLE, MessageBoxW};
-use windows::core::{HSTRING, PCWSTR};
+#[cfg(windows)]
+use crate::platform::{CurrentPlatform, Platform};
+use std::env::current_exe;
+use std::path::PathBuf;
 
-pub(crate) fn message_box(title: &str, text: &str, icon: MESSAGEBOX_STYLE) {
-    unsafe {
-        MessageBoxW(
-            None,
-            PCWSTR::from_raw(HSTRING::from(text).as_ptr()),
-            PCWSTR::from_raw(HSTRING::from(title).as_ptr()),
-            icon,
-        );
-    }
+#[cfg(windows)]
+pub(crate) fn message_box(title: &str, text: &str) {
+    CurrentPlatform::message_box(title, text);
+}
+
+/// The directory containing the game executable, used instead of the working directory since
+/// the game can be started from anywhere.
+pub(crate) fn game_root() -> PathBuf {
+    let exe = current_exe().expect("failed to get the game executable path");
+    exe.parent().unwrap().to_path_buf()
 }
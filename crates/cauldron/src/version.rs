@@ -1,13 +1,10 @@
+use crate::platform::{CurrentPlatform, Platform};
+use cauldron_ids::GAME_IDS;
 use serde::{Deserialize, Serialize};
 use std::env::current_exe;
 use std::fmt::{Display, Formatter};
-use std::ptr;
-use windows::Win32::Storage::FileSystem::{
-    GetFileVersionInfoSizeW, GetFileVersionInfoW, VS_FIXEDFILEINFO, VerQueryValueW,
-};
-use windows::core::{HSTRING, PCWSTR, w};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone)]
 pub struct GameVersion {
     pub major: u32,
     pub minor: u32,
@@ -15,6 +12,16 @@ pub struct GameVersion {
     pub build: u32,
 }
 
+impl Display for GameVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.patch, self.build
+        )
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum CauldronGameType {
     HorizonForbiddenWest,
@@ -23,21 +30,20 @@ pub enum CauldronGameType {
 }
 
 impl CauldronGameType {
+    pub const ALL: [CauldronGameType; 3] = [
+        CauldronGameType::HorizonForbiddenWest,
+        CauldronGameType::HorizonZeroDawn,
+        CauldronGameType::HorizonZeroDawnRemastered,
+    ];
+
+    /// The game's entry in [GAME_IDS], shared with the `cauldron_plugin` macro.
     pub fn id(&self) -> String {
-        match self {
-            CauldronGameType::HorizonForbiddenWest => String::from("hfw"),
-            CauldronGameType::HorizonZeroDawn => String::from("hzd"),
-            CauldronGameType::HorizonZeroDawnRemastered => String::from("hzdr"),
-        }
+        String::from(GAME_IDS[*self as usize])
     }
 
     pub fn from_id(id: &str) -> Option<Self> {
-        match id {
-            "hfw" => Some(CauldronGameType::HorizonForbiddenWest),
-            "hzd" => Some(CauldronGameType::HorizonZeroDawn),
-            "hzdr" => Some(CauldronGameType::HorizonZeroDawnRemastered),
-            _ => None,
-        }
+        let index = GAME_IDS.iter().position(|game| *game == id)?;
+        CauldronGameType::ALL.get(index).copied()
     }
 }
 
@@ -71,43 +77,5 @@ impl CauldronGameType {
 }
 
 pub fn version() -> GameVersion {
-    let path = current_exe().unwrap();
-    let mut version_info_size = unsafe {
-        GetFileVersionInfoSizeW(
-            PCWSTR::from_raw(HSTRING::from(path.as_path()).as_ptr()),
-            None,
-        )
-    };
-    let mut version_info_buf = vec![0u8; version_info_size as usize];
-    unsafe {
-        GetFileVersionInfoW(
-            PCWSTR::from_raw(HSTRING::from(path.as_path()).as_ptr()),
-            None,
-            version_info_size,
-            version_info_buf.as_mut_ptr() as _,
-        )
-        .unwrap()
-    };
-
-    let mut version_info: *mut VS_FIXEDFILEINFO = ptr::null_mut();
-    unsafe {
-        let _ = VerQueryValueW(
-            version_info_buf.as_ptr() as _,
-            w!("\\\\\0"),
-            &mut version_info as *mut *mut _ as _,
-            &mut version_info_size,
-        );
-    };
-    let version_info = unsafe { version_info.as_ref().unwrap() };
-    let major = (version_info.dwFileVersionMS >> 16) & 0xffff;
-    let minor = (version_info.dwFileVersionMS) & 0xffff;
-    let patch = (version_info.dwFileVersionLS >> 16) & 0xffff;
-    let build = (version_info.dwFileVersionLS) & 0xffff;
-
-    GameVersion {
-        major,
-        minor,
-        patch,
-        build,
-    }
+    CurrentPlatform::file_version(&current_exe().unwrap()).unwrap_or_default()
 }
@@ -0,0 +1,24 @@
+//! The ids in `cauldron-ids`, which the `cauldron_plugin` macro checks plugins against.
+
+use cauldron::metadata::LOADER_ID;
+use cauldron::metadata::validator::is_valid_id;
+use cauldron::version::CauldronGameType;
+use cauldron_ids::GAME_IDS;
+
+#[test]
+fn game_ids_match_the_games() {
+    assert_eq!(CauldronGameType::ALL.map(|game| game.id()), GAME_IDS);
+    for game in CauldronGameType::ALL {
+        assert_eq!(CauldronGameType::from_id(&game.id()), Some(game));
+    }
+    assert_eq!(CauldronGameType::from_id("cauldron"), None);
+}
+
+#[test]
+fn shared_ids_are_valid() {
+    // games are dependency keys, the loader id is reserved for one.
+    assert!(GAME_IDS.iter().all(|id| is_valid_id(id)));
+    assert!(is_valid_id(LOADER_ID));
+    assert!(!is_valid_id("1st"));
+    assert!(!is_valid_id(&"a".repeat(65)));
+}
@@ -0,0 +1,87 @@
+//! Migrating older metadata to [cauldron::metadata::PluginMetadata] on load.
+
+use cauldron::metadata::{PluginInitPhase, PluginMetadataDependency, parse_plugin_metadata};
+
+const V0: &str = r#"
+schema_version = 0
+
+[cauldron]
+id = "example"
+version = "1.2.0"
+
+[cauldron.metadata]
+name = "Example"
+
+[cauldron.dependencies]
+other = "^1.0"
+hfw = { version = ">=1.5", reason = "hooks the game" }
+
+[tool]
+key = "value"
+"#;
+
+#[test]
+fn v0_metadata_is_migrated() {
+    let metadata = parse_plugin_metadata(V0).unwrap();
+    assert_eq!(metadata.schema_version, 1);
+
+    let cauldron = &metadata.cauldron;
+    assert_eq!(cauldron.id, "example");
+    assert_eq!(cauldron.version, "1.2.0");
+    assert_eq!(
+        cauldron.metadata.as_ref().unwrap().name.as_deref(),
+        Some("Example")
+    );
+    assert_eq!(cauldron.init_phase, PluginInitPhase::Default);
+    assert!(cauldron.conflicts.is_none());
+
+    let dependencies = cauldron.dependencies.as_ref().unwrap();
+    assert_eq!(dependencies.len(), 2);
+    assert!(matches!(
+        &dependencies["other"],
+        PluginMetadataDependency::Plain(version) if version == "^1.0"
+    ));
+    assert!(matches!(
+        &dependencies["hfw"],
+        PluginMetadataDependency::Detailed(detailed) if detailed.version == ">=1.5"
+    ));
+    assert_eq!(
+        metadata.extra["tool"]["key"],
+        toml::Value::String("value".to_string())
+    );
+
+    // depending on a game was the only way to limit a v0 plugin to it.
+    assert_eq!(cauldron.supported_games, Some(vec!["hfw".to_string()]));
+}
+
+#[test]
+fn v0_metadata_without_games_supports_any() {
+    let metadata = parse_plugin_metadata(
+        r#"
+        schema_version = 0
+
+        [cauldron]
+        id = "example"
+        version = "1.2.0"
+
+        [cauldron.dependencies]
+        other = "^1.0"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(metadata.schema_version, 1);
+    assert!(metadata.cauldron.supported_games.is_none());
+
+    let metadata = parse_plugin_metadata(
+        r#"
+        schema_version = 0
+
+        [cauldron]
+        id = "example"
+        version = "1.2.0"
+        "#,
+    )
+    .unwrap();
+    assert!(metadata.cauldron.supported_games.is_none());
+    assert!(metadata.cauldron.dependencies.is_none());
+}
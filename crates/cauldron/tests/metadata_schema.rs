@@ -0,0 +1,114 @@
+//! Runs the JSON schemas in `schemas/` and [cauldron::metadata::validator] over the files in
+//! `tests/metadata`, both have to agree on every file. The committed schemas also have to match
+//! what [cauldron::metadata::schema] generates.
+//!
+//! Invalid files list the paths the validator is expected to report as `# error: <path>` comments.
+
+use cauldron::metadata::parse_plugin_metadata;
+use cauldron::metadata::schema::{SCHEMA_BASE_URL, schemas, to_file_contents};
+use cauldron::metadata::validator::validate;
+use jsonschema::{Resource, Validator};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn read_json(path: PathBuf) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(&path).unwrap())
+        .unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+}
+
+fn schemas_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../schemas")
+}
+
+fn schema() -> Validator {
+    let schemas = schemas_dir();
+    let mut options = jsonschema::options();
+    for version in ["v0", "v1"] {
+        let name = format!("cauldron-{}.json", version);
+        let resource = Resource::from_contents(read_json(schemas.join(&name))).unwrap();
+        options = options.with_resource(format!("{}/{}", SCHEMA_BASE_URL, name), resource);
+    }
+    options
+        .build(&read_json(schemas.join("cauldron-main.json")))
+        .unwrap()
+}
+
+fn corpus(kind: &str) -> Vec<(String, String)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/metadata")
+        .join(kind);
+    let mut files = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            (name, fs::read_to_string(path).unwrap())
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    assert!(!files.is_empty(), "no {} metadata files", kind);
+    files
+}
+
+fn to_json(metadata: &str) -> serde_json::Value {
+    serde_json::to_value(toml::from_str::<toml::Table>(metadata).unwrap()).unwrap()
+}
+
+#[test]
+fn valid_metadata_passes_schema_and_validator() {
+    let schema = schema();
+    for (name, metadata) in corpus("valid") {
+        let schema_errors = schema
+            .iter_errors(&to_json(&metadata))
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+        assert!(schema_errors.is_empty(), "{}: {:?}", name, schema_errors);
+        assert_eq!(validate(&metadata), Ok(()), "{}", name);
+        assert!(parse_plugin_metadata(&metadata).is_ok(), "{}", name);
+    }
+}
+
+#[test]
+fn invalid_metadata_fails_schema_and_validator() {
+    let schema = schema();
+    for (name, metadata) in corpus("invalid") {
+        assert!(
+            !schema.is_valid(&to_json(&metadata)),
+            "{} passes the schema",
+            name
+        );
+
+        let errors = validate(&metadata).expect_err(&name);
+        let paths = errors
+            .iter()
+            .map(|error| error.path.as_str())
+            .collect::<Vec<_>>();
+        let expected = metadata
+            .lines()
+            .filter_map(|line| line.strip_prefix("# error: "))
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty(), "{} has no expected errors", name);
+        for path in expected {
+            assert!(
+                paths.contains(&path),
+                "{}: {} not in {:?}",
+                name,
+                path,
+                paths
+            );
+        }
+    }
+}
+
+#[test]
+fn committed_schemas_are_up_to_date() {
+    for (name, schema) in schemas() {
+        let committed = fs::read_to_string(schemas_dir().join(name)).unwrap_or_default();
+        assert!(
+            committed == to_file_contents(&schema),
+            "schemas/{} is out of date, run `cargo xtask schemas`",
+            name
+        );
+    }
+}
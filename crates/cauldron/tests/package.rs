@@ -0,0 +1,164 @@
+use cauldron::package::{self, MANIFEST_NAME, PackageError, PackageManifest};
+use cauldron::test_host::TestHost;
+use cauldron::version::CauldronGameType;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+fn files() -> BTreeMap<String, Vec<u8>> {
+    BTreeMap::from([
+        (
+            "example.dll".to_string(),
+            b"MZ not really a library".to_vec(),
+        ),
+        ("assets/icon.png".to_string(), vec![0x89, b'P', b'N', b'G']),
+    ])
+}
+
+fn package() -> (PackageManifest, Vec<u8>) {
+    let mut bytes = Cursor::new(Vec::new());
+    let manifest = package::write(&mut bytes, "example", "1.2.0", "example.dll", &files()).unwrap();
+    (manifest, bytes.into_inner())
+}
+
+/// Writes a zip by hand, for packages `package::write` refuses to create.
+fn raw_package(manifest: &PackageManifest, files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file(MANIFEST_NAME, SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(toml::to_string(manifest).unwrap().as_bytes())
+        .unwrap();
+    for (name, contents) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn packages_round_trip() {
+    let (manifest, bytes) = package();
+    assert_eq!(
+        manifest.files["example.dll"],
+        package::hash(b"MZ not really a library")
+    );
+
+    let package = package::read(Cursor::new(bytes)).unwrap();
+    assert_eq!(package.manifest, manifest);
+    assert_eq!(package.files, files());
+}
+
+#[test]
+fn modified_file_is_rejected() {
+    let (manifest, _) = package();
+    let bytes = raw_package(
+        &manifest,
+        &[
+            ("example.dll", b"MZ something else"),
+            ("assets/icon.png", &[0x89, b'P', b'N', b'G']),
+        ],
+    );
+
+    assert!(matches!(
+        package::read(Cursor::new(bytes)),
+        Err(PackageError::HashMismatch(file)) if file == "example.dll"
+    ));
+}
+
+#[test]
+fn partial_package_is_rejected() {
+    let (manifest, _) = package();
+    let bytes = raw_package(&manifest, &[("example.dll", b"MZ not really a library")]);
+
+    assert!(matches!(
+        package::read(Cursor::new(bytes)),
+        Err(PackageError::MissingFile(file)) if file == "assets/icon.png"
+    ));
+}
+
+#[test]
+fn unlisted_file_is_rejected() {
+    let (manifest, _) = package();
+    let bytes = raw_package(
+        &manifest,
+        &[
+            ("example.dll", b"MZ not really a library"),
+            ("assets/icon.png", &[0x89, b'P', b'N', b'G']),
+            ("winhttp.dll", b"MZ"),
+        ],
+    );
+
+    assert!(matches!(
+        package::read(Cursor::new(bytes)),
+        Err(PackageError::UnlistedFile(file)) if file == "winhttp.dll"
+    ));
+}
+
+#[test]
+fn paths_outside_the_package_are_rejected() {
+    let mut files = files();
+    files.insert("../../winhttp.dll".to_string(), b"MZ".to_vec());
+
+    let result = package::write(
+        Cursor::new(Vec::new()),
+        "example",
+        "1.2.0",
+        "example.dll",
+        &files,
+    );
+    assert!(matches!(result, Err(PackageError::InvalidPath(path)) if path == "../../winhttp.dll"));
+}
+
+#[test]
+fn packages_are_extracted_by_version() {
+    let dir = temp_dir("cauldron-test-package-extract");
+    let (_, bytes) = package();
+    fs::write(dir.join("example.cauldron"), bytes).unwrap();
+    let cache = dir.join("packages");
+
+    let extracted = package::extract(&dir.join("example.cauldron"), &cache).unwrap();
+    assert_eq!(extracted.dir, cache.join("example").join("1.2.0"));
+    assert_eq!(extracted.library(), extracted.dir.join("example.dll"));
+    assert_eq!(
+        fs::read(extracted.dir.join("assets/icon.png")).unwrap(),
+        [0x89, b'P', b'N', b'G']
+    );
+
+    // a damaged extraction is replaced rather than loaded.
+    fs::write(extracted.library(), b"MZ patched").unwrap();
+    let extracted = package::extract(&dir.join("example.cauldron"), &cache).unwrap();
+    assert_eq!(
+        fs::read(extracted.library()).unwrap(),
+        b"MZ not really a library"
+    );
+}
+
+#[test]
+fn tampered_package_is_not_loaded() {
+    let dir = temp_dir("cauldron-test-package-host");
+    let (manifest, _) = package();
+    let bytes = raw_package(&manifest, &[("example.dll", b"MZ something else")]);
+    fs::write(dir.join("example.cauldron"), bytes).unwrap();
+
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .game_root(&dir)
+        .with_package(dir.join("example.cauldron"))
+        .run();
+
+    assert!(report.plugins.is_empty());
+    assert_eq!(
+        report.rejected[0].reason,
+        "invalid package: example.dll does not match its hash in the manifest"
+    );
+    assert!(!dir.join("cauldron").join("packages").exists());
+}
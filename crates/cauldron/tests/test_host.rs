@@ -0,0 +1,396 @@
+use cauldron::CauldronPlugin;
+use cauldron::context::PluginContext;
+use cauldron::report::LoadError;
+use cauldron::test_host::TestHost;
+use cauldron::version::CauldronGameType;
+use libdecima::mem::patches::PatchRegistry;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::{env, fs};
+
+static INIT_ORDER: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+struct Base;
+
+impl CauldronPlugin for Base {
+    fn new() -> Self {
+        Base
+    }
+
+    fn on_init(&self, _context: &PluginContext) {
+        INIT_ORDER.lock().unwrap().push("base");
+    }
+}
+
+struct Addon;
+
+impl CauldronPlugin for Addon {
+    fn new() -> Self {
+        Addon
+    }
+
+    fn on_init(&self, context: &PluginContext) {
+        assert_eq!(
+            context.loader.game.game_type,
+            CauldronGameType::HorizonForbiddenWest
+        );
+        INIT_ORDER.lock().unwrap().push("addon");
+    }
+}
+
+const BASE: &str = r#"
+schema_version = 0
+
+[cauldron]
+id = "base"
+version = "1.2.0"
+"#;
+
+const ADDON: &str = r#"
+schema_version = 0
+
+[cauldron]
+id = "addon"
+version = "0.1.0"
+
+[cauldron.dependencies]
+hfw = "*"
+base = "^1.1"
+"#;
+
+#[test]
+fn dependencies_are_initialized_first() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Addon>(ADDON)
+        .with_plugin::<Base>(BASE)
+        .run();
+
+    assert_eq!(report.error, None);
+    assert!(report.plugins.iter().all(|p| p.initialized));
+    assert_eq!(*INIT_ORDER.lock().unwrap(), ["base", "addon"]);
+}
+
+#[test]
+fn missing_dependency_is_reported() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Addon>(ADDON)
+        .run();
+
+    assert_eq!(
+        report.error,
+        Some(LoadError::MissingDependency {
+            plugin: "addon".to_string(),
+            dependency: "base".to_string(),
+            requirement: "^1.1".to_string(),
+        })
+    );
+    assert!(report.plugins.is_empty());
+}
+
+#[test]
+fn malformed_metadata_is_rejected() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Base>("schema_version = 0")
+        .run();
+
+    assert_eq!(report.error, None);
+    assert_eq!(report.rejected.len(), 1);
+    assert!(report.plugins.is_empty());
+}
+
+#[test]
+fn libraries_that_fail_to_load_are_rejected() {
+    let dir = env::temp_dir().join("cauldron-test-library");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let library = dir.join(format!(
+        "{}example{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    ));
+    fs::write(&library, b"MZ not really a library").unwrap();
+
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_library(&library)
+        .with_library(dir.join("missing"))
+        .with_plugin::<Base>(&metadata("base", "1.0.0", ""))
+        .run();
+
+    assert_eq!(report.error, None);
+    assert_eq!(
+        report
+            .rejected
+            .iter()
+            .map(|rejected| (rejected.source.clone(), rejected.reason.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            (library.display().to_string(), "failed to load library"),
+            (
+                dir.join("missing").display().to_string(),
+                "failed to load library"
+            ),
+        ]
+    );
+    assert!(report.plugin("base").unwrap().initialized);
+}
+
+struct Quiet;
+
+impl CauldronPlugin for Quiet {
+    fn new() -> Self {
+        Quiet
+    }
+}
+
+fn metadata(id: &str, version: &str, extra: &str) -> String {
+    format!(
+        "schema_version = 1\n\n[cauldron]\nid = \"{}\"\nversion = \"{}\"\n{}",
+        id, version, extra
+    )
+}
+
+fn init_order(host: TestHost) -> Vec<String> {
+    let report = host.run();
+    assert_eq!(report.error, None);
+    report.plugins.into_iter().map(|p| p.id).collect()
+}
+
+#[test]
+fn matching_conflict_is_reported() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Quiet>(&metadata(
+            "new-ui",
+            "1.0.0",
+            "[cauldron.conflicts]\nold-ui = { version = \"<2.0.0\", reason = \"Both replace the menu.\" }",
+        ))
+        .with_plugin::<Quiet>(&metadata("old-ui", "1.4.0", ""))
+        .run();
+
+    let error = report.error.unwrap();
+    assert_eq!(
+        error,
+        LoadError::Conflict {
+            plugin: "new-ui".to_string(),
+            conflict: "old-ui".to_string(),
+            requirement: "<2.0.0".to_string(),
+            found: "1.4.0".parse().unwrap(),
+            reason: Some("Both replace the menu.".to_string()),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Plugin new-ui conflicts with old-ui <2.0.0 (found 1.4.0): Both replace the menu."
+    );
+    assert!(report.plugins.is_empty());
+}
+
+#[test]
+fn conflict_outside_range_is_allowed() {
+    let order = init_order(
+        TestHost::new(CauldronGameType::HorizonForbiddenWest)
+            .with_plugin::<Quiet>(&metadata(
+                "new-ui",
+                "1.0.0",
+                "[cauldron.conflicts]\nold-ui = \"<2.0.0\"",
+            ))
+            .with_plugin::<Quiet>(&metadata("old-ui", "2.1.0", "")),
+    );
+
+    assert_eq!(order, ["new-ui", "old-ui"]);
+}
+
+#[test]
+fn load_hints_change_order() {
+    let order = init_order(
+        TestHost::new(CauldronGameType::HorizonForbiddenWest)
+            .with_plugin::<Quiet>(&metadata(
+                "aa",
+                "1.0.0",
+                "load_after = [ \"bb\", \"not-installed\" ]",
+            ))
+            .with_plugin::<Quiet>(&metadata("bb", "1.0.0", ""))
+            .with_plugin::<Quiet>(&metadata("cc", "1.0.0", "load_before = [ \"bb\" ]")),
+    );
+
+    assert_eq!(order, ["cc", "bb", "aa"]);
+}
+
+#[test]
+fn contradicting_hints_are_ignored() {
+    let order = init_order(
+        TestHost::new(CauldronGameType::HorizonForbiddenWest)
+            .with_plugin::<Quiet>(&metadata(
+                "addon",
+                "1.0.0",
+                "load_before = [ \"base\", \"early\" ]\n[cauldron.dependencies]\nbase = \"*\"",
+            ))
+            .with_plugin::<Quiet>(&metadata("base", "1.0.0", ""))
+            .with_plugin::<Quiet>(&metadata("early", "1.0.0", "init_phase = \"early\"")),
+    );
+
+    assert_eq!(order, ["early", "base", "addon"]);
+}
+
+#[test]
+fn dependency_cycle_is_reported() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Quiet>(&metadata(
+            "aa",
+            "1.0.0",
+            "[cauldron.dependencies]\nbb = \"*\"",
+        ))
+        .with_plugin::<Quiet>(&metadata(
+            "bb",
+            "1.0.0",
+            "[cauldron.dependencies]\ncc = \"*\"",
+        ))
+        .with_plugin::<Quiet>(&metadata(
+            "cc",
+            "1.0.0",
+            "[cauldron.dependencies]\naa = \"*\"",
+        ))
+        .run();
+
+    assert!(matches!(
+        report.error,
+        Some(LoadError::CircularDependency { .. })
+    ));
+    assert!(report.plugins.is_empty());
+}
+
+#[test]
+fn newer_loader_requirement_is_refused() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Quiet>(&metadata(
+            "future",
+            "1.0.0",
+            "[cauldron.dependencies]\ncauldron = \">=99.0\"",
+        ))
+        .run();
+
+    let error = report.error.unwrap();
+    assert!(matches!(
+        &error,
+        LoadError::IncompatibleLoader { plugin, requirement, .. }
+            if plugin == "future" && requirement == ">=99.0"
+    ));
+    assert!(
+        error
+            .to_string()
+            .starts_with("Plugin future requires cauldron >=99.0 (running ")
+    );
+    assert!(report.plugins.is_empty());
+}
+
+#[test]
+fn loader_requirement_is_reported() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Quiet>(&metadata(
+            "current",
+            "1.0.0",
+            "minimum_cauldron_version = \"0.1.0\"\n\n[cauldron.dependencies]\ncauldron = \"<99.0\"",
+        ))
+        .run();
+
+    assert_eq!(report.error, None);
+    assert_eq!(
+        report
+            .plugin("current")
+            .unwrap()
+            .loader_requirement
+            .as_deref(),
+        Some(">=0.1.0, <99.0")
+    );
+}
+
+static DIRECTORIES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+struct Writer;
+
+impl CauldronPlugin for Writer {
+    fn new() -> Self {
+        Writer
+    }
+
+    fn on_init(&self, context: &PluginContext) {
+        let mut directories = DIRECTORIES.lock().unwrap();
+        directories.push(context.install_dir().to_path_buf());
+        directories.push(context.data_dir().unwrap());
+        directories.push(context.cache_dir().unwrap());
+        directories.push(context.log_dir().unwrap());
+    }
+}
+
+#[test]
+fn plugin_directories_are_created_in_game_root() {
+    let root = env::temp_dir().join("cauldron-test-directories");
+    let _ = fs::remove_dir_all(&root);
+
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .game_root(&root)
+        .with_plugin::<Writer>(&metadata("writer", "1.0.0", ""))
+        .run();
+
+    assert_eq!(report.error, None);
+    let cauldron = root.join("cauldron");
+    assert_eq!(
+        *DIRECTORIES.lock().unwrap(),
+        [
+            cauldron.join("plugins").join("writer"),
+            cauldron.join("data").join("writer"),
+            cauldron.join("cache").join("writer"),
+            cauldron.join("logs").join("writer"),
+        ]
+    );
+    assert!(!cauldron.join("plugins").exists());
+    for kind in ["data", "cache", "logs"] {
+        assert!(cauldron.join(kind).join("writer").is_dir());
+    }
+}
+
+/// Stands in for game code, atomics so it can be written through a shared reference.
+static CODE: [AtomicU8; 4] = [const { AtomicU8::new(0x48) }; 4];
+
+struct Patcher;
+
+impl CauldronPlugin for Patcher {
+    fn new() -> Self {
+        Patcher
+    }
+
+    fn on_init(&self, context: &PluginContext) {
+        let code = CODE.as_ptr() as usize;
+        // CODE is only ever accessed through atomics.
+        unsafe {
+            context
+                .patch("patcher::Ret", code, &[0xC3], Some(&[0x48]))
+                .unwrap();
+            assert!(
+                context
+                    .patch("patcher::Nop", code + 1, &[0x90], Some(&[0xCC]))
+                    .is_err()
+            );
+        }
+    }
+}
+
+#[test]
+fn patches_are_reported() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<Patcher>(&metadata("patcher", "1.0.0", ""))
+        .run();
+
+    let patches = report
+        .patches
+        .iter()
+        .filter(|entry| entry.patch.owner == "patcher")
+        .collect::<Vec<_>>();
+    assert_eq!(patches.len(), 2);
+    assert!(patches[0].enabled);
+    assert!(!patches[1].enabled && patches[1].error.is_some());
+    assert_eq!(CODE[0].load(Ordering::Relaxed), 0xC3);
+
+    assert!(PatchRegistry::global().revert_owner("patcher").is_empty());
+    assert_eq!(CODE[0].load(Ordering::Relaxed), 0x48);
+}
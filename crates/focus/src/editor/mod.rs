@@ -506,7 +506,13 @@ impl FocusEditor {
             .speed(0.5)
             .ui(ui);
         if ui.button("move!").clicked() {
-            let player = Player::get_local_player(0);
+            let player = match Player::get_local_player(0) {
+                Ok(player) => player,
+                Err(error) => {
+                    log!("focus", "local player unavailable: {error:?}");
+                    return;
+                }
+            };
             let player = unsafe { &*player };
             let entity = unsafe { &*player.entity };
 
@@ -517,7 +523,13 @@ impl FocusEditor {
     }
 
     pub fn handle_inspect_click(&mut self) {
-        let player = Player::get_local_player(0);
+        let player = match Player::get_local_player(0) {
+            Ok(player) => player,
+            Err(error) => {
+                log!("focus", "local player unavailable: {error:?}");
+                return;
+            }
+        };
         let player = unsafe { &*player };
         // log!("{player:#?}");
 
@@ -541,26 +553,36 @@ impl FocusEditor {
         let mut ray_i32 = 0i32;
         let mut ray_u32 = 0u32;
 
-        let result = GCore::IntersectLine(
-            &camera_transform.pos,
-            &end_transform.pos.into(),
-            self.collision_layer,
-            if self.player_as_entity {
-                player.entity as *const _
-            } else {
-                std::ptr::null()
-            },
-            self.flag_unk_a,
-            self.flag_unk_b,
-            0,
-            &mut ray_hit_pos,
-            &mut ray_vec3,
-            &mut ray_float,
-            &mut ray_entity,
-            &mut ray_void,
-            &mut ray_i32,
-            &mut ray_u32,
-        );
+        // the outputs are locals, the entity is the live player's.
+        let result = unsafe {
+            GCore::IntersectLine(
+                &camera_transform.pos,
+                &end_transform.pos.into(),
+                self.collision_layer,
+                if self.player_as_entity {
+                    player.entity as *const _
+                } else {
+                    std::ptr::null()
+                },
+                self.flag_unk_a,
+                self.flag_unk_b,
+                0,
+                &mut ray_hit_pos,
+                &mut ray_vec3,
+                &mut ray_float,
+                &mut ray_entity,
+                &mut ray_void,
+                &mut ray_i32,
+                &mut ray_u32,
+            )
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                log!("focus", "IntersectLine unavailable: {error:?}");
+                return;
+            }
+        };
 
         log!("focus", "ray hit? {result}");
 
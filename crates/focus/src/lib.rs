@@ -34,7 +34,7 @@ pub mod internal {
     use egui::{Context, Key, RawInput};
     use glam::{EulerRot, Mat3};
     use libdecima::log;
-    use libdecima::mem::offsets::Offset;
+    use libdecima::mem::offsets::Offsets;
     use libdecima::types::decima::core::camera_entity::CameraEntity;
     use libdecima::types::decima::core::entity::Entity;
     use libdecima::types::decima::core::player::Player;
@@ -66,17 +66,17 @@ pub mod internal {
             _ => unreachable!(),
         }
 
-        let present_ptr = Offset::from_signature(
-            "48 8D 0D ? ? ? ? 66 89 68 08 48 89 08 40 88 68 0A 48 89 68 0C 48 89 68 18 48 89 68 20",
-        )
-        .unwrap()
-        .as_relative(7)
-        .as_adjusted(size_of::<*mut c_void>() * 10)
-        .as_ptr::<*mut c_void>();
+        let present_ptr = match Offsets::find("nx::NxDXGIImpl::fn_present") {
+            Ok(offset) => offset.as_ptr::<c_void>(),
+            Err(error) => {
+                log!("focus::internal", "present not found ({}), not attaching.", error);
+                return;
+            }
+        };
         // log!("focus::internal", "{:p}", present_ptr);
 
         let present_hook =
-            unsafe { MhHook::new(*present_ptr, present_hook_impl as *mut _).unwrap() };
+            unsafe { MhHook::new(present_ptr, present_hook_impl as *mut _).unwrap() };
 
         unsafe {
             DXGI_PRESENT
@@ -196,7 +196,13 @@ pub mod internal {
                     .open(&mut self.transforms_open)
                     .show(ctx, |ui| {
                         if self.player_entity_ref.is_none() {
-                            let player = Player::get_local_player(0);
+                            let player = match Player::get_local_player(0) {
+                                Ok(player) => player,
+                                Err(error) => {
+                                    ui.label(format!("Local player unavailable: {error:?}"));
+                                    return;
+                                }
+                            };
                             let player = unsafe { &*player };
                             self.player_entity_ref = Some(unsafe { &mut *player.entity });
                             self.player_camera_ref =
@@ -204,7 +210,13 @@ pub mod internal {
                         }
 
                         if ui.button("get references").clicked() {
-                            let player = Player::get_local_player(0);
+                            let player = match Player::get_local_player(0) {
+                                Ok(player) => player,
+                                Err(error) => {
+                                    ui.label(format!("Local player unavailable: {error:?}"));
+                                    return;
+                                }
+                            };
                             let player = unsafe { &*player };
                             self.player_entity_ref = Some(unsafe { &mut *player.entity });
                             self.player_camera_ref =
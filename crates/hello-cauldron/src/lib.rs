@@ -1,6 +1,8 @@
-use cauldron::{CauldronLoader, CauldronPlugin, define_cauldron_plugin};
+use cauldron::context::PluginContext;
+use cauldron::{CauldronPlugin, cauldron_plugin};
 use libdecima::log;
 
+#[cauldron_plugin(name = "Hello Cauldron")]
 pub struct HelloCauldron {}
 
 impl CauldronPlugin for HelloCauldron {
@@ -8,7 +10,7 @@ impl CauldronPlugin for HelloCauldron {
         HelloCauldron {}
     }
 
-    fn on_init(&self, _loader: &CauldronLoader) {
+    fn on_init(&self, _context: &PluginContext) {
         log!("Hello Cauldron!");
     }
 
@@ -19,5 +21,3 @@ impl CauldronPlugin for HelloCauldron {
 
 unsafe impl Send for HelloCauldron {}
 unsafe impl Sync for HelloCauldron {}
-
-define_cauldron_plugin!(HelloCauldron, include_str!("../hello.cauldron.toml"));
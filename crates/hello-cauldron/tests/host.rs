@@ -0,0 +1,37 @@
+use cauldron::test_host::TestHost;
+use cauldron::version::CauldronGameType;
+use hello_cauldron::HelloCauldron;
+
+#[test]
+fn initializes() {
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_plugin::<HelloCauldron>(HelloCauldron::CAULDRON_METADATA)
+        .run();
+
+    assert_eq!(report.error, None);
+    assert!(report.plugin("hello-cauldron").unwrap().initialized);
+}
+
+#[test]
+fn initializes_from_its_library() {
+    // `cargo test` only builds the cdylib into `deps` next to the test, it's copied up into the
+    // profile directory by a `cargo build`.
+    let exe = std::env::current_exe().unwrap();
+    let name = format!(
+        "{}hello_cauldron{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    let deps = exe.parent().unwrap();
+    let library = [deps.join(&name), deps.parent().unwrap().join(&name)]
+        .into_iter()
+        .find(|path| path.exists())
+        .expect("the hello-cauldron library wasn't built");
+    let report = TestHost::new(CauldronGameType::HorizonForbiddenWest)
+        .with_library(&library)
+        .run();
+
+    assert_eq!(report.error, None);
+    assert!(report.rejected.is_empty(), "{:?}", report.rejected);
+    assert!(report.plugin("hello-cauldron").unwrap().initialized);
+}
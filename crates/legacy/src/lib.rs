@@ -2,10 +2,16 @@ mod hooks;
 mod types;
 
 use crate::hooks::init_hooks;
-use cauldron::{CauldronLoader, CauldronPlugin, define_cauldron_plugin};
+use cauldron::context::PluginContext;
+use cauldron::{CauldronPlugin, cauldron_plugin};
 use libdecima::log;
 use libdecima::mem::offsets::Offsets;
 
+#[cauldron_plugin(
+    name = "The Legacy",
+    description = "That which is lost or forbidden. Py's HFW expriments.",
+    supported_games(hfw)
+)]
 pub struct LegacyCauldron {}
 
 impl CauldronPlugin for LegacyCauldron {
@@ -13,7 +19,7 @@ impl CauldronPlugin for LegacyCauldron {
         LegacyCauldron {}
     }
 
-    fn on_init(&self, _loader: &CauldronLoader) {
+    fn on_init(&self, _context: &PluginContext) {
         log!("That which is lost or forbidden.");
         Offsets::setup();
         init_hooks();
@@ -23,5 +29,3 @@ impl CauldronPlugin for LegacyCauldron {
 
 unsafe impl Send for LegacyCauldron {}
 unsafe impl Sync for LegacyCauldron {}
-
-define_cauldron_plugin!(LegacyCauldron, include_str!("../legacy.cauldron.toml"));
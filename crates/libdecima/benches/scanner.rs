@@ -0,0 +1,78 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use libdecima::mem::pattern::parse_pattern;
+use libdecima::mem::scanner::{MultiScanner, Scanner, find_naive};
+use std::hint::black_box;
+
+const SIZE: usize = 100 * 1024 * 1024;
+
+const PATTERNS: [&str; 4] = [
+    "48 8B 0D ? ? ? ? 8B D3 4C 8B 01 41 FF 90 ? ? 00 00",
+    "40 57 48 83 EC 30 48 63 F9 48 8B 0D ? ? ? ? 48 85 C9",
+    "E8 ? ? ? ? 0F B6 F8 47 38 ? ? 75 05 E8",
+    "4C 8B DC 49 89 5B 10 49 89 73 18 55 57 41 54",
+];
+
+/// Pseudo-random bytes skewed towards common x86 opcodes, with each pattern planted once near
+/// the end so every scan covers (almost) the whole buffer.
+fn image() -> Vec<u8> {
+    const COMMON: [u8; 8] = [0x00, 0x48, 0x8B, 0x89, 0xFF, 0xCC, 0x0F, 0xE8];
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut image = (0..SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            if state % 3 == 0 {
+                COMMON[(state >> 8) as usize % COMMON.len()]
+            } else {
+                (state >> 16) as u8
+            }
+        })
+        .collect::<Vec<_>>();
+    for (index, pattern) in PATTERNS.iter().enumerate() {
+        let offset = SIZE - (index + 1) * 4096;
+        for (byte, pattern) in parse_pattern(pattern).unwrap().iter().enumerate() {
+            image[offset + byte] = pattern.value;
+        }
+    }
+    image
+}
+
+fn scanner(c: &mut Criterion) {
+    let image = image();
+    let patterns = PATTERNS
+        .iter()
+        .map(|pattern| parse_pattern(pattern).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("scan 100 MB");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(SIZE as u64));
+    for (index, pattern) in patterns.iter().enumerate() {
+        group.bench_with_input(BenchmarkId::new("naive", index), pattern, |b, pattern| {
+            b.iter(|| find_naive(pattern, black_box(&image)))
+        });
+        let scanner = Scanner::new(pattern);
+        group.bench_with_input(
+            BenchmarkId::new("scanner", index),
+            &scanner,
+            |b, scanner| b.iter(|| scanner.find(black_box(&image))),
+        );
+    }
+    group.bench_function("naive, all patterns", |b| {
+        b.iter(|| {
+            patterns
+                .iter()
+                .map(|pattern| find_naive(pattern, black_box(&image)))
+                .collect::<Vec<_>>()
+        })
+    });
+    let multi = MultiScanner::new(&patterns);
+    group.bench_function("multi scanner, all patterns", |b| {
+        b.iter(|| multi.find_all(black_box(&image)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scanner);
+criterion_main!(benches);
@@ -1,4 +1,3 @@
-#![feature(once_cell_get_mut)]
 // #![feature(macro_metavar_expr_concat)]
 #![allow(static_mut_refs)]
 
@@ -7,6 +6,7 @@
 compile_error!("At least one target feature must be enabled.");
 
 pub mod mem;
+pub mod platform;
 pub mod types;
 pub mod util;
 
@@ -73,14 +73,144 @@ pub mod macros {
         };
     }
 
+    /// Parses an ida-style pattern at compile time into a `&'static [PatternByte]`, see
+    /// [mem::pattern](crate::mem::pattern) for the syntax.
+    ///
+    /// ```
+    /// use libdecima::mem::pattern::PatternByte;
+    /// use libdecima::pattern;
+    ///
+    /// const CALL: &[PatternByte] = pattern!("E8 ? ? ? ? 4?");
+    /// assert_eq!(CALL.len(), 6);
+    /// assert!(CALL[5].matches(0x48));
+    /// ```
+    ///
+    /// Invalid patterns fail to build:
+    ///
+    /// ```compile_fail
+    /// let pattern = libdecima::pattern!("48 8G");
+    /// ```
+    #[macro_export]
+    macro_rules! pattern {
+        ($pattern:literal) => {{
+            const LEN: usize = match $crate::mem::pattern::pattern_len($pattern) {
+                Ok(len) => len,
+                Err(error) => panic!("{}", error.reason()),
+            };
+            const PATTERN: [$crate::mem::pattern::PatternByte; LEN] =
+                $crate::mem::pattern::compile::<LEN>($pattern);
+            &PATTERN as &'static [$crate::mem::pattern::PatternByte]
+        }};
+    }
+
+    /// Declares typed bindings to game functions found by signature.
+    ///
+    /// Bindings either name an entry of the loaded signature database with `Offsets["name"]`,
+    /// or give a pattern and optional resolution steps (methods on
+    /// [Offset](crate::mem::offsets::Offset), fallible ones included) inline. Either way the
+    /// function is looked up the first time it's called and the address is cached. Patterns
+    /// cache failures too, while database entries are looked up again until they're found, as
+    /// the database may not be loaded yet. Calls return `Err` instead of panicking when the
+    /// function can't be found.
+    ///
+    /// The bindings are `unsafe fn`s, the game function is called with whatever arguments
+    /// they're given. Wrap them in safe functions checking what the game expects.
+    ///
+    /// ```no_run
+    /// use libdecima::decima_fn;
+    /// use std::ffi::c_char;
+    ///
+    /// decima_fn! {
+    ///     /// `RTTI const * RTTI::FindTypeByName(char const *)`
+    ///     fn find_type_by_name(name: *const c_char) -> *const u8 = Offsets["RTTI::FindByName"];
+    ///
+    ///     // resolved through the call instruction at the match.
+    ///     pub fn get_local_player(index: u32) -> *mut u8 = "E8 ? ? ? ? 48 8B D8" => as_target();
+    /// }
+    ///
+    /// let player = unsafe { get_local_player(0)? };
+    /// # Ok::<(), libdecima::mem::PatternSearchError>(())
+    /// ```
+    #[macro_export]
+    macro_rules! decima_fn {
+        (@ret) => { () };
+        (@ret $ret:ty) => { $ret };
+        (
+            @bind [$(#[$attr:meta])*] $vis:vis $name:ident($($arg:ident: $arg_t:ty),*)
+                ($($ret:ty)?) $address:block
+        ) => {
+            $(#[$attr])*
+            ///
+            /// # Safety
+            /// Calls the game function as it is, the arguments have to be what it expects.
+            $vis unsafe fn $name(
+                $($arg: $arg_t),*
+            ) -> ::std::result::Result<$crate::decima_fn!(@ret $($ret)?), $crate::mem::PatternSearchError> {
+                let address: usize = $address;
+                let func = unsafe {
+                    ::std::mem::transmute::<*const (), extern "C" fn($($arg_t),*) $(-> $ret)?>(
+                        address as *const (),
+                    )
+                };
+                Ok(func($($arg),*))
+            }
+        };
+        () => {};
+        (
+            $(#[$attr:meta])*
+            $vis:vis fn $name:ident($($arg:ident: $arg_t:ty),* $(,)?) $(-> $ret:ty)? =
+                Offsets[$signature:literal];
+            $($rest:tt)*
+        ) => {
+            $crate::decima_fn!(@bind [$(#[$attr])*] $vis $name($($arg: $arg_t),*) ($($ret)?) {
+                static ADDRESS: ::std::sync::OnceLock<usize> = ::std::sync::OnceLock::new();
+                match ADDRESS.get() {
+                    Some(address) => *address,
+                    None => {
+                        let address = $crate::mem::offsets::Offsets::find($signature)?
+                            .as_ptr::<u8>() as usize;
+                        *ADDRESS.get_or_init(|| address)
+                    }
+                }
+            });
+            $crate::decima_fn!($($rest)*);
+        };
+        (
+            $(#[$attr:meta])*
+            $vis:vis fn $name:ident($($arg:ident: $arg_t:ty),* $(,)?) $(-> $ret:ty)? =
+                $signature:literal $(=> $($step:ident($($step_arg:expr),*)).+)?;
+            $($rest:tt)*
+        ) => {
+            $crate::decima_fn!(@bind [$(#[$attr])*] $vis $name($($arg: $arg_t),*) ($($ret)?) {
+                static ADDRESS: ::std::sync::OnceLock<
+                    ::std::result::Result<usize, $crate::mem::PatternSearchError>,
+                > = ::std::sync::OnceLock::new();
+                ADDRESS
+                    .get_or_init(|| {
+                        let offset =
+                            $crate::mem::offsets::Offset::from_pattern($crate::pattern!($signature))?;
+                        $($(
+                            let offset = $crate::mem::offsets::IntoOffsetResult::into_offset_result(
+                                offset.$step($($step_arg),*),
+                            )?;
+                        )+)?
+                        Ok(offset.as_ptr::<u8>() as usize)
+                    })
+                    .clone()?
+            });
+            $crate::decima_fn!($($rest)*);
+        };
+    }
+
+    /// Implements `get_instance` for a global instance pointer, found through the signature
+    /// database entry `$signature`.
     #[macro_export]
     macro_rules! impl_instance {
-        ($name:ident, $signature:literal, $instruction_length:literal) => {
+        ($name:ident, $signature:literal) => {
             impl $name {
                 pub fn get_instance() -> Option<&'static $name> {
-                    let ptr = crate::mem::offsets::Offset::from_signature($signature)
-                        .unwrap()
-                        .as_relative($instruction_length)
+                    let ptr = $crate::mem::offsets::Offsets::find($signature)
+                        .ok()?
                         .as_ptr::<*mut $name>();
                     if !ptr.is_null() {
                         let ptr = unsafe { *ptr };
@@ -93,13 +223,10 @@ pub mod macros {
                 }
             }
         };
-        ($name:ident, $signature:literal) => {
-            impl_instance!($name, $signature, 7);
-        };
     }
 }
 
-#[cfg(feature = "nixxes")]
+#[cfg(all(feature = "nixxes", windows))]
 pub mod log {
     use crate::types::nixxes::log::NxLogImpl;
 
@@ -129,18 +256,20 @@ pub mod log {
     }
 }
 
-#[cfg(not(feature = "nixxes"))]
+#[cfg(not(all(feature = "nixxes", windows)))]
 pub mod log {
     #[macro_export]
     macro_rules! log {
         // log!("category", *format! args*);
-        ($category:literal, $($arg:tt)*) => {
-            // todo: unimplemented
-        };
+        ($category:literal, $($arg:tt)*) => {{
+            // todo: unimplemented, the arguments are still used so they don't warn.
+            let _ = ($category, format_args!($($arg)*));
+        }};
 
         // log!(*format! args*);
-        ($($arg:tt)*) => {
+        ($($arg:tt)*) => {{
             // todo: unimplemented
-        };
+            let _ = format_args!($($arg)*);
+        }};
     }
 }
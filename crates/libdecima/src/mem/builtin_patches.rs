@@ -0,0 +1,207 @@
+//! The loader's own patches to game code, see `data/patches` for the format.
+//!
+//! Like signatures, the patches are data: each names the signature giving its address and the
+//! bytes to write there, so one can be added, changed for a game update or turned off without a
+//! new build. [PatchDatabase::apply] makes them through a [PatchRegistry], so they're listed and
+//! reverted with every other patch.
+
+use crate::mem::PatternSearchError;
+use crate::mem::patches::{Patch, PatchError, PatchMemory, PatchRegistry};
+use crate::mem::pattern::parse_pattern;
+use crate::mem::versions::{Versioned, select};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Owner of the built-in patches in the registry.
+pub const OWNER: &str = "cauldron";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchDatabase {
+    /// Id of the game the database is for, e.g. `hfw`.
+    pub game: String,
+    #[serde(default, rename = "patch")]
+    pub patches: Vec<BuiltinPatch>,
+}
+
+impl PatchDatabase {
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    /// The database shipped with libdecima for `game`, if there is one.
+    pub fn builtin(game: &str) -> Option<Self> {
+        let source = match game {
+            "hfw" => include_str!("../../data/patches/hfw.toml"),
+            _ => return None,
+        };
+        Some(Self::parse(source).expect("builtin patch database is invalid"))
+    }
+
+    /// Adds the patches of `other`, replacing the entries with the same name.
+    pub fn merge(&mut self, other: PatchDatabase) {
+        let names = other
+            .patches
+            .iter()
+            .map(|patch| patch.name.clone())
+            .collect::<Vec<_>>();
+        self.patches.retain(|patch| !names.contains(&patch.name));
+        self.patches.extend(other.patches);
+    }
+
+    /// The entries for game `version`, one per name, see [select].
+    pub fn select(&self, version: &str) -> Vec<&BuiltinPatch> {
+        select(&self.patches, version)
+    }
+
+    /// Applies the patches for game `version` to `registry` as [OWNER], in order. `enabled`
+    /// decides which are made, `resolve` gives the address of a signature.
+    ///
+    /// # Safety
+    /// Every address `resolve` gives must be valid to patch with the bytes of the patches using
+    /// it, see [PatchRegistry::enable].
+    pub unsafe fn apply<M: PatchMemory>(
+        &self,
+        registry: &mut PatchRegistry<M>,
+        version: &str,
+        enabled: impl Fn(&BuiltinPatch) -> bool,
+        resolve: impl Fn(&str) -> Result<usize, PatternSearchError>,
+    ) -> Vec<BuiltinPatchStatus> {
+        self.select(version)
+            .into_iter()
+            .map(|patch| {
+                let state = if !enabled(patch) {
+                    BuiltinPatchState::Disabled
+                } else {
+                    match unsafe { patch.apply(registry, &resolve) } {
+                        Ok(()) => BuiltinPatchState::Applied,
+                        Err(error) => BuiltinPatchState::Failed(error),
+                    }
+                };
+                BuiltinPatchStatus {
+                    name: patch.name.clone(),
+                    description: patch.description.clone(),
+                    state,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuiltinPatch {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Game versions the entry applies to, prefixes or ranges of `major.minor.patch.build`,
+    /// see [versions](crate::mem::versions). Empty for every version.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+    /// Name of the signature giving the address.
+    pub signature: String,
+    /// Hex bytes written at the address, e.g. `B0 01 90`.
+    pub bytes: String,
+    /// Hex bytes that have to be at the address before it's patched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Whether the patch is made when the config doesn't say.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Versioned for BuiltinPatch {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn versions(&self) -> &[String] {
+        &self.versions
+    }
+}
+
+impl BuiltinPatch {
+    /// The patch at `address`.
+    pub fn to_patch(&self, address: usize) -> Result<Patch, PatternSearchError> {
+        Ok(Patch {
+            name: self.name.clone(),
+            owner: OWNER.to_string(),
+            address,
+            bytes: parse_bytes(&self.bytes)?,
+            expected: self.expected.as_deref().map(parse_bytes).transpose()?,
+        })
+    }
+
+    unsafe fn apply<M: PatchMemory>(
+        &self,
+        registry: &mut PatchRegistry<M>,
+        resolve: impl Fn(&str) -> Result<usize, PatternSearchError>,
+    ) -> Result<(), BuiltinPatchError> {
+        let address = resolve(&self.signature).map_err(|error| BuiltinPatchError::Signature {
+            name: self.signature.clone(),
+            error,
+        })?;
+        let patch = self
+            .to_patch(address)
+            .map_err(BuiltinPatchError::InvalidBytes)?;
+        unsafe { registry.apply(patch) }.map_err(BuiltinPatchError::Patch)
+    }
+}
+
+/// Parses hex bytes like a pattern, without wildcards.
+fn parse_bytes(bytes: &str) -> Result<Vec<u8>, PatternSearchError> {
+    parse_pattern(bytes)?
+        .into_iter()
+        .enumerate()
+        .map(|(position, byte)| match byte.mask {
+            0xFF => Ok(byte.value),
+            _ => Err(PatternSearchError::Malformed {
+                position,
+                reason: "wildcards can't be written",
+            }),
+        })
+        .collect()
+}
+
+/// What became of a built-in patch, see [PatchDatabase::apply].
+#[derive(Debug, Clone)]
+pub struct BuiltinPatchStatus {
+    pub name: String,
+    pub description: String,
+    pub state: BuiltinPatchState,
+}
+
+#[derive(Debug, Clone)]
+pub enum BuiltinPatchState {
+    Applied,
+    /// Turned off in the config or the database.
+    Disabled,
+    Failed(BuiltinPatchError),
+}
+
+#[derive(Debug, Clone)]
+pub enum BuiltinPatchError {
+    /// The signature `name` giving the address wasn't found.
+    Signature {
+        name: String,
+        error: PatternSearchError,
+    },
+    InvalidBytes(PatternSearchError),
+    Patch(PatchError),
+}
+
+impl Display for BuiltinPatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuiltinPatchError::Signature { name, error } => {
+                write!(f, "signature {}: {}", name, error)
+            }
+            BuiltinPatchError::InvalidBytes(error) => write!(f, "invalid bytes: {}", error),
+            BuiltinPatchError::Patch(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BuiltinPatchError {}
@@ -0,0 +1,140 @@
+//! Resolved signatures persisted between launches, see [OffsetCache].
+//!
+//! Scanning the whole executable for every signature takes a while, but the results only change
+//! when the game is updated or a signature is edited. The cache is stored per executable hash,
+//! each entry is keyed by a [fingerprint](Signature::fingerprint) of its signature, and entries
+//! are only used if their pattern still matches where it was found.
+
+use crate::mem::image::Image;
+use crate::mem::signatures::{Signature, SignatureCheck, check_all_image};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::{fs, io};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffsetCache {
+    /// Hash of the executable the entries were found in, see [OffsetCache::hash].
+    pub executable: String,
+    #[serde(default, rename = "entry")]
+    pub entries: Vec<CacheEntry>,
+}
+
+/// Where a signature was found. Signatures that weren't found aren't cached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub name: String,
+    /// [Signature::fingerprint] of the signature when it was found.
+    pub signature: String,
+    /// Index of the pattern that matched, see [SignatureCheck::found].
+    pub pattern: usize,
+    /// RVA of the first match, before any steps.
+    pub rva: usize,
+    pub matches: usize,
+}
+
+impl OffsetCache {
+    pub fn new(executable: &str) -> Self {
+        OffsetCache {
+            executable: executable.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Lowercase hex SHA-256 of an executable's bytes.
+    pub fn hash(executable: &[u8]) -> String {
+        Sha256::digest(executable)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// `offsets-<executable hash>.toml`.
+    pub fn file_name(executable: &str) -> String {
+        format!("offsets-{}.toml", executable)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    /// The cache for the executable hashed to `executable` in `dir`, empty if there is none or
+    /// it can't be read.
+    pub fn load(dir: &Path, executable: &str) -> Self {
+        fs::read_to_string(dir.join(OffsetCache::file_name(executable)))
+            .ok()
+            .and_then(|source| OffsetCache::parse(&source).ok())
+            .filter(|cache| cache.executable == executable)
+            .unwrap_or_else(|| OffsetCache::new(executable))
+    }
+
+    /// Writes the cache to `dir`, removing caches of any other executable, which are left over
+    /// from before a game update.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let file_name = OffsetCache::file_name(&self.executable);
+        for entry in fs::read_dir(dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("offsets-") && name.ends_with(".toml") && name != file_name {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        let source = toml::to_string(self).expect("offset cache can't be serialized");
+        fs::write(dir.join(file_name), source)
+    }
+
+    pub fn get(&self, signature: &Signature) -> Option<&CacheEntry> {
+        let fingerprint = signature.fingerprint();
+        self.entries
+            .iter()
+            .find(|entry| entry.name == signature.name && entry.signature == fingerprint)
+    }
+
+    /// The cached check of `signature`, if its pattern (or string reference) still matches at
+    /// the cached RVA in `image`.
+    pub fn verify(&self, signature: &Signature, image: &Image) -> Option<SignatureCheck> {
+        let entry = self.get(signature)?;
+        if !signature.matches_at(image, entry.pattern, entry.rva) {
+            return None;
+        }
+        Some(signature.matched(image, entry.pattern, image.at_rva(entry.rva), entry.matches))
+    }
+
+    /// Checks `signatures` in `image` like [check_all_image], scanning only for those without a
+    /// verified entry, then replaces the entries with what was found.
+    pub fn check(&mut self, signatures: &[Signature], image: &Image) -> Vec<SignatureCheck> {
+        let cached = signatures
+            .iter()
+            .map(|signature| self.verify(signature, image))
+            .collect::<Vec<_>>();
+        let uncached = signatures
+            .iter()
+            .zip(&cached)
+            .filter(|(_, check)| check.is_none())
+            .map(|(signature, _)| signature.clone())
+            .collect::<Vec<_>>();
+        let mut scanned = check_all_image(&uncached, image).into_iter();
+        let checks = cached
+            .into_iter()
+            .map(|check| check.or_else(|| scanned.next()))
+            .collect::<Option<Vec<_>>>()
+            .expect("a check for every signature");
+
+        self.entries = signatures
+            .iter()
+            .zip(&checks)
+            .filter(|(_, check)| check.result.is_ok())
+            .filter_map(|(signature, check)| {
+                let (pattern, found) = check.found?;
+                Some(CacheEntry {
+                    name: signature.name.clone(),
+                    signature: signature.fingerprint(),
+                    pattern,
+                    rva: image.rva(found)?,
+                    matches: check.matches,
+                })
+            })
+            .collect();
+        checks
+    }
+}
@@ -0,0 +1,142 @@
+//! Function boundaries from the x64 exception directory (`.pdata`), see [FunctionTable].
+//!
+//! Every function that touches the stack or calls another one has a `RUNTIME_FUNCTION` entry so
+//! exceptions can unwind through it, sorted by start address. Leaf functions have none. Functions
+//! the compiler split into parts have one entry per part, with every part but the first chained
+//! to the entry before it through its unwind info.
+
+use crate::mem::image::{Image, read_at};
+use crate::mem::pe::{IMAGE_DIRECTORY_ENTRY_EXCEPTION, ImageRuntimeFunctionEntry};
+
+/// `UNW_FLAG_CHAININFO`, the unwind info is followed by the entry of the part before.
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+/// Chains longer than this are treated as broken rather than followed forever.
+const MAX_CHAIN_LEN: usize = 32;
+
+const ENTRY_SIZE: usize = size_of::<ImageRuntimeFunctionEntry>();
+
+/// A `.pdata` entry, as RVAs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub start: usize,
+    /// One past the last byte.
+    pub end: usize,
+    pub unwind_info: usize,
+}
+
+impl Function {
+    fn from_entry(entry: ImageRuntimeFunctionEntry) -> Self {
+        Function {
+            start: entry.begin_address as usize,
+            end: entry.end_address as usize,
+            unwind_info: entry.unwind_info_address as usize,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, rva: usize) -> bool {
+        (self.start..self.end).contains(&rva)
+    }
+}
+
+/// The exception directory of an image, read in place.
+#[derive(Debug, Copy, Clone)]
+pub struct FunctionTable<'a> {
+    image: &'a [u8],
+    pdata: &'a [u8],
+}
+
+impl<'a> FunctionTable<'a> {
+    /// The table of `image`, empty if it has no exception directory, like plain buffers.
+    pub fn new(image: &Image<'a>) -> Self {
+        FunctionTable {
+            image: image.bytes(),
+            pdata: image
+                .directory(IMAGE_DIRECTORY_ENTRY_EXCEPTION)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pdata.len() / ENTRY_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<Function> {
+        read_at::<ImageRuntimeFunctionEntry>(self.pdata, index.checked_mul(ENTRY_SIZE)?)
+            .map(Function::from_entry)
+    }
+
+    /// Every entry in address order, including the parts of split functions.
+    pub fn entries(&self) -> impl Iterator<Item = Function> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    /// Every function in address order, skipping the parts chained to another entry.
+    pub fn functions(&self) -> impl Iterator<Item = Function> + '_ {
+        self.entries().filter(|entry| self.chained(entry).is_none())
+    }
+
+    /// The entry covering `rva`, which may be part of a split function.
+    pub fn entry_containing(&self, rva: usize) -> Option<Function> {
+        // entries are sorted and don't overlap, so the candidate is the last one starting at or
+        // before the address.
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.get(middle)?.start <= rva {
+                true => low = middle + 1,
+                false => high = middle,
+            }
+        }
+        self.get(low.checked_sub(1)?)
+            .filter(|entry| entry.contains(rva))
+    }
+
+    /// The function containing `rva`, following split parts back to the entry of the function's
+    /// start. Its `end` is that of the first part.
+    pub fn function_containing(&self, rva: usize) -> Option<Function> {
+        let mut function = self.entry_containing(rva)?;
+        for _ in 0..MAX_CHAIN_LEN {
+            match self.chained(&function) {
+                Some(parent) => function = parent,
+                None => return Some(function),
+            }
+        }
+        None
+    }
+
+    /// Whether `rva` is where a function starts, e.g. to check a signature lands on one.
+    pub fn is_function_start(&self, rva: usize) -> bool {
+        self.function_containing(rva)
+            .is_some_and(|function| function.start == rva)
+    }
+
+    /// The entry `entry` is chained to, if it's a later part of a split function.
+    fn chained(&self, entry: &Function) -> Option<Function> {
+        // an odd unwind info address points at the entry itself rather than at unwind info.
+        if entry.unwind_info & 1 != 0 {
+            return read_at::<ImageRuntimeFunctionEntry>(self.image, entry.unwind_info & !1)
+                .map(Function::from_entry);
+        }
+        let header = read_at::<[u8; 4]>(self.image, entry.unwind_info)?;
+        if (header[0] >> 3) & UNW_FLAG_CHAININFO == 0 {
+            return None;
+        }
+        // the unwind codes are 2 bytes each, padded to an even count.
+        let codes = (header[2] as usize).next_multiple_of(2) * size_of::<u16>();
+        read_at::<ImageRuntimeFunctionEntry>(self.image, entry.unwind_info + 4 + codes)
+            .map(Function::from_entry)
+    }
+}
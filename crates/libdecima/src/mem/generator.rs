@@ -0,0 +1,136 @@
+//! Generates signatures for code in an image, see [generate_signature].
+//!
+//! Instructions are decoded from the address and turned into pattern bytes, with everything the
+//! linker or a recompile moves wildcarded: rel32 branch and call targets, and RIP-relative
+//! displacements. The pattern is then cut down to the shortest prefix only matching once.
+
+use crate::mem::image::Image;
+use crate::mem::pattern::{PatternByte, format_pattern};
+use crate::mem::scanner::Scanner;
+use iced_x86::{Code, Decoder, DecoderOptions, OpKind};
+use std::fmt::{Display, Formatter};
+
+/// Longest pattern generated, in bytes.
+pub const MAX_SIGNATURE_LEN: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The address isn't in the section searched.
+    OutOfRange,
+    /// The bytes at `rva` don't decode as an instruction, or are padding.
+    InvalidInstruction { rva: usize },
+    /// The longest pattern generated still matches `matches` times.
+    NotUnique { matches: usize },
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerateError::OutOfRange => write!(f, "address outside of the section"),
+            GenerateError::InvalidInstruction { rva } => {
+                write!(f, "invalid instruction at {:#x}", rva)
+            }
+            GenerateError::NotUnique { matches } => {
+                write!(f, "no unique pattern, still {} matches", matches)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// The instructions at `position` in `code`, up to `max_len` bytes of them or the first `int3`,
+/// as a pattern with relocatable operands wildcarded. `rva` is the RVA of the start of `code`.
+pub fn instruction_pattern(
+    code: &[u8],
+    rva: usize,
+    position: usize,
+    max_len: usize,
+) -> Result<Vec<PatternByte>, GenerateError> {
+    let bytes = code.get(position..).ok_or(GenerateError::OutOfRange)?;
+    let mut decoder = Decoder::with_ip(64, bytes, (rva + position) as u64, DecoderOptions::NONE);
+    let mut pattern = Vec::new();
+    while pattern.len() < max_len && decoder.can_decode() {
+        let offset = decoder.position();
+        let instruction = decoder.decode();
+        if instruction.is_invalid() {
+            if pattern.is_empty() {
+                return Err(GenerateError::InvalidInstruction {
+                    rva: rva + position + offset,
+                });
+            }
+            break;
+        }
+        // padding between functions, the pattern shouldn't run into the next one.
+        if instruction.code() == Code::Int3 {
+            break;
+        }
+        let offsets = decoder.get_constant_offsets(&instruction);
+        let mut wildcards = Vec::new();
+        if instruction.is_ip_rel_memory_operand() {
+            wildcards.push((offsets.displacement_offset(), offsets.displacement_size()));
+        }
+        let branch = (0..instruction.op_count())
+            .any(|operand| instruction.op_kind(operand) == OpKind::NearBranch64);
+        // rel8 branches stay inside the function, only rel32 ones can point anywhere.
+        if branch && offsets.immediate_size() == 4 {
+            wildcards.push((offsets.immediate_offset(), offsets.immediate_size()));
+        }
+
+        for (index, byte) in bytes[offset..offset + instruction.len()].iter().enumerate() {
+            let wildcard = wildcards
+                .iter()
+                .any(|(start, size)| (*start..start + size).contains(&index));
+            pattern.push(match wildcard {
+                true => PatternByte::ANY,
+                false => PatternByte::exact(*byte),
+            });
+        }
+    }
+    pattern.truncate(max_len);
+    Ok(pattern)
+}
+
+/// The shortest pattern for the code at `rva` only matching once in `section` of `image`.
+pub fn generate_pattern(
+    image: &Image,
+    section: &str,
+    rva: usize,
+) -> Result<Vec<PatternByte>, GenerateError> {
+    let code = image
+        .search_range(section)
+        .map_err(|_| GenerateError::OutOfRange)?;
+    let start = code.as_ptr() as usize - image.base();
+    let position = rva
+        .checked_sub(start)
+        .filter(|position| *position < code.len())
+        .ok_or(GenerateError::OutOfRange)?;
+    let pattern = instruction_pattern(code, start, position, MAX_SIGNATURE_LEN)?;
+
+    // longer prefixes never match more often, so the shortest unique one can be binary searched.
+    // only prefixes ending in an exact byte are considered, trailing wildcards add nothing.
+    let lengths = (1..=pattern.len())
+        .filter(|len| pattern[len - 1].mask != 0)
+        .collect::<Vec<_>>();
+    let matches = |len: usize| {
+        Scanner::new(&pattern[..len])
+            .find_iter(code)
+            .take(2)
+            .count()
+    };
+    let longest = *lengths
+        .last()
+        .ok_or(GenerateError::InvalidInstruction { rva })?;
+    if matches(longest) > 1 {
+        return Err(GenerateError::NotUnique {
+            matches: Scanner::new(&pattern[..longest]).find_iter(code).count(),
+        });
+    }
+    let shortest = lengths.partition_point(|len| matches(*len) > 1);
+    Ok(pattern[..lengths[shortest]].to_vec())
+}
+
+/// [generate_pattern] for `.text`, formatted as an IDA-style signature.
+pub fn generate_signature(image: &Image, rva: usize) -> Result<String, GenerateError> {
+    Ok(format_pattern(&generate_pattern(image, ".text", rva)?))
+}
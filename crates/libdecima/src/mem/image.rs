@@ -0,0 +1,249 @@
+//! Images to search for patterns in, see [Image].
+//!
+//! An image is a byte slice laid out the way it would be in memory: the live game module, a PE
+//! file read from disk and mapped with [Image::map], or a plain buffer without headers. Offline
+//! tools and tests can resolve the same signatures as the game does at runtime.
+
+use crate::mem::instruction::{MAX_INSTRUCTION_LEN, decode};
+use crate::mem::offsets::Offset;
+use crate::mem::pattern::PatternByte;
+use crate::mem::pe::{
+    IMAGE_DOS_SIGNATURE, IMAGE_NT_OPTIONAL_HDR64_MAGIC, IMAGE_NT_SIGNATURE,
+    IMAGE_NUMBEROF_DIRECTORY_ENTRIES, ImageDataDirectory, ImageDosHeader, ImageFileHeader,
+    ImageNtHeaders64, ImageSectionHeader,
+};
+use crate::mem::scanner::Scanner;
+use crate::mem::{PatternSearchError, get_module};
+use iced_x86::Instruction;
+use std::mem::offset_of;
+use std::ptr::read_unaligned;
+use std::slice;
+
+/// Reads a `T` at `offset` in `data`, if it's entirely in bounds.
+pub(crate) fn read_at<T: Copy>(data: &[u8], offset: usize) -> Option<T> {
+    let bytes = data.get(offset..offset.checked_add(size_of::<T>())?)?;
+    Some(unsafe { read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+#[derive(Debug, Clone)]
+pub struct Image<'a> {
+    data: &'a [u8],
+    /// Address the pointers stored in the image are relative to. The preferred base for files
+    /// mapped from disk, the actual one for everything else.
+    image_base: usize,
+    sections: Vec<ImageSectionHeader>,
+    directories: Vec<ImageDataDirectory>,
+}
+
+impl<'a> Image<'a> {
+    /// A plain buffer without headers or sections, searched as a whole.
+    pub fn new(data: &'a [u8]) -> Self {
+        Image {
+            data,
+            image_base: data.as_ptr() as usize,
+            sections: Vec::new(),
+            directories: Vec::new(),
+        }
+    }
+
+    /// A PE32+ image with its sections laid out at their RVAs, like a loaded module or the
+    /// output of [Image::map].
+    pub fn parse(data: &'a [u8]) -> Result<Self, PatternSearchError> {
+        let invalid = PatternSearchError::InvalidImage;
+        let dos_header = read_at::<ImageDosHeader>(data, 0).ok_or(invalid("no dos header"))?;
+        if dos_header.e_magic != IMAGE_DOS_SIGNATURE {
+            return Err(invalid("bad dos signature"));
+        }
+        let nt_offset =
+            usize::try_from(dos_header.e_lfanew).map_err(|_| invalid("bad e_lfanew"))?;
+        let nt_headers =
+            read_at::<ImageNtHeaders64>(data, nt_offset).ok_or(invalid("no nt headers"))?;
+        if nt_headers.signature != IMAGE_NT_SIGNATURE {
+            return Err(invalid("bad nt signature"));
+        }
+        if nt_headers.optional_header.magic != IMAGE_NT_OPTIONAL_HDR64_MAGIC {
+            return Err(invalid("not a PE32+ image"));
+        }
+
+        let file_header = nt_headers.file_header;
+        let sections_offset = nt_offset
+            + offset_of!(ImageNtHeaders64, file_header)
+            + size_of::<ImageFileHeader>()
+            + file_header.size_of_optional_header as usize;
+        let sections = (0..file_header.number_of_sections as usize)
+            .map(|index| {
+                read_at::<ImageSectionHeader>(
+                    data,
+                    sections_offset + index * size_of::<ImageSectionHeader>(),
+                )
+                .ok_or(invalid("section headers out of bounds"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let optional_header = nt_headers.optional_header;
+        let directory_count = (optional_header.number_of_rva_and_sizes as usize)
+            .min(IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+        let directories = optional_header.data_directory[..directory_count].to_vec();
+
+        Ok(Image {
+            data,
+            image_base: optional_header.image_base as usize,
+            sections,
+            directories,
+        })
+    }
+
+    /// The game module of the current process.
+    pub fn live() -> Result<Image<'static>, PatternSearchError> {
+        let (start, end) = get_module()?;
+        unsafe { Image::from_module(start, end - start) }
+    }
+
+    /// The loaded module of `size` bytes at `module`.
+    ///
+    /// # Safety
+    /// All `size` bytes at `module` have to be readable for as long as the image is used.
+    pub unsafe fn from_module(
+        module: usize,
+        size: usize,
+    ) -> Result<Image<'static>, PatternSearchError> {
+        let data = unsafe { slice::from_raw_parts(module as *const u8, size) };
+        let mut image = Image::parse(data)?;
+        // the loader already relocated everything to where the module actually is.
+        image.image_base = module;
+        Ok(image)
+    }
+
+    /// Lays out a PE file as read from disk the way the loader would, ready for
+    /// [Image::parse]. Imports and relocations are left alone.
+    pub fn map(file: &[u8]) -> Result<Vec<u8>, PatternSearchError> {
+        let headers = Image::parse(file)?;
+        let invalid = PatternSearchError::InvalidImage;
+        let dos_header = read_at::<ImageDosHeader>(file, 0).ok_or(invalid("no dos header"))?;
+        let optional_header = read_at::<ImageNtHeaders64>(file, dos_header.e_lfanew as usize)
+            .ok_or(invalid("no nt headers"))?
+            .optional_header;
+
+        let mut mapped = vec![0u8; optional_header.size_of_image as usize];
+        let header_size = (optional_header.size_of_headers as usize)
+            .min(file.len())
+            .min(mapped.len());
+        mapped[..header_size].copy_from_slice(&file[..header_size]);
+        for section in &headers.sections {
+            let size = section.size_of_raw_data.min(section.virtual_size) as usize;
+            let raw = section.pointer_to_raw_data as usize;
+            let virtual_address = section.virtual_address as usize;
+            let source = file
+                .get(raw..raw + size)
+                .ok_or(invalid("section data out of bounds"))?;
+            mapped
+                .get_mut(virtual_address..virtual_address + size)
+                .ok_or(invalid("section outside of the image"))?
+                .copy_from_slice(source);
+        }
+        Ok(mapped)
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Address of the first byte of the image.
+    pub fn base(&self) -> usize {
+        self.data.as_ptr() as usize
+    }
+
+    /// Address the pointers stored in the image are relative to, see [Image::deref].
+    pub fn image_base(&self) -> usize {
+        self.image_base
+    }
+
+    pub fn sections(&self) -> &[ImageSectionHeader] {
+        &self.sections
+    }
+
+    /// The bytes of the section called `name`, as far as they are in the image.
+    pub fn section(&self, name: &str) -> Result<&'a [u8], PatternSearchError> {
+        let section = self
+            .sections
+            .iter()
+            .find(|section| section.name() == name)
+            .ok_or(PatternSearchError::OutOfRange)?;
+        let start = (section.virtual_address as usize).min(self.data.len());
+        let end = (start + section.virtual_size as usize).min(self.data.len());
+        Ok(&self.data[start..end])
+    }
+
+    /// The data of directory `index`, e.g.
+    /// [IMAGE_DIRECTORY_ENTRY_EXCEPTION](crate::mem::pe::IMAGE_DIRECTORY_ENTRY_EXCEPTION), if the
+    /// image has one. Plain buffers have none.
+    pub fn directory(&self, index: usize) -> Option<&'a [u8]> {
+        let directory = self.directories.get(index)?;
+        let start = directory.virtual_address as usize;
+        let end = start.checked_add(directory.size as usize)?;
+        match directory.size {
+            0 => None,
+            _ => self.data.get(start..end),
+        }
+    }
+
+    /// Where a pattern for section `name` is searched: the section, or everything in plain
+    /// buffers, which have none.
+    pub fn search_range(&self, name: &str) -> Result<&'a [u8], PatternSearchError> {
+        if self.sections.is_empty() {
+            return Ok(self.data);
+        }
+        self.section(name)
+    }
+
+    pub fn contains(&self, offset: Offset) -> bool {
+        self.rva(offset).is_some()
+    }
+
+    /// Offset of `offset` from the start of the image, if it's in it.
+    pub fn rva(&self, offset: Offset) -> Option<usize> {
+        let rva = (offset.as_ptr::<u8>() as usize).checked_sub(self.base())?;
+        (rva < self.data.len()).then_some(rva)
+    }
+
+    pub fn at_rva(&self, rva: usize) -> Offset {
+        Offset::new(self.base().wrapping_add(rva))
+    }
+
+    /// Reads a `T` at `offset`, failing if it isn't entirely in the image.
+    pub fn read<T: Copy>(&self, offset: Offset) -> Result<T, PatternSearchError> {
+        let rva = self.rva(offset).ok_or(PatternSearchError::OutOfRange)?;
+        read_at(self.data, rva).ok_or(PatternSearchError::OutOfRange)
+    }
+
+    /// Decodes the instruction at `offset`, failing if it isn't in the image.
+    pub fn instruction(&self, offset: Offset) -> Result<Instruction, PatternSearchError> {
+        let rva = self.rva(offset).ok_or(PatternSearchError::OutOfRange)?;
+        let end = (rva + MAX_INSTRUCTION_LEN).min(self.data.len());
+        decode(&self.data[rva..end], offset.as_ptr::<u8>() as usize)
+    }
+
+    /// Follows the pointer at `offset`, translating it into the image if it was stored relative
+    /// to a different base.
+    pub fn deref(&self, offset: Offset) -> Result<Offset, PatternSearchError> {
+        let pointer = self.read::<usize>(offset)?;
+        Ok(Offset::new(
+            pointer
+                .wrapping_sub(self.image_base)
+                .wrapping_add(self.base()),
+        ))
+    }
+
+    /// The first match of `pattern` in section `section`, see [Image::search_range].
+    pub fn find(
+        &self,
+        section: &str,
+        pattern: &[PatternByte],
+    ) -> Result<Offset, PatternSearchError> {
+        let data = self.search_range(section)?;
+        Scanner::new(pattern)
+            .find(data)
+            .map(|offset| Offset::new(data.as_ptr() as usize + offset))
+            .ok_or(PatternSearchError::NotFound)
+    }
+}
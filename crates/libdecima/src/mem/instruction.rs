@@ -0,0 +1,37 @@
+//! Decoding the instruction at an address, so its operands can be followed without knowing how
+//! it's encoded.
+//!
+//! [Offset::as_relative](crate::mem::offsets::Offset::as_relative) needs the instruction's length
+//! and only works when the displacement is its last 4 bytes, which isn't the case for e.g.
+//! `cmp byte ptr [rip+x], 5`. [target] works for any instruction with a RIP-relative memory
+//! operand or a branch target.
+
+use crate::mem::PatternSearchError;
+use iced_x86::{Decoder, DecoderOptions, Instruction, OpKind};
+
+/// Longest possible x86-64 instruction.
+pub const MAX_INSTRUCTION_LEN: usize = 15;
+
+/// Decodes the instruction at the start of `bytes`, which are at `address`.
+pub fn decode(bytes: &[u8], address: usize) -> Result<Instruction, PatternSearchError> {
+    let mut decoder = Decoder::with_ip(64, bytes, address as u64, DecoderOptions::NONE);
+    let instruction = decoder.decode();
+    if instruction.is_invalid() {
+        return Err(PatternSearchError::InvalidInstruction(address));
+    }
+    Ok(instruction)
+}
+
+/// Where `instruction` points: the address of its RIP-relative memory operand, or where a call,
+/// jmp or jcc goes.
+pub fn target(instruction: &Instruction) -> Result<usize, PatternSearchError> {
+    if instruction.is_ip_rel_memory_operand() {
+        return Ok(instruction.ip_rel_memory_address() as usize);
+    }
+    let branch = (0..instruction.op_count())
+        .any(|operand| instruction.op_kind(operand) == OpKind::NearBranch64);
+    match branch {
+        true => Ok(instruction.near_branch_target() as usize),
+        false => Err(PatternSearchError::NoTarget(instruction.ip() as usize)),
+    }
+}
@@ -1,74 +1,135 @@
+pub mod builtin_patches;
+pub mod cache;
+pub mod functions;
+pub mod generator;
+pub mod image;
+pub mod instruction;
+pub mod msvc_rtti;
 pub mod offsets;
+pub mod patches;
+pub mod pattern;
+pub mod pe;
 pub mod scan;
+pub mod scanner;
+pub mod signatures;
+pub mod versions;
+pub mod xref;
 
 use crate::log;
+use crate::mem::image::Image;
 use crate::mem::offsets::Offset;
+use crate::mem::pattern::PatternByte;
+use crate::mem::pe::{ImageDosHeader, ImageNtHeaders64};
+use crate::mem::scanner::Scanner;
+use crate::platform::{CurrentPlatform, Platform};
 use std::ffi::c_void;
-use std::ptr::read_unaligned;
+use std::fmt::{Display, Formatter};
 use std::slice;
-use windows::Win32::System::Diagnostics::Debug::{
-    FlushInstructionCache, IMAGE_NT_HEADERS64, IMAGE_SECTION_HEADER,
-};
-use windows::Win32::System::LibraryLoader::GetModuleHandleW;
-use windows::Win32::System::Memory::{
-    PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS, VirtualProtect,
-};
-use windows::Win32::System::SystemServices::IMAGE_DOS_HEADER;
-use windows::Win32::System::Threading::GetCurrentProcess;
 
 #[derive(Debug, Clone)]
 pub enum PatternSearchError {
-    ParseInt(std::num::ParseIntError),
+    /// An invalid hex digit at `position` in the pattern.
+    ParseInt {
+        position: usize,
+        error: std::num::ParseIntError,
+    },
+    Malformed {
+        position: usize,
+        reason: &'static str,
+    },
     OutOfRange,
     NotFound,
+    /// No signature with this name is in the loaded database.
+    UnknownSignature(String),
+    /// The bytes searched aren't a valid PE32+ image.
+    InvalidImage(&'static str),
+    /// The bytes at this address don't decode as an instruction.
+    InvalidInstruction(usize),
+    /// The instruction at this address has no RIP-relative operand or branch target to follow.
+    NoTarget(usize),
+    /// The start of the function containing the instruction at this RVA can't be told.
+    NoFunctionStart(usize),
 }
 
-/// parses an ida-style byte sequence pattern
-pub fn parse_pattern(mask: &str) -> Result<Vec<(u8, bool)>, PatternSearchError> {
-    let mask = mask.replace("?", "??");
-    let mask = mask.replace(" ", "");
-
-    (0..mask.len())
-        .step_by(2)
-        .map(|i| {
-            let radix = &mask[i..i + 2];
-            if radix == "??" {
-                Ok((0x00, true))
-            } else {
-                Ok((
-                    u8::from_str_radix(radix, 16).unwrap(), /*? todo */
-                    false,
-                ))
+impl Display for PatternSearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternSearchError::ParseInt { position, error } => {
+                write!(f, "invalid pattern at {}: {}", position, error)
             }
-        })
-        .collect()
+            PatternSearchError::Malformed { position, reason } => {
+                write!(f, "invalid pattern at {}: {}", position, reason)
+            }
+            PatternSearchError::OutOfRange => write!(f, "out of range"),
+            PatternSearchError::NotFound => write!(f, "not found"),
+            PatternSearchError::UnknownSignature(name) => write!(f, "unknown signature {}", name),
+            PatternSearchError::InvalidImage(reason) => write!(f, "invalid image: {}", reason),
+            PatternSearchError::InvalidInstruction(address) => {
+                write!(f, "invalid instruction at {:#x}", address)
+            }
+            PatternSearchError::NoTarget(address) => {
+                write!(f, "nothing to follow in the instruction at {:#x}", address)
+            }
+            PatternSearchError::NoFunctionStart(rva) => {
+                write!(f, "no function start found before {:#x}", rva)
+            }
+        }
+    }
 }
 
-pub fn find_pattern(
+impl std::error::Error for PatternSearchError {}
+
+pub use pattern::parse_pattern;
+
+/// The first match of `mask` in the `max_size` bytes at `start_address`.
+///
+/// # Safety
+/// The `max_size` bytes at `start_address` have to be valid for reads.
+pub unsafe fn find_pattern(
     start_address: *mut u8,
     max_size: usize,
     mask: &str,
 ) -> Result<*mut u8, PatternSearchError> {
-    let pattern = parse_pattern(mask)?;
-    let data_end = start_address as usize + max_size + 1;
-
-    let result = unsafe { slice::from_raw_parts(start_address, max_size + 1) }
-        .windows(pattern.len())
-        .position(|pos| {
-            pos.iter()
-                .enumerate()
-                .all(|(i, b)| pattern[i].1 || pattern[i].0.eq(b))
-        });
-
-    let Some(result) = result else {
-        return Err(PatternSearchError::NotFound);
-    };
+    unsafe { find_pattern_bytes(start_address, max_size, &parse_pattern(mask)?) }
+}
 
-    if result > data_end {
-        return Err(PatternSearchError::OutOfRange);
+/// [find_pattern] for an already parsed pattern, see [pattern!](crate::pattern).
+///
+/// # Safety
+/// Same as [find_pattern].
+pub unsafe fn find_pattern_bytes(
+    start_address: *mut u8,
+    max_size: usize,
+    pattern: &[PatternByte],
+) -> Result<*mut u8, PatternSearchError> {
+    let data = unsafe { slice::from_raw_parts(start_address, max_size) };
+    match Scanner::new(pattern).find(data) {
+        Some(offset) => Ok((start_address as usize + offset) as *mut u8),
+        None => Err(PatternSearchError::NotFound),
     }
+}
 
-    Ok((start_address as usize + result) as *mut u8)
+/// Every match of `mask` in the `max_size` bytes at `start_address`, in address order.
+///
+/// # Safety
+/// Same as [find_pattern].
+pub unsafe fn find_pattern_all(
+    start_address: *mut u8,
+    max_size: usize,
+    mask: &str,
+) -> Result<Vec<*mut u8>, PatternSearchError> {
+    let data = unsafe { slice::from_raw_parts(start_address, max_size) };
+    Ok(Scanner::parse(mask)?
+        .find_iter(data)
+        .map(|offset| (start_address as usize + offset) as *mut u8)
+        .collect())
+}
+
+/// [find_pattern] restricted to a section of the game module, e.g. `.text`.
+pub fn find_pattern_in_section(section: &str, mask: &str) -> Result<*mut u8, PatternSearchError> {
+    let (start, end) = get_section(section)?;
+    // the section is part of the loaded module.
+    unsafe { find_pattern(start as *mut u8, end - start, mask) }
 }
 
 pub fn offset_from_instruction(
@@ -76,90 +137,38 @@ pub fn offset_from_instruction(
     rip_rel_add: u32,
 ) -> Result<*const u8, PatternSearchError> {
     let (module_base, module_end) = get_module()?;
-    let addr = find_pattern(module_base as *mut u8, module_end - module_base, signature)?;
-    let rel_offset = unsafe {
-        let ptr = addr.add(rip_rel_add as usize) as *const i32;
-        read_unaligned(ptr) + size_of::<i32>() as i32
-    };
-    Ok(unsafe {
-        addr.add(rip_rel_add as usize)
-            .add(rel_offset as usize)
-            .sub(module_base)
-    })
+    // the module is loaded for as long as the process runs.
+    let addr =
+        unsafe { find_pattern(module_base as *mut u8, module_end - module_base, signature)? };
+    let target = Offset::new(addr as usize).as_relative(rip_rel_add as usize + size_of::<i32>());
+    Ok((target.as_ptr::<u8>() as usize).wrapping_sub(module_base) as *const u8)
 }
 
 pub fn get_module() -> Result<(usize, usize), PatternSearchError> {
-    let base = unsafe { GetModuleHandleW(None).unwrap() };
-    if base.0.is_null() {
-        return Err(PatternSearchError::OutOfRange);
-    }
-
-    let base = base.0 as usize;
-    let dos_header = unsafe { &*(base as *const IMAGE_DOS_HEADER) };
-    let nt_headers_ptr =
-        (base as isize).wrapping_add(dos_header.e_lfanew as isize) as *const IMAGE_NT_HEADERS64;
-    let nt_headers = unsafe {
-        if nt_headers_ptr.is_null() {
-            return Err(PatternSearchError::OutOfRange);
-        } else {
-            &*nt_headers_ptr
-        }
-    };
-    let end = base + nt_headers.OptionalHeader.SizeOfImage as usize;
-    Ok((base, end))
+    CurrentPlatform::main_module().ok_or(PatternSearchError::OutOfRange)
 }
 
 pub fn get_pe_section_range(
     module: usize,
     section_name: &str,
 ) -> Result<(usize, usize), PatternSearchError> {
-    let dos_header = unsafe { &*(module as *const IMAGE_DOS_HEADER) };
-    let nt_headers_ptr =
-        (module as isize).wrapping_add(dos_header.e_lfanew as isize) as *const IMAGE_NT_HEADERS64;
-
-    if !nt_headers_ptr.is_null() {
-        let nt_headers = unsafe { &*nt_headers_ptr };
-        let header_size = size_of::<IMAGE_SECTION_HEADER>();
-        let sections_base =
-            (nt_headers_ptr as *const u8).wrapping_add(size_of::<IMAGE_NT_HEADERS64>());
-
-        for i in 0..nt_headers.FileHeader.NumberOfSections {
-            let section = unsafe {
-                &*((sections_base as usize + ((i as usize) * header_size))
-                    as *const IMAGE_SECTION_HEADER)
-            };
-
-            let section_name_str = std::str::from_utf8({
-                let buf = &section.Name;
-                let mut len = buf.len();
-                while len > 0 {
-                    if buf[len - 1] != 0 {
-                        break;
-                    }
-                    len -= 1;
-                }
-                &buf[..len]
-            })
-            .unwrap_or("");
-
-            if section_name_str == section_name {
-                let start = module + section.VirtualAddress as usize;
-                let end = unsafe {
-                    module + section.VirtualAddress as usize + section.Misc.VirtualSize as usize
-                };
-                if start != 0 && end != 0 {
-                    return Ok((start, end));
-                }
-            }
-        }
-    }
-    Err(PatternSearchError::OutOfRange)
+    let dos_header = unsafe { &*(module as *const ImageDosHeader) };
+    let nt_headers = unsafe {
+        &*((module as isize).wrapping_add(dos_header.e_lfanew as isize) as *const ImageNtHeaders64)
+    };
+    let size = nt_headers.optional_header.size_of_image as usize;
+    let image = unsafe { Image::from_module(module, size)? };
+    section_range(&image, section_name)
 }
 
 pub fn get_section(section_name: &str) -> Result<(usize, usize), PatternSearchError> {
-    let module = get_module()?.0;
-    let (base, end) = get_pe_section_range(module, section_name)?;
-    Ok((base, end))
+    section_range(&Image::live()?, section_name)
+}
+
+fn section_range(image: &Image, section_name: &str) -> Result<(usize, usize), PatternSearchError> {
+    let section = image.section(section_name)?;
+    let start = section.as_ptr() as usize;
+    Ok((start, start + section.len()))
 }
 
 pub fn get_code_section() -> Result<(usize, usize), PatternSearchError> {
@@ -174,48 +183,19 @@ pub fn get_data_section() -> Result<(usize, usize), PatternSearchError> {
     get_section(".data")
 }
 
+/// Writes `data` to `ptr` for good, see [PatchRegistry](crate::mem::patches::PatchRegistry) for
+/// patches that can be undone.
 pub fn patch(ptr: *mut c_void, data: &[u8]) {
     if !ptr.is_null() {
         unsafe {
-            let mut flags = PAGE_PROTECTION_FLAGS::default();
-            VirtualProtect(ptr, data.len(), PAGE_EXECUTE_READWRITE, &mut flags).unwrap();
-            for (index, byte) in data.iter().enumerate() {
-                std::ptr::write_bytes(ptr.add(index * size_of::<u8>()), *byte, 1);
+            if !CurrentPlatform::write_memory(ptr, data) {
+                log!(
+                    "libdecima",
+                    "failed to patch {} bytes at {:p}",
+                    data.len(),
+                    ptr
+                );
             }
-            VirtualProtect(ptr, data.len(), flags, &mut flags).unwrap();
-            FlushInstructionCache(GetCurrentProcess(), Some(ptr as *const _), data.len()).unwrap();
         }
     }
 }
-
-/// Patches out the Crash Logger and Telemetry Logger
-pub fn patch_reporting_loggers() {
-    #[cfg(feature = "hfw")]
-    {
-        // patches by Nukem9
-
-        // disable crash logger
-        patch(
-            Offset::from_signature("40 53 48 83 EC 20 80 79 38 00 48 8B D9 75 4C")
-                .unwrap()
-                .as_ptr::<c_void>(),
-            &[
-                0xc3, // ret
-            ],
-        );
-
-        // disable telemetry logger
-        patch(
-            Offset::from_signature("E8 ? ? ? ? 0F B6 F8 47 38 ? ? 75 05 E8")
-                .unwrap()
-                .as_ptr::<c_void>(),
-            &[
-                0xB0, 0x01, // mov al,0x1
-                0x90, // nop
-                0x90, // nop
-                0x90, // nop
-            ],
-        );
-        log!("Disabled (patched) crash and telemetry loggers.");
-    }
-}
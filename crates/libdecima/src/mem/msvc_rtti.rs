@@ -0,0 +1,251 @@
+//! The RTTI records MSVC emits for polymorphic C++ classes, see [ClassTable].
+//!
+//! Not to be confused with Decima's own RTTI in [types](crate::types). Every vtable is preceded
+//! by a pointer to a `CompleteObjectLocator`, which names the class through its `TypeDescriptor`
+//! (e.g. `.?AVPlayer@@`) and lists its bases. Finding the locators in `.rdata` gives the vtable of
+//! every class without a signature per class. All references between the records are RVAs.
+
+use crate::assert_size;
+use crate::mem::PatternSearchError;
+use crate::mem::image::{Image, read_at};
+use std::collections::HashMap;
+
+/// `signature` of the locators in 64-bit images, which store RVAs rather than pointers.
+const COL_SIGNATURE_RVA: u32 = 1;
+/// Longest decorated name read.
+const MAX_NAME_LEN: usize = 0x1000;
+/// Offset of the decorated name in a `TypeDescriptor`, after the `type_info` vtable pointer and
+/// a spare pointer.
+const TYPE_DESCRIPTOR_NAME: usize = 0x10;
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct RttiCompleteObjectLocator {
+    pub signature: u32,
+    /// Offset of the subobject the vtable is for in the complete object.
+    pub offset: u32,
+    pub cd_offset: u32,
+    pub type_descriptor: u32,
+    pub class_descriptor: u32,
+    /// The locator's own RVA.
+    pub object_base: u32,
+}
+assert_size!(RttiCompleteObjectLocator, 0x18);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct RttiClassHierarchyDescriptor {
+    pub signature: u32,
+    pub attributes: u32,
+    /// Entries in `base_class_array`, including the class itself.
+    pub num_base_classes: u32,
+    pub base_class_array: u32,
+}
+assert_size!(RttiClassHierarchyDescriptor, 0x10);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct RttiBaseClassDescriptor {
+    pub type_descriptor: u32,
+    /// Bases of this base, which follow it in the base class array.
+    pub num_contained_bases: u32,
+    pub mdisp: i32,
+    pub pdisp: i32,
+    pub vdisp: i32,
+    pub attributes: u32,
+    pub class_descriptor: u32,
+}
+assert_size!(RttiBaseClassDescriptor, 0x1C);
+
+/// A polymorphic class, see [ClassTable::parse].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    /// The undecorated name, e.g. `nx::NxD3DImpl`, see [undecorate].
+    pub name: String,
+    /// The name in the `TypeDescriptor`, e.g. `.?AVNxD3DImpl@nx@@`.
+    pub decorated_name: String,
+    pub type_descriptor: usize,
+    /// One per subobject with virtual functions, ordered by offset. The first is the vtable of
+    /// the class itself.
+    pub vtables: Vec<Vtable>,
+    /// Every base class, depth first in declaration order.
+    pub bases: Vec<BaseClass>,
+}
+
+impl Class {
+    /// RVA of the vtable of the class itself, the one at offset 0.
+    pub fn vtable(&self) -> Option<usize> {
+        self.vtables
+            .iter()
+            .find(|vtable| vtable.offset == 0)
+            .map(|vtable| vtable.rva)
+    }
+
+    /// The bases the class derives from directly, skipping their own bases.
+    pub fn direct_bases(&self) -> Vec<&BaseClass> {
+        let mut direct = Vec::new();
+        let mut index = 0;
+        while let Some(base) = self.bases.get(index) {
+            direct.push(base);
+            index += 1 + base.contained_bases;
+        }
+        direct
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Vtable {
+    /// RVA of the first virtual function pointer.
+    pub rva: usize,
+    /// Offset of the subobject using it in the complete object.
+    pub offset: usize,
+    /// RVA of its `CompleteObjectLocator`.
+    pub locator: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseClass {
+    pub name: String,
+    /// Offset of the base in the class.
+    pub offset: isize,
+    /// How many of the bases after this one are its own bases.
+    pub contained_bases: usize,
+}
+
+/// Every polymorphic class in an image.
+#[derive(Debug, Clone, Default)]
+pub struct ClassTable {
+    classes: Vec<Class>,
+}
+
+impl ClassTable {
+    /// Finds every `CompleteObjectLocator` and the vtables using it in `.rdata` of `image`.
+    /// Locators no vtable points at are skipped.
+    pub fn parse(image: &Image) -> Result<Self, PatternSearchError> {
+        let data = image.bytes();
+        let rdata = image.search_range(".rdata")?;
+        let start = rdata.as_ptr() as usize - image.base();
+
+        let mut locators = HashMap::new();
+        for position in (0..rdata.len()).step_by(align_of::<RttiCompleteObjectLocator>()) {
+            let Some(locator) = read_at::<RttiCompleteObjectLocator>(rdata, position) else {
+                break;
+            };
+            // a locator stores its own RVA, which is what makes it unlikely to be anything else.
+            if locator.signature == COL_SIGNATURE_RVA
+                && locator.object_base as usize == start + position
+            {
+                locators.insert(start + position, locator);
+            }
+        }
+
+        // vtables are preceded by a pointer to their locator.
+        let mut classes: HashMap<usize, Class> = HashMap::new();
+        for position in (0..rdata.len()).step_by(size_of::<usize>()) {
+            let Some(pointer) = read_at::<usize>(rdata, position) else {
+                break;
+            };
+            let rva = pointer.wrapping_sub(image.image_base());
+            let Some(locator) = locators.get(&rva) else {
+                continue;
+            };
+            let vtable = Vtable {
+                rva: start + position + size_of::<usize>(),
+                offset: locator.offset as usize,
+                locator: rva,
+            };
+            let type_descriptor = locator.type_descriptor as usize;
+            if let Some(class) = classes.get_mut(&type_descriptor) {
+                class.vtables.push(vtable);
+                continue;
+            }
+            let Some(decorated_name) = type_name(data, type_descriptor) else {
+                continue;
+            };
+            classes.insert(
+                type_descriptor,
+                Class {
+                    name: undecorate(&decorated_name),
+                    decorated_name,
+                    type_descriptor,
+                    vtables: vec![vtable],
+                    bases: bases(data, locator.class_descriptor as usize).unwrap_or_default(),
+                },
+            );
+        }
+
+        let mut classes = classes.into_values().collect::<Vec<_>>();
+        for class in &mut classes {
+            class
+                .vtables
+                .sort_by_key(|vtable| (vtable.offset, vtable.rva));
+        }
+        classes.sort_by(|a, b| (&a.name, a.type_descriptor).cmp(&(&b.name, b.type_descriptor)));
+        Ok(ClassTable { classes })
+    }
+
+    /// Every class, sorted by name.
+    pub fn classes(&self) -> &[Class] {
+        &self.classes
+    }
+
+    /// The class called `name`, undecorated (`nx::NxD3DImpl`) or decorated.
+    pub fn get(&self, name: &str) -> Option<&Class> {
+        self.classes
+            .iter()
+            .find(|class| class.name == name || class.decorated_name == name)
+    }
+
+    /// RVA of the vtable of the class called `name`, see [Class::vtable].
+    pub fn vtable(&self, name: &str) -> Option<usize> {
+        self.get(name)?.vtable()
+    }
+
+    /// The classes with `name` among their bases.
+    pub fn derived<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Class> + 'a {
+        self.classes
+            .iter()
+            .filter(move |class| class.bases.iter().any(|base| base.name == name))
+    }
+}
+
+/// The decorated name of the `TypeDescriptor` at `rva`, if it's one of a class or struct.
+fn type_name(data: &[u8], rva: usize) -> Option<String> {
+    let start = rva.checked_add(TYPE_DESCRIPTOR_NAME)?;
+    let bytes = data.get(start..data.len().min(start + MAX_NAME_LEN))?;
+    let name = &bytes[..bytes.iter().position(|byte| *byte == 0)?];
+    let name = std::str::from_utf8(name).ok()?;
+    (name.starts_with(".?AV") || name.starts_with(".?AU")).then(|| name.to_string())
+}
+
+/// The bases listed by the `ClassHierarchyDescriptor` at `rva`, without the class itself.
+fn bases(data: &[u8], rva: usize) -> Option<Vec<BaseClass>> {
+    let hierarchy = read_at::<RttiClassHierarchyDescriptor>(data, rva)?;
+    let array = hierarchy.base_class_array as usize;
+    (1..hierarchy.num_base_classes as usize)
+        .map(|index| {
+            let descriptor = read_at::<u32>(data, array + index * size_of::<u32>())?;
+            let base = read_at::<RttiBaseClassDescriptor>(data, descriptor as usize)?;
+            let name = type_name(data, base.type_descriptor as usize)?;
+            Some(BaseClass {
+                name: undecorate(&name),
+                offset: base.mdisp as isize,
+                contained_bases: base.num_contained_bases as usize,
+            })
+        })
+        .collect()
+}
+
+/// `.?AVNxD3DImpl@nx@@` as `nx::NxD3DImpl`. Names that aren't a plain (possibly nested) class
+/// name, like templates, are returned as they are.
+pub fn undecorate(name: &str) -> String {
+    let plain = name
+        .strip_prefix(".?AV")
+        .or_else(|| name.strip_prefix(".?AU"))
+        .and_then(|name| name.strip_suffix("@@"))
+        .filter(|name| !name.is_empty() && !name.contains(['?', '$']));
+    match plain {
+        Some(plain) => plain.split('@').rev().collect::<Vec<_>>().join("::"),
+        None => name.to_string(),
+    }
+}
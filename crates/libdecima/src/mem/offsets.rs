@@ -1,108 +1,207 @@
-use crate::mem::{PatternSearchError, find_pattern, get_module};
-use std::cell::OnceCell;
-use std::collections::HashMap;
+use crate::mem::cache::OffsetCache;
+use crate::mem::image::Image;
+use crate::mem::instruction::{MAX_INSTRUCTION_LEN, decode, target};
+use crate::mem::pattern::PatternByte;
+use crate::mem::signatures::{
+    Signature, SignatureCheck, SignatureDatabase, SignatureReport, check_all,
+};
+use crate::mem::{PatternSearchError, find_pattern, find_pattern_bytes, get_module};
+use iced_x86::Instruction;
+use std::collections::BTreeMap;
 use std::ops::{Add, Sub};
 use std::ptr::read_unaligned;
+use std::slice;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+/// Named addresses in the game module, resolved from a [SignatureDatabase] on first use.
 #[derive(Debug)]
 pub struct Offsets {
-    pub(crate) addresses: HashMap<String, *const u8>,
+    pub(crate) signatures: BTreeMap<String, Signature>,
+    /// Resolved module-relative offsets, failures included.
+    pub(crate) addresses: BTreeMap<String, Result<usize, PatternSearchError>>,
 }
 
+/// Game of the database [Offsets::setup] loads, the one libdecima's types are for.
+const BUILTIN_GAME: &str = "hfw";
+
+static OFFSETS: RwLock<Offsets> = RwLock::new(Offsets {
+    signatures: BTreeMap::new(),
+    addresses: BTreeMap::new(),
+});
+
 impl Offsets {
-    fn instance() -> &'static mut Offsets {
-        unsafe {
-            static mut OFFSETS: OnceCell<Offsets> = OnceCell::new();
-            OFFSETS.get_mut_or_init(|| Offsets {
-                addresses: HashMap::new(),
-            })
+    fn read() -> RwLockReadGuard<'static, Offsets> {
+        OFFSETS
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write() -> RwLockWriteGuard<'static, Offsets> {
+        OFFSETS
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Loads the entries of `database` used for game `version`, replacing any loaded before.
+    pub fn load(database: &SignatureDatabase, version: &str) {
+        let mut offsets = Offsets::write();
+        offsets.signatures = select(database, version);
+        offsets.addresses.clear();
+    }
+
+    pub fn is_loaded() -> bool {
+        !Offsets::read().signatures.is_empty()
+    }
+
+    /// Resolves the signature called `name`, scanning for it the first time it's asked for.
+    ///
+    /// Falls back to the builtin database if none was loaded, see [Offsets::setup].
+    pub fn find(name: &str) -> Result<Offset, PatternSearchError> {
+        let signature = {
+            let offsets = Offsets::read();
+            if let Some(address) = offsets.addresses.get(name) {
+                let address = address.clone()?;
+                return Ok(Offset::new(get_module()?.0.wrapping_add(address)));
+            }
+            offsets.signatures.get(name).cloned()
+        };
+        let signature = match signature {
+            Some(signature) => signature,
+            None => {
+                Offsets::setup();
+                Offsets::read()
+                    .signatures
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| PatternSearchError::UnknownSignature(name.to_string()))?
+            }
+        };
+
+        let module = get_module()?.0;
+        let address = signature
+            .resolve()
+            .map(|offset| (offset.as_ptr::<u8>() as usize).wrapping_sub(module));
+        Offsets::write()
+            .addresses
+            .insert(name.to_string(), address.clone());
+        Ok(Offset::new(module.wrapping_add(address?)))
+    }
+
+    /// Resolves every loaded signature at once, so broken ones show up at startup rather than
+    /// whenever they're first used. Results are cached for [Offsets::find].
+    pub fn check() -> SignatureReport {
+        Offsets::check_with(check_all)
+    }
+
+    /// [Offsets::check], reusing the offsets in `cache` that are still valid and updating it
+    /// with everything found.
+    pub fn check_cached(cache: &mut OffsetCache) -> SignatureReport {
+        Offsets::check_with(|signatures| match Image::live() {
+            Ok(image) => cache.check(signatures, &image),
+            Err(_) => check_all(signatures),
+        })
+    }
+
+    fn check_with(check: impl FnOnce(&[Signature]) -> Vec<SignatureCheck>) -> SignatureReport {
+        let signatures = Offsets::read()
+            .signatures
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        let checks = check(&signatures);
+
+        if let Ok((module, _)) = get_module() {
+            let mut offsets = Offsets::write();
+            for check in &checks {
+                let address = check
+                    .result
+                    .clone()
+                    .map(|offset| (offset.as_ptr::<u8>() as usize).wrapping_sub(module));
+                offsets.addresses.insert(check.name.clone(), address);
+            }
         }
+        SignatureReport { checks }
     }
 
+    /// Maps `name` to a module-relative `address`, unless it's already mapped.
     pub fn map_address(name: &str, address: *const u8) {
-        Offsets::instance()
+        Offsets::write()
             .addresses
             .entry(String::from(name))
-            .or_insert(address);
+            .or_insert(Ok(address as usize));
     }
 
-    pub fn map_pattern(name: &str, pattern: &str) {
-        let (start, end) = get_module().unwrap();
-        Offsets::map_address(
-            name,
-            find_pattern(start as *mut u8, end - start, pattern).unwrap(),
-        );
+    /// Maps `name` to the first match of `pattern` in the game module, see [Offsets::map_address].
+    pub fn map_pattern(name: &str, pattern: &str) -> Result<(), PatternSearchError> {
+        let (start, end) = get_module()?;
+        // the module is loaded for as long as the process runs.
+        let found = unsafe { find_pattern(start as *mut u8, end - start, pattern)? };
+        Offsets::map_address(name, (found as usize - start) as *const u8);
+        Ok(())
     }
 
     pub fn map_offset(name: &str, offset: Offset) {
         Offsets::map_address(name, offset.as_offset());
     }
 
-    pub fn resolve_raw(name: &str) -> Option<&*const u8> {
-        Offsets::instance().addresses.get(name)
+    /// The module-relative offset of `name`, if it was resolved.
+    pub fn resolve_raw(name: &str) -> Option<*const u8> {
+        match Offsets::read().addresses.get(name)? {
+            Ok(address) => Some(*address as *const u8),
+            Err(_) => None,
+        }
     }
 
     pub fn resolve<T: Sized>(name: &str) -> Option<*mut T> {
-        Some(
-            get_module()
-                .unwrap()
-                .0
-                .add(*Offsets::instance().addresses.get(name)? as usize) as *mut T,
-        )
+        Some(Offsets::find(name).ok()?.as_ptr::<T>())
     }
 
+    /// Loads the builtin database if nothing was loaded yet, keeping the addresses mapped already.
+    ///
+    /// Plugins link their own copy of libdecima, which only the loader's copy is given a
+    /// database, so this is what their bindings resolve through. Without a game version only
+    /// the entries for every version are used.
     pub fn setup() {
-        if !Offsets::instance().addresses.is_empty() {
+        let mut offsets = Offsets::write();
+        if !offsets.signatures.is_empty() {
             return;
         }
-
-        #[cfg(feature = "hfw")]
-        {
-            // use crate::mem::offset_from_instruction;
-            // Offsets::map_pattern(
-            //     "nx::NxAppImpl::fn_create_swap_chain",
-            //     "48 89 5C 24 10 48 89 6C 24 18 56 57 41 56 48 83 EC ? 48 8B F1 49 8B F8",
-            // );
-            // Offsets::map_pattern(
-            //     "nx::NxD3DImpl::fn_initialize",
-            //     "48 89 5C 24 18 48 89 6C 24 20 56 57 41 54 41 56 41 57 48 83 EC ? 45 0F B6 F0",
-            // );
-
-            // Offsets::map_pattern(
-            //     "nx::NxDXGIImpl::fn_present",
-            //     "40 55 56 57 41 54 41 56 48 8D AC 24 00 FD FF FF",
-            // );
-            // Offsets::map_offset(
-            //     "nx::NxD3DImpl::Instance",
-            //     Offset::from_signature(
-            //         "48 8B 0D ? ? ? ? 8B D3 4C 8B 01 41 FF 90 ? ? 00 00 48 81 C4 ? 01 00 00 5B C3",
-            //     )
-            //     .unwrap()
-            //     .as_relative(7),
-            // );
-            // Offsets::map_offset(
-            //     "nx::NxDXGIImpl::Instance",
-            //     Offset::from_signature("48 8D 0D ? ? ? ? 66 89 68 08 48 89 08 40 88 68 0A 48 89 68 0C 48 89 68 18 48 89 68 20")
-            //         .unwrap()
-            //         .as_relative(7),
-            // );
-            //
-            // Offsets::map_pattern(
-            //     "RTTIFactory::RegisterType",
-            //     "40 55 53 56 48 8D 6C 24 ? 48 81 EC ? ? ? ? 0F B6 42 05 48 8B DA 48 8B",
-            // );
-            //
-            // Offsets::map_pattern(
-            //     "RTTIFactory::RegisterAllTypes",
-            //     "40 55 48 8B EC 48 83 EC 70 80 3D ? ? ? ? ? 0F 85 ? ? ? ? 48 89 9C 24",
-            // );
+        if let Some(database) = SignatureDatabase::builtin(BUILTIN_GAME) {
+            offsets.signatures = select(&database, "");
         }
     }
 }
 
+fn select(database: &SignatureDatabase, version: &str) -> BTreeMap<String, Signature> {
+    database
+        .select(version)
+        .into_iter()
+        .map(|signature| (signature.name.clone(), signature.clone()))
+        .collect()
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Offset(usize);
 
+/// Lets [decima_fn!](crate::decima_fn) chain fallible [Offset] methods like [Offset::as_target]
+/// along with infallible ones.
+#[doc(hidden)]
+pub trait IntoOffsetResult {
+    fn into_offset_result(self) -> Result<Offset, PatternSearchError>;
+}
+
+impl IntoOffsetResult for Offset {
+    fn into_offset_result(self) -> Result<Offset, PatternSearchError> {
+        Ok(self)
+    }
+}
+
+impl IntoOffsetResult for Result<Offset, PatternSearchError> {
+    fn into_offset_result(self) -> Result<Offset, PatternSearchError> {
+        self
+    }
+}
+
 impl Offset {
     pub fn new(address: usize) -> Self {
         Offset(address)
@@ -110,7 +209,19 @@ impl Offset {
 
     pub fn from_signature(pattern: &str) -> Result<Self, PatternSearchError> {
         let (module_start, module_end) = get_module()?;
-        let search = find_pattern(module_start as *mut _, module_end - module_start, pattern)?;
+        // the module is loaded for as long as the process runs.
+        let search =
+            unsafe { find_pattern(module_start as *mut _, module_end - module_start, pattern)? };
+        Ok(Self::new(search as _))
+    }
+
+    /// [Offset::from_signature] for an already parsed pattern, see [pattern!](crate::pattern).
+    pub fn from_pattern(pattern: &[PatternByte]) -> Result<Self, PatternSearchError> {
+        let (module_start, module_end) = get_module()?;
+        // the module is loaded for as long as the process runs.
+        let search = unsafe {
+            find_pattern_bytes(module_start as *mut _, module_end - module_start, pattern)?
+        };
         Ok(Self::new(search as _))
     }
 
@@ -123,15 +234,37 @@ impl Offset {
         Offset(self.0.sub(offset))
     }
 
+    /// Follows the signed rel32 in the last 4 bytes of the instruction of `instruction_length`
+    /// bytes at the address. See [Offset::as_target] for instructions where that isn't the case.
     pub fn as_relative(&self, instruction_length: usize) -> Offset {
-        let rel_adjust = unsafe {
-            std::mem::transmute::<usize, *mut u32>(
-                self.0.add(instruction_length.sub(size_of::<u32>())),
-            )
-        };
-        let rel_adjust = unsafe { read_unaligned(rel_adjust) } as usize;
-        let result = Offset(self.0.add(rel_adjust.add(instruction_length)));
-        result
+        let rel_adjust = self.0.add(instruction_length.sub(size_of::<i32>())) as *const i32;
+        let rel_adjust = unsafe { read_unaligned(rel_adjust) } as isize;
+        Offset(
+            self.0
+                .add(instruction_length)
+                .wrapping_add_signed(rel_adjust),
+        )
+    }
+
+    /// Decodes the instruction at the address.
+    pub fn instruction(&self) -> Result<Instruction, PatternSearchError> {
+        let bytes = unsafe { slice::from_raw_parts(self.0 as *const u8, MAX_INSTRUCTION_LEN) };
+        decode(bytes, self.0)
+    }
+
+    /// Follows the instruction at the address to its RIP-relative operand or branch target,
+    /// whatever its encoding.
+    pub fn as_target(&self) -> Result<Offset, PatternSearchError> {
+        Ok(Offset(target(&self.instruction()?)?))
+    }
+
+    /// The address `count` instructions further on.
+    pub fn as_next(&self, count: usize) -> Result<Offset, PatternSearchError> {
+        let mut offset = *self;
+        for _ in 0..count {
+            offset = Offset(offset.instruction()?.next_ip() as usize);
+        }
+        Ok(offset)
     }
 
     pub fn as_ptr<T>(&self) -> *mut T {
@@ -0,0 +1,403 @@
+//! Reversible byte patches, see [PatchRegistry].
+//!
+//! [patch](crate::mem::patch) writes bytes and forgets about them. Patches going through a
+//! registry remember what they replaced and who made them, so they can be turned off again,
+//! overlapping patches from different owners are refused rather than silently corrupting each
+//! other, and the loader can list what's patched.
+
+use crate::platform::{CurrentPlatform, Platform};
+use std::fmt::{Display, Formatter};
+use std::slice;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+/// Where a registry reads and writes, so the bookkeeping can be tested on a plain buffer.
+pub trait PatchMemory {
+    /// The `len` bytes at `address`, if they can be read.
+    ///
+    /// # Safety
+    /// `address` must be valid for reads of `len` bytes in this memory, see
+    /// [PatchRegistry::enable].
+    unsafe fn read(&self, address: usize, len: usize) -> Option<Vec<u8>>;
+
+    /// Writes `bytes` to `address`, returning `false` if it failed.
+    ///
+    /// # Safety
+    /// Overwriting the bytes at `address` must be sound, see [PatchRegistry::enable].
+    unsafe fn write(&mut self, address: usize, bytes: &[u8]) -> bool;
+}
+
+/// The memory of the current process, written through [Platform::write_memory].
+#[derive(Debug)]
+pub struct ProcessMemory;
+
+impl PatchMemory for ProcessMemory {
+    unsafe fn read(&self, address: usize, len: usize) -> Option<Vec<u8>> {
+        if address == 0 {
+            return None;
+        }
+        Some(unsafe { slice::from_raw_parts(address as *const u8, len) }.to_vec())
+    }
+
+    unsafe fn write(&mut self, address: usize, bytes: &[u8]) -> bool {
+        address != 0 && unsafe { CurrentPlatform::write_memory(address as *mut _, bytes) }
+    }
+}
+
+/// A buffer addressed by offset.
+impl PatchMemory for Vec<u8> {
+    unsafe fn read(&self, address: usize, len: usize) -> Option<Vec<u8>> {
+        self.get(address..address.checked_add(len)?)
+            .map(|bytes| bytes.to_vec())
+    }
+
+    unsafe fn write(&mut self, address: usize, bytes: &[u8]) -> bool {
+        let Some(end) = address.checked_add(bytes.len()) else {
+            return false;
+        };
+        match self.get_mut(address..end) {
+            Some(target) => {
+                target.copy_from_slice(bytes);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    /// Unique in the registry, e.g. the signature the address came from.
+    pub name: String,
+    /// Id of the plugin that made the patch, `cauldron` for the loader's own.
+    pub owner: String,
+    pub address: usize,
+    pub bytes: Vec<u8>,
+    /// What has to be at the address before it's patched, anything if `None`. Catches patching
+    /// the wrong place after a game update.
+    pub expected: Option<Vec<u8>>,
+}
+
+impl Patch {
+    /// One past the last byte patched, `None` if that's past the end of the address space.
+    pub fn end(&self) -> Option<usize> {
+        self.address.checked_add(self.bytes.len())
+    }
+
+    pub fn overlaps(&self, other: &Patch) -> bool {
+        let end = |patch: &Patch| patch.end().unwrap_or(usize::MAX);
+        self.address < end(other) && other.address < end(self)
+    }
+}
+
+/// A registered patch and its state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchEntry {
+    pub patch: Patch,
+    /// The bytes the patch replaced, empty until it's first enabled.
+    pub original: Vec<u8>,
+    pub enabled: bool,
+    /// Why the patch was last refused, cleared once it's enabled.
+    pub error: Option<PatchError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    AlreadyRegistered(String),
+    Unknown(String),
+    /// `name` overlaps `other`, which belongs to `owner`.
+    Conflict {
+        name: String,
+        other: String,
+        owner: String,
+    },
+    /// The bytes at the address aren't the ones `name` expects.
+    UnexpectedBytes {
+        name: String,
+        expected: Vec<u8>,
+        found: Vec<u8>,
+    },
+    /// The patch runs past the end of the address space.
+    OutOfRange {
+        name: String,
+        address: usize,
+    },
+    Unreadable {
+        name: String,
+        address: usize,
+    },
+    WriteFailed {
+        name: String,
+        address: usize,
+    },
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let hex = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        match self {
+            PatchError::AlreadyRegistered(name) => write!(f, "patch {} already exists", name),
+            PatchError::Unknown(name) => write!(f, "no patch named {}", name),
+            PatchError::Conflict { name, other, owner } => {
+                write!(f, "patch {} overlaps {} by {}", name, other, owner)
+            }
+            PatchError::UnexpectedBytes {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "patch {} expected {}, found {}",
+                name,
+                hex(expected),
+                hex(found)
+            ),
+            PatchError::OutOfRange { name, address } => {
+                write!(f, "patch {} at {:#x} is out of range", name, address)
+            }
+            PatchError::Unreadable { name, address } => {
+                write!(f, "patch {} can't read {:#x}", name, address)
+            }
+            PatchError::WriteFailed { name, address } => {
+                write!(f, "patch {} failed to write {:#x}", name, address)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Every patch made through it, enabled or not.
+#[derive(Debug)]
+pub struct PatchRegistry<M: PatchMemory = ProcessMemory> {
+    memory: M,
+    entries: Vec<PatchEntry>,
+}
+
+static PATCHES: Mutex<PatchRegistry> = Mutex::new(PatchRegistry::new(ProcessMemory));
+
+impl PatchRegistry {
+    /// The registry for the current process, shared by the loader and every plugin.
+    pub fn global() -> MutexGuard<'static, PatchRegistry> {
+        PATCHES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// [PatchRegistry::global] if it isn't locked, for places that can't wait, like `DllMain`.
+    pub fn try_global() -> Option<MutexGuard<'static, PatchRegistry>> {
+        match PATCHES.try_lock() {
+            Ok(registry) => Some(registry),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+impl<M: PatchMemory> PatchRegistry<M> {
+    pub const fn new(memory: M) -> Self {
+        PatchRegistry {
+            memory,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// Writes through this aren't tracked, and make patches they touch fail to disable.
+    pub fn memory_mut(&mut self) -> &mut M {
+        &mut self.memory
+    }
+
+    /// Every registered patch, in registration order.
+    pub fn entries(&self) -> &[PatchEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PatchEntry> {
+        self.entries.iter().find(|entry| entry.patch.name == name)
+    }
+
+    /// Adds `patch` without enabling it. Refused if it overlaps a patch of another owner.
+    pub fn register(&mut self, patch: Patch) -> Result<(), PatchError> {
+        if self.get(&patch.name).is_some() {
+            return Err(PatchError::AlreadyRegistered(patch.name));
+        }
+        if patch.end().is_none() {
+            return Err(PatchError::OutOfRange {
+                name: patch.name,
+                address: patch.address,
+            });
+        }
+        let conflict = self
+            .entries
+            .iter()
+            .find(|entry| entry.patch.owner != patch.owner && entry.patch.overlaps(&patch));
+        if let Some(entry) = conflict {
+            return Err(conflict_error(&patch, entry));
+        }
+        self.entries.push(PatchEntry {
+            patch,
+            original: Vec::new(),
+            enabled: false,
+            error: None,
+        });
+        Ok(())
+    }
+
+    /// Registers and enables `patch`. It stays registered (and disabled) if enabling fails.
+    ///
+    /// # Safety
+    /// See [PatchRegistry::enable].
+    pub unsafe fn apply(&mut self, patch: Patch) -> Result<(), PatchError> {
+        let name = patch.name.clone();
+        self.register(patch)?;
+        unsafe { self.enable(&name) }
+    }
+
+    /// Writes the patch, after checking nothing enabled overlaps it and the bytes it replaces are
+    /// the expected ones.
+    ///
+    /// # Safety
+    /// The patched range must be valid to read and write in the registry's memory. For
+    /// [ProcessMemory] that means mapped memory of the current process that nothing holds a
+    /// reference into, and code there must still be valid to run once patched. Plain buffers
+    /// accept any address.
+    pub unsafe fn enable(&mut self, name: &str) -> Result<(), PatchError> {
+        let index = self.index(name)?;
+        let result = unsafe { self.try_enable(index) };
+        self.entries[index].error = result.as_ref().err().cloned();
+        result
+    }
+
+    unsafe fn try_enable(&mut self, index: usize) -> Result<(), PatchError> {
+        let entry = &self.entries[index];
+        if entry.enabled {
+            return Ok(());
+        }
+        let patch = &entry.patch;
+        let conflict = self
+            .entries
+            .iter()
+            .find(|other| other.enabled && other.patch.overlaps(patch));
+        if let Some(other) = conflict {
+            return Err(conflict_error(patch, other));
+        }
+        let original =
+            unsafe { self.memory.read(patch.address, patch.bytes.len()) }.ok_or_else(|| {
+                PatchError::Unreadable {
+                    name: patch.name.clone(),
+                    address: patch.address,
+                }
+            })?;
+        let unexpected = patch
+            .expected
+            .as_ref()
+            .filter(|expected| **expected != original);
+        if let Some(expected) = unexpected {
+            return Err(PatchError::UnexpectedBytes {
+                name: patch.name.clone(),
+                expected: expected.clone(),
+                found: original,
+            });
+        }
+        if !unsafe { self.memory.write(patch.address, &patch.bytes) } {
+            return Err(PatchError::WriteFailed {
+                name: patch.name.clone(),
+                address: patch.address,
+            });
+        }
+        let entry = &mut self.entries[index];
+        entry.original = original;
+        entry.enabled = true;
+        Ok(())
+    }
+
+    /// Restores the bytes the patch replaced. Refused if something else changed them since, as
+    /// restoring would undo that too.
+    pub fn disable(&mut self, name: &str) -> Result<(), PatchError> {
+        let index = self.index(name)?;
+        let entry = &self.entries[index];
+        if !entry.enabled {
+            return Ok(());
+        }
+        let (patch, original) = (&entry.patch, &entry.original);
+        // the range was valid when the patch was enabled, and it's still there.
+        let current =
+            unsafe { self.memory.read(patch.address, patch.bytes.len()) }.ok_or_else(|| {
+                PatchError::Unreadable {
+                    name: patch.name.clone(),
+                    address: patch.address,
+                }
+            })?;
+        if current != patch.bytes {
+            return Err(PatchError::UnexpectedBytes {
+                name: patch.name.clone(),
+                expected: patch.bytes.clone(),
+                found: current,
+            });
+        }
+        if !unsafe { self.memory.write(patch.address, original) } {
+            return Err(PatchError::WriteFailed {
+                name: patch.name.clone(),
+                address: patch.address,
+            });
+        }
+        self.entries[index].enabled = false;
+        Ok(())
+    }
+
+    /// Disables and unregisters the patch.
+    pub fn revert(&mut self, name: &str) -> Result<(), PatchError> {
+        self.disable(name)?;
+        let index = self.index(name)?;
+        self.entries.remove(index);
+        Ok(())
+    }
+
+    /// Reverts every patch of `owner`, newest first, returning the ones that failed.
+    pub fn revert_owner(&mut self, owner: &str) -> Vec<PatchError> {
+        self.revert_where(|patch| patch.owner == owner)
+    }
+
+    /// Reverts every patch, newest first, returning the ones that failed.
+    pub fn revert_all(&mut self) -> Vec<PatchError> {
+        self.revert_where(|_| true)
+    }
+
+    fn revert_where(&mut self, filter: impl Fn(&Patch) -> bool) -> Vec<PatchError> {
+        let names = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| filter(&entry.patch))
+            .map(|entry| entry.patch.name.clone())
+            .collect::<Vec<_>>();
+        names
+            .iter()
+            .filter_map(|name| self.revert(name).err())
+            .collect()
+    }
+
+    fn index(&self, name: &str) -> Result<usize, PatchError> {
+        self.entries
+            .iter()
+            .position(|entry| entry.patch.name == name)
+            .ok_or_else(|| PatchError::Unknown(name.to_string()))
+    }
+}
+
+fn conflict_error(patch: &Patch, other: &PatchEntry) -> PatchError {
+    PatchError::Conflict {
+        name: patch.name.clone(),
+        other: other.patch.name.clone(),
+        owner: other.patch.owner.clone(),
+    }
+}
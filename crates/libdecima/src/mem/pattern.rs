@@ -0,0 +1,239 @@
+//! IDA-style byte patterns, e.g. `48 8B 0D ? ? ? ? 4?`.
+//!
+//! Bytes are separated by whitespace and are either two hex digits, `?`/`??` for any byte, or a
+//! hex digit and a `?` for a byte with one known nibble. The tokenizer is `const` so patterns can
+//! be checked and compiled at build time with [pattern!](crate::pattern).
+
+use crate::mem::PatternSearchError;
+
+/// A pattern byte, matching bytes where `byte & mask == value`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PatternByte {
+    pub value: u8,
+    pub mask: u8,
+}
+
+impl PatternByte {
+    /// `?` or `??`, matches any byte.
+    pub const ANY: PatternByte = PatternByte { value: 0, mask: 0 };
+
+    pub const fn exact(value: u8) -> Self {
+        PatternByte { value, mask: 0xFF }
+    }
+
+    pub const fn matches(&self, byte: u8) -> bool {
+        byte & self.mask == self.value
+    }
+}
+
+/// Where and why a pattern couldn't be parsed, see [PatternError::into_search_error].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// A character that isn't a hex digit or `?` at `position`, in the byte `token`.
+    InvalidDigit {
+        position: usize,
+        token: (usize, usize),
+    },
+    Malformed {
+        position: usize,
+        reason: &'static str,
+    },
+}
+
+impl PatternError {
+    pub const fn position(&self) -> usize {
+        match *self {
+            PatternError::InvalidDigit { position, .. } => position,
+            PatternError::Malformed { position, .. } => position,
+        }
+    }
+
+    pub const fn reason(&self) -> &'static str {
+        match *self {
+            PatternError::InvalidDigit { .. } => "invalid hex digit in pattern",
+            PatternError::Malformed { reason, .. } => reason,
+        }
+    }
+
+    /// Converts to the error [find_pattern](crate::mem::find_pattern) returns for `pattern`.
+    pub fn into_search_error(self, pattern: &str) -> PatternSearchError {
+        match self {
+            PatternError::InvalidDigit {
+                position,
+                token: (start, end),
+            } => match u8::from_str_radix(&pattern[start..end], 16) {
+                Err(error) => PatternSearchError::ParseInt { position, error },
+                // a sign parses as part of a number, but isn't a hex digit, e.g. `+1`.
+                Ok(_) => PatternSearchError::Malformed {
+                    position,
+                    reason: self.reason(),
+                },
+            },
+            PatternError::Malformed { position, reason } => {
+                PatternSearchError::Malformed { position, reason }
+            }
+        }
+    }
+}
+
+const fn is_separator(byte: u8) -> bool {
+    byte.is_ascii_whitespace()
+}
+
+/// The `(start, end)` of the next token at or after `position`.
+const fn next_token(pattern: &[u8], mut position: usize) -> Option<(usize, usize)> {
+    while position < pattern.len() && is_separator(pattern[position]) {
+        position += 1;
+    }
+    if position == pattern.len() {
+        return None;
+    }
+    let start = position;
+    while position < pattern.len() && !is_separator(pattern[position]) {
+        position += 1;
+    }
+    Some((start, position))
+}
+
+/// Parses a nibble, `None` for `?`.
+const fn parse_nibble(pattern: &[u8], position: usize) -> Result<Option<u8>, ()> {
+    match pattern[position] {
+        b'?' => Ok(None),
+        digit @ b'0'..=b'9' => Ok(Some(digit - b'0')),
+        digit @ b'a'..=b'f' => Ok(Some(digit - b'a' + 10)),
+        digit @ b'A'..=b'F' => Ok(Some(digit - b'A' + 10)),
+        _ => Err(()),
+    }
+}
+
+const fn parse_token(
+    pattern: &[u8],
+    start: usize,
+    end: usize,
+) -> Result<PatternByte, PatternError> {
+    const fn invalid_digit(position: usize, start: usize, end: usize) -> PatternError {
+        PatternError::InvalidDigit {
+            position,
+            token: (start, end),
+        }
+    }
+
+    match end - start {
+        1 => match parse_nibble(pattern, start) {
+            Ok(None) => Ok(PatternByte::ANY),
+            Ok(Some(_)) => Err(PatternError::Malformed {
+                position: start,
+                reason: "bytes need two hex digits",
+            }),
+            Err(_) => Err(invalid_digit(start, start, end)),
+        },
+        2 => {
+            let high = match parse_nibble(pattern, start) {
+                Ok(nibble) => nibble,
+                Err(_) => return Err(invalid_digit(start, start, end)),
+            };
+            let low = match parse_nibble(pattern, start + 1) {
+                Ok(nibble) => nibble,
+                Err(_) => return Err(invalid_digit(start + 1, start, end)),
+            };
+            let (mut value, mut mask) = (0, 0);
+            if let Some(high) = high {
+                value |= high << 4;
+                mask |= 0xF0;
+            }
+            if let Some(low) = low {
+                value |= low;
+                mask |= 0x0F;
+            }
+            Ok(PatternByte { value, mask })
+        }
+        _ => Err(PatternError::Malformed {
+            position: start,
+            reason: "bytes need to be separated by whitespace",
+        }),
+    }
+}
+
+/// Number of bytes in `pattern`, checking every one of them.
+pub const fn pattern_len(pattern: &str) -> Result<usize, PatternError> {
+    let bytes = pattern.as_bytes();
+    let mut len = 0;
+    let mut position = 0;
+    while let Some((start, end)) = next_token(bytes, position) {
+        if let Err(error) = parse_token(bytes, start, end) {
+            return Err(error);
+        }
+        len += 1;
+        position = end;
+    }
+    if len == 0 {
+        return Err(PatternError::Malformed {
+            position: 0,
+            reason: "empty pattern",
+        });
+    }
+    Ok(len)
+}
+
+/// Compiles a pattern of `N` bytes, panicking if it's invalid or of a different length. Use
+/// [pattern!](crate::pattern) rather than calling this directly.
+pub const fn compile<const N: usize>(pattern: &str) -> [PatternByte; N] {
+    let bytes = pattern.as_bytes();
+    let mut compiled = [PatternByte::ANY; N];
+    let mut index = 0;
+    let mut position = 0;
+    while let Some((start, end)) = next_token(bytes, position) {
+        match parse_token(bytes, start, end) {
+            Ok(byte) if index < N => compiled[index] = byte,
+            Ok(_) => panic!("pattern is longer than expected"),
+            Err(error) => panic!("{}", error.reason()),
+        }
+        index += 1;
+        position = end;
+    }
+    if index != N {
+        panic!("pattern is shorter than expected");
+    }
+    compiled
+}
+
+/// Parses an ida-style byte sequence pattern.
+pub fn parse_pattern(pattern: &str) -> Result<Vec<PatternByte>, PatternSearchError> {
+    let bytes = pattern.as_bytes();
+    let mut parsed = Vec::new();
+    let mut position = 0;
+    while let Some((start, end)) = next_token(bytes, position) {
+        parsed.push(
+            parse_token(bytes, start, end).map_err(|error| error.into_search_error(pattern))?,
+        );
+        position = end;
+    }
+    if parsed.is_empty() {
+        return Err(PatternSearchError::Malformed {
+            position: 0,
+            reason: "empty pattern",
+        });
+    }
+    Ok(parsed)
+}
+
+/// Formats a pattern the way [parse_pattern] reads it, e.g. `48 8B 0D ? ? ? ? 4?`.
+pub fn format_pattern(pattern: &[PatternByte]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let nibble = |value: u8, mask: u8| match mask {
+        0 => '?',
+        _ => DIGITS[value as usize] as char,
+    };
+    pattern
+        .iter()
+        .map(|byte| match byte.mask {
+            0 => String::from("?"),
+            _ => format!(
+                "{}{}",
+                nibble(byte.value >> 4, byte.mask >> 4),
+                nibble(byte.value & 0x0F, byte.mask & 0x0F)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,140 @@
+//! Minimal PE32+ header definitions, layout compatible with the ones in `winnt.h`.
+
+use crate::assert_size;
+
+pub const IMAGE_DOS_SIGNATURE: u16 = 0x5A4D; // MZ
+pub const IMAGE_NT_SIGNATURE: u32 = 0x00004550; // PE\0\0
+pub const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20B;
+pub const IMAGE_NUMBEROF_DIRECTORY_ENTRIES: usize = 16;
+pub const IMAGE_DIRECTORY_ENTRY_EXCEPTION: usize = 3;
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageDosHeader {
+    pub e_magic: u16,
+    pub e_cblp: u16,
+    pub e_cp: u16,
+    pub e_crlc: u16,
+    pub e_cparhdr: u16,
+    pub e_minalloc: u16,
+    pub e_maxalloc: u16,
+    pub e_ss: u16,
+    pub e_sp: u16,
+    pub e_csum: u16,
+    pub e_ip: u16,
+    pub e_cs: u16,
+    pub e_lfarlc: u16,
+    pub e_ovno: u16,
+    pub e_res: [u16; 4],
+    pub e_oemid: u16,
+    pub e_oeminfo: u16,
+    pub e_res2: [u16; 10],
+    pub e_lfanew: i32,
+}
+assert_size!(ImageDosHeader, 0x40);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageFileHeader {
+    pub machine: u16,
+    pub number_of_sections: u16,
+    pub time_date_stamp: u32,
+    pub pointer_to_symbol_table: u32,
+    pub number_of_symbols: u32,
+    pub size_of_optional_header: u16,
+    pub characteristics: u16,
+}
+assert_size!(ImageFileHeader, 0x14);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageDataDirectory {
+    pub virtual_address: u32,
+    pub size: u32,
+}
+assert_size!(ImageDataDirectory, 0x8);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageOptionalHeader64 {
+    pub magic: u16,
+    pub major_linker_version: u8,
+    pub minor_linker_version: u8,
+    pub size_of_code: u32,
+    pub size_of_initialized_data: u32,
+    pub size_of_uninitialized_data: u32,
+    pub address_of_entry_point: u32,
+    pub base_of_code: u32,
+    pub image_base: u64,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub major_operating_system_version: u16,
+    pub minor_operating_system_version: u16,
+    pub major_image_version: u16,
+    pub minor_image_version: u16,
+    pub major_subsystem_version: u16,
+    pub minor_subsystem_version: u16,
+    pub win32_version_value: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub check_sum: u32,
+    pub subsystem: u16,
+    pub dll_characteristics: u16,
+    pub size_of_stack_reserve: u64,
+    pub size_of_stack_commit: u64,
+    pub size_of_heap_reserve: u64,
+    pub size_of_heap_commit: u64,
+    pub loader_flags: u32,
+    pub number_of_rva_and_sizes: u32,
+    pub data_directory: [ImageDataDirectory; IMAGE_NUMBEROF_DIRECTORY_ENTRIES],
+}
+assert_size!(ImageOptionalHeader64, 0xF0);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageNtHeaders64 {
+    pub signature: u32,
+    pub file_header: ImageFileHeader,
+    pub optional_header: ImageOptionalHeader64,
+}
+assert_size!(ImageNtHeaders64, 0x108);
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageSectionHeader {
+    pub name: [u8; 8],
+    pub virtual_size: u32,
+    pub virtual_address: u32,
+    pub size_of_raw_data: u32,
+    pub pointer_to_raw_data: u32,
+    pub pointer_to_relocations: u32,
+    pub pointer_to_linenumbers: u32,
+    pub number_of_relocations: u16,
+    pub number_of_linenumbers: u16,
+    pub characteristics: u32,
+}
+assert_size!(ImageSectionHeader, 0x28);
+
+impl ImageSectionHeader {
+    /// The section name with trailing nul padding removed.
+    pub fn name(&self) -> &str {
+        let mut len = self.name.len();
+        while len > 0 {
+            if self.name[len - 1] != 0 {
+                break;
+            }
+            len -= 1;
+        }
+        std::str::from_utf8(&self.name[..len]).unwrap_or("")
+    }
+}
+
+/// An entry of the exception directory (`.pdata`), covering a function or a part of one.
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ImageRuntimeFunctionEntry {
+    pub begin_address: u32,
+    pub end_address: u32,
+    pub unwind_info_address: u32,
+}
+assert_size!(ImageRuntimeFunctionEntry, 0xC);
@@ -1,6 +1,9 @@
-use crate::mem::{find_pattern, get_data_section, get_rdata_section};
+use crate::mem::scanner::Scanner;
+use crate::mem::{get_data_section, get_rdata_section};
+use crate::pattern;
 use crate::types::decima::core::rtti::*;
 use std::ffi::c_void;
+use std::slice;
 
 pub unsafe fn scan_memory_for_types(rtti_scan_callback: fn(rtti: *const RTTI)) -> Vec<*const RTTI> {
     unsafe {
@@ -16,16 +19,21 @@ pub unsafe fn scan_memory_for_types(rtti_scan_callback: fn(rtti: *const RTTI)) -
         };
 
         let mut types: Vec<*const RTTI> = Vec::new();
+        if data_start == 0 {
+            return types;
+        }
 
-        let mut current: *const c_void = data_start as *const c_void;
-        loop {
-            let rtti_ptr = find_pattern(current as *mut u8, data_end, "FF FF FF FF ? ? ? ?");
-            let Ok(rtti_ptr) = rtti_ptr else {
-                break;
-            };
-            let rtti_ptr = rtti_ptr as *const c_void;
+        let data = slice::from_raw_parts(data_start as *const u8, data_end - data_start);
+        let scanner = Scanner::new(pattern!("FF FF FF FF ? ? ? ?"));
+        let mut next = 0;
+        for offset in scanner.find_iter(data) {
+            // skip matches starting within the first 5 bytes of the previous one.
+            if offset < next {
+                continue;
+            }
+            next = offset + 5;
+            let rtti_ptr = (data_start + offset) as *const c_void;
 
-            current = rtti_ptr.add(5);
             let rtti = &*(rtti_ptr as *const RTTI);
             if let Some(primitive) = rtti.as_atom() {
                 if primitive.size == 0
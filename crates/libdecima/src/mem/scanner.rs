@@ -0,0 +1,184 @@
+//! Pattern scanning over byte slices.
+//!
+//! Rather than comparing the whole pattern at every position, a [Scanner] looks for the rarest
+//! run of exact bytes in the pattern (its anchor) with `memchr`'s SIMD searcher, and only checks
+//! the rest of the pattern where the anchor is found. [MultiScanner] does the same for many
+//! patterns at once, finding every anchor in a single pass.
+
+use crate::mem::PatternSearchError;
+use crate::mem::pattern::{PatternByte, parse_pattern};
+use aho_corasick::AhoCorasick;
+use memchr::memmem::Finder;
+
+/// How common a byte is in x86-64 code, higher is more common. Anchors are picked to avoid
+/// these, they'd only narrow the search down a little.
+const fn commonness(byte: u8) -> u32 {
+    match byte {
+        0x00 => 255,
+        0xFF | 0xCC => 240,
+        0x48 | 0x8B => 230,
+        0x89 | 0x0F | 0x4C | 0x24 => 200,
+        0x44 | 0xE8 | 0x83 | 0x8D | 0x01 | 0x90 => 180,
+        0x85 | 0xC0 | 0xC3 | 0x74 | 0x75 | 0x41 | 0x49 | 0x20 | 0x10 | 0x08 => 150,
+        0x05 | 0x0D | 0x15 | 0x5C | 0x33 | 0xEB | 0x40 | 0x28 | 0x18 | 0x30 | 0x38 | 0x4D => 120,
+        _ => 60,
+    }
+}
+
+/// The `(offset, bytes)` of the run of exact bytes in `pattern` least likely to show up by
+/// chance, `None` if the pattern is all wildcards.
+fn anchor(pattern: &[PatternByte]) -> Option<(usize, Vec<u8>)> {
+    let mut best: Option<(usize, Vec<u8>, u32)> = None;
+    let mut start = 0;
+    while start < pattern.len() {
+        if pattern[start].mask != 0xFF {
+            start += 1;
+            continue;
+        }
+        let end = pattern[start..]
+            .iter()
+            .position(|byte| byte.mask != 0xFF)
+            .map_or(pattern.len(), |len| start + len);
+        let run = pattern[start..end]
+            .iter()
+            .map(|byte| byte.value)
+            .collect::<Vec<_>>();
+        let rarity = run.iter().map(|byte| 256 - commonness(*byte)).sum::<u32>();
+        if best.as_ref().is_none_or(|(_, _, best)| rarity > *best) {
+            best = Some((start, run, rarity));
+        }
+        start = end;
+    }
+    best.map(|(offset, run, _)| (offset, run))
+}
+
+/// A compiled pattern.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    pattern: Vec<PatternByte>,
+    /// Offset of the anchor in the pattern and a searcher for it.
+    anchor: Option<(usize, Finder<'static>)>,
+}
+
+impl Scanner {
+    pub fn new(pattern: &[PatternByte]) -> Self {
+        Scanner {
+            pattern: pattern.to_vec(),
+            anchor: anchor(pattern).map(|(offset, run)| (offset, Finder::new(&run).into_owned())),
+        }
+    }
+
+    pub fn parse(pattern: &str) -> Result<Self, PatternSearchError> {
+        Ok(Scanner::new(&parse_pattern(pattern)?))
+    }
+
+    pub fn pattern(&self) -> &[PatternByte] {
+        &self.pattern
+    }
+
+    /// Whether the pattern matches `haystack` at `position`.
+    pub fn matches_at(&self, haystack: &[u8], position: usize) -> bool {
+        haystack
+            .get(position..position + self.pattern.len())
+            .is_some_and(|window| {
+                self.pattern
+                    .iter()
+                    .zip(window)
+                    .all(|(pattern, byte)| pattern.matches(*byte))
+            })
+    }
+
+    /// Offset of the first match in `haystack`.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.find_iter(haystack).next()
+    }
+
+    /// Offsets of every match in `haystack`, overlapping ones included, in order.
+    pub fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let mut position = 0;
+        std::iter::from_fn(move || {
+            let Some((offset, finder)) = &self.anchor else {
+                // nothing to anchor on, nibble wildcards still have to match.
+                while position + self.pattern.len() <= haystack.len() {
+                    position += 1;
+                    if self.matches_at(haystack, position - 1) {
+                        return Some(position - 1);
+                    }
+                }
+                return None;
+            };
+            loop {
+                let found = position + finder.find(haystack.get(position..)?)?;
+                position = found + 1;
+                let Some(start) = found.checked_sub(*offset) else {
+                    continue;
+                };
+                if self.matches_at(haystack, start) {
+                    return Some(start);
+                }
+            }
+        })
+    }
+}
+
+/// Many patterns, searched for in one pass.
+#[derive(Debug, Clone)]
+pub struct MultiScanner {
+    scanners: Vec<Scanner>,
+    /// Anchors of every pattern that has one.
+    anchors: AhoCorasick,
+    /// Index of the pattern each anchor belongs to.
+    anchored: Vec<usize>,
+}
+
+impl MultiScanner {
+    pub fn new(patterns: &[Vec<PatternByte>]) -> Self {
+        let scanners = patterns
+            .iter()
+            .map(|pattern| Scanner::new(pattern))
+            .collect::<Vec<_>>();
+        let (anchored, anchors): (Vec<_>, Vec<_>) = patterns
+            .iter()
+            .enumerate()
+            .filter_map(|(index, pattern)| Some((index, anchor(pattern)?.1)))
+            .unzip();
+        MultiScanner {
+            scanners,
+            anchors: AhoCorasick::new(anchors).expect("too many patterns to scan for"),
+            anchored,
+        }
+    }
+
+    /// Offsets of every match of every pattern in `haystack`, indexed like the patterns.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<Vec<usize>> {
+        let mut matches = vec![Vec::new(); self.scanners.len()];
+        for found in self.anchors.find_overlapping_iter(haystack) {
+            let index = self.anchored[found.pattern().as_usize()];
+            let scanner = &self.scanners[index];
+            let Some((offset, _)) = &scanner.anchor else {
+                continue;
+            };
+            match found.start().checked_sub(*offset) {
+                Some(start) if scanner.matches_at(haystack, start) => matches[index].push(start),
+                _ => {}
+            }
+        }
+        // patterns without an anchor are compared at every position.
+        for (index, scanner) in self.scanners.iter().enumerate() {
+            if scanner.anchor.is_none() {
+                matches[index] = scanner.find_iter(haystack).collect();
+            }
+        }
+        matches
+    }
+}
+
+/// Compares the whole pattern at every position, the reference [Scanner] is tested against.
+pub fn find_naive(pattern: &[PatternByte], haystack: &[u8]) -> Option<usize> {
+    haystack.windows(pattern.len()).position(|window| {
+        pattern
+            .iter()
+            .zip(window)
+            .all(|(pattern, byte)| pattern.matches(*byte))
+    })
+}
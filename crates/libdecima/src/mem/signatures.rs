@@ -0,0 +1,471 @@
+//! Per-game signature databases, see `data/signatures` for the format.
+//!
+//! Signatures are data so a game update can be handled by editing (or overriding) a database
+//! rather than the code using them. [Offsets](crate::mem::offsets::Offsets) loads a database and
+//! resolves its entries by name on first use.
+
+use crate::mem::PatternSearchError;
+use crate::mem::image::Image;
+use crate::mem::instruction::target;
+use crate::mem::offsets::Offset;
+use crate::mem::pattern::parse_pattern;
+use crate::mem::scanner::{MultiScanner, Scanner};
+use crate::mem::versions::{Versioned, select};
+use crate::mem::xref::{find_references, find_string, function_start};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::ptr::read_unaligned;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureDatabase {
+    /// Id of the game the database is for, e.g. `hfw`.
+    pub game: String,
+    #[serde(default, rename = "signature")]
+    pub signatures: Vec<Signature>,
+}
+
+impl SignatureDatabase {
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    /// The database shipped with libdecima for `game`, if there is one.
+    pub fn builtin(game: &str) -> Option<Self> {
+        let source = match game {
+            "hfw" => include_str!("../../data/signatures/hfw.toml"),
+            _ => return None,
+        };
+        Some(Self::parse(source).expect("builtin signature database is invalid"))
+    }
+
+    /// Adds the signatures of `other`, replacing every entry with the same name as one of them.
+    pub fn merge(&mut self, other: SignatureDatabase) {
+        let names = other
+            .signatures
+            .iter()
+            .map(|signature| signature.name.clone())
+            .collect::<Vec<_>>();
+        self.signatures
+            .retain(|signature| !names.contains(&signature.name));
+        self.signatures.extend(other.signatures);
+    }
+
+    /// The entries used for game `version`, one per name, see [select].
+    pub fn select(&self, version: &str) -> Vec<&Signature> {
+        select(&self.signatures, version)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub name: String,
+    /// Game versions the entry applies to, prefixes or ranges of `major.minor.patch.build`,
+    /// see [versions](crate::mem::versions). Empty for every version.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+    /// Candidate ida-style patterns, the first one found is used.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// A string in `.rdata` the code references, tried when none of the patterns are found. It
+    /// matches at each instruction in the section loading its address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub string: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<Step>,
+    /// Section of the game module searched, `.text` unless set.
+    #[serde(default = "default_section")]
+    pub section: String,
+}
+
+fn default_section() -> String {
+    String::from(".text")
+}
+
+impl Versioned for Signature {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn versions(&self) -> &[String] {
+        &self.versions
+    }
+}
+
+impl Signature {
+    /// Finds the signature in its section of the game module.
+    pub fn resolve(&self) -> Result<Offset, PatternSearchError> {
+        self.resolve_image(&Image::live()?)
+    }
+
+    /// Finds the signature in its section of `image` and applies its steps to the match.
+    pub fn resolve_image(&self, image: &Image) -> Result<Offset, PatternSearchError> {
+        let data = image.search_range(&self.section)?;
+        for pattern in &self.patterns {
+            if let Some(found) = Scanner::parse(pattern)?.find(data) {
+                return self.apply_steps(image, offset_in(data, found));
+            }
+        }
+        match self.find_references(image)?.first() {
+            Some(found) => self.apply_steps(image, *found),
+            None => Err(PatternSearchError::NotFound),
+        }
+    }
+
+    /// Looks for every match of the signature in its section, see [Signature::check_image].
+    pub fn check(&self) -> SignatureCheck {
+        match Image::live() {
+            Ok(image) => self.check_image(&image),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    /// Like [Signature::resolve_image], but also counts how often the pattern used matched, so
+    /// patterns that are no longer unique can be reported.
+    pub fn check_image(&self, image: &Image) -> SignatureCheck {
+        let data = match image.search_range(&self.section) {
+            Ok(data) => data,
+            Err(error) => return self.failed(error),
+        };
+        for (index, pattern) in self.patterns.iter().enumerate() {
+            match Scanner::parse(pattern) {
+                Ok(scanner) => {
+                    let found = scanner.find_iter(data).collect::<Vec<_>>();
+                    if !found.is_empty() {
+                        return self.matched(image, index, offset_in(data, found[0]), found.len());
+                    }
+                }
+                Err(error) => return self.failed(error),
+            }
+        }
+        self.check_string(image)
+    }
+
+    /// Every instruction in the signature's section referencing [Signature::string], in address
+    /// order. Empty for signatures without one.
+    pub fn find_references(&self, image: &Image) -> Result<Vec<Offset>, PatternSearchError> {
+        let Some(string) = &self.string else {
+            return Ok(Vec::new());
+        };
+        let mut references = Vec::new();
+        for rva in find_string(image, ".rdata", string)? {
+            references.extend(find_references(image, &self.section, rva)?);
+        }
+        references.sort();
+        Ok(references
+            .into_iter()
+            .map(|rva| image.at_rva(rva))
+            .collect())
+    }
+
+    /// Every match of locator `index` in `image`: pattern `index`, or the references to
+    /// [Signature::string] for `patterns.len()`, see [SignatureCheck::found].
+    pub fn find_all(&self, image: &Image, index: usize) -> Result<Vec<Offset>, PatternSearchError> {
+        let Some(pattern) = self.patterns.get(index) else {
+            return self.find_references(image);
+        };
+        let data = image.search_range(&self.section)?;
+        Ok(Scanner::parse(pattern)?
+            .find_iter(data)
+            .map(|found| offset_in(data, found))
+            .collect())
+    }
+
+    /// Whether locator `index` still matches at `rva` in `image`.
+    pub(crate) fn matches_at(&self, image: &Image, index: usize, rva: usize) -> bool {
+        let Some(pattern) = self.patterns.get(index) else {
+            return self.references_string_at(image, rva);
+        };
+        let (Ok(pattern), Ok(section)) =
+            (parse_pattern(pattern), image.search_range(&self.section))
+        else {
+            return false;
+        };
+        rva.checked_sub(section.as_ptr() as usize - image.base())
+            .is_some_and(|position| Scanner::new(&pattern).matches_at(section, position))
+    }
+
+    /// Whether the instruction at `rva` in `image` loads the address of [Signature::string].
+    fn references_string_at(&self, image: &Image, rva: usize) -> bool {
+        let Some(string) = &self.string else {
+            return false;
+        };
+        let Ok(target) = image
+            .instruction(image.at_rva(rva))
+            .and_then(|instruction| target(&instruction))
+        else {
+            return false;
+        };
+        let needle = [string.as_bytes(), &[0]].concat();
+        image
+            .rva(Offset::new(target))
+            .and_then(|start| image.bytes().get(start..start + needle.len()))
+            .is_some_and(|bytes| bytes == needle)
+    }
+
+    /// The check of [Signature::string], once none of the patterns matched.
+    fn check_string(&self, image: &Image) -> SignatureCheck {
+        match self.find_references(image) {
+            Ok(found) if !found.is_empty() => {
+                self.matched(image, self.patterns.len(), found[0], found.len())
+            }
+            Ok(_) => self.failed(PatternSearchError::NotFound),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    /// Hash of everything used to find the signature, changing when any of it is edited.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        for pattern in &self.patterns {
+            hasher.update(pattern.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.update(format!("{:?}\n{:?}\n{}", self.string, self.steps, self.section).as_bytes());
+        hasher.finalize()[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    pub(crate) fn failed(&self, error: PatternSearchError) -> SignatureCheck {
+        SignatureCheck {
+            name: self.name.clone(),
+            result: Err(error),
+            matches: 0,
+            found: None,
+        }
+    }
+
+    /// The check for pattern `pattern` matching `matches` times, first at `found`.
+    pub(crate) fn matched(
+        &self,
+        image: &Image,
+        pattern: usize,
+        found: Offset,
+        matches: usize,
+    ) -> SignatureCheck {
+        SignatureCheck {
+            name: self.name.clone(),
+            result: self.apply_steps(image, found),
+            matches,
+            found: Some((pattern, found)),
+        }
+    }
+
+    fn apply_steps(&self, image: &Image, offset: Offset) -> Result<Offset, PatternSearchError> {
+        self.steps.iter().try_fold(offset, |offset, step| {
+            step.apply_in(image, &self.section, offset)
+        })
+    }
+}
+
+/// Address of the byte at `offset` in `data`.
+fn offset_in(data: &[u8], offset: usize) -> Offset {
+    Offset::new(data.as_ptr() as usize + offset)
+}
+
+/// Checks every signature, scanning each section they're in once rather than once per pattern.
+pub fn check_all(signatures: &[Signature]) -> Vec<SignatureCheck> {
+    match Image::live() {
+        Ok(image) => check_all_image(signatures, &image),
+        Err(error) => signatures
+            .iter()
+            .map(|signature| signature.failed(error.clone()))
+            .collect(),
+    }
+}
+
+/// [check_all] for the sections of `image`.
+pub fn check_all_image(signatures: &[Signature], image: &Image) -> Vec<SignatureCheck> {
+    let mut sections: BTreeMap<&str, Vec<&Signature>> = BTreeMap::new();
+    for signature in signatures {
+        sections
+            .entry(signature.section.as_str())
+            .or_default()
+            .push(signature);
+    }
+
+    let mut checks = Vec::new();
+    for (section, signatures) in sections {
+        match image.search_range(section) {
+            Ok(data) => checks.extend(check_all_data(&signatures, image, data)),
+            Err(error) => checks.extend(
+                signatures
+                    .iter()
+                    .map(|signature| signature.failed(error.clone())),
+            ),
+        }
+    }
+    checks.sort_by_key(|check| {
+        signatures
+            .iter()
+            .position(|signature| signature.name == check.name)
+    });
+    checks
+}
+
+/// Checks `signatures` against `data`, a part of `image`.
+fn check_all_data(signatures: &[&Signature], image: &Image, data: &[u8]) -> Vec<SignatureCheck> {
+    let parsed = signatures
+        .iter()
+        .map(|signature| {
+            signature
+                .patterns
+                .iter()
+                .map(|pattern| parse_pattern(pattern))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let patterns = parsed
+        .iter()
+        .flatten()
+        .filter_map(|pattern| pattern.as_ref().ok().cloned())
+        .collect::<Vec<_>>();
+    let mut found = MultiScanner::new(&patterns).find_all(data).into_iter();
+
+    signatures
+        .iter()
+        .zip(parsed)
+        .map(|(signature, patterns)| {
+            // matches of every pattern are taken from `found`, even after one is used.
+            let patterns = patterns
+                .into_iter()
+                .map(|pattern| pattern.map(|_| found.next().unwrap_or_default()))
+                .collect::<Vec<_>>();
+            for (index, pattern) in patterns.into_iter().enumerate() {
+                match pattern {
+                    Ok(offsets) if offsets.is_empty() => continue,
+                    Ok(offsets) => {
+                        let found = offset_in(data, offsets[0]);
+                        return signature.matched(image, index, found, offsets.len());
+                    }
+                    Err(error) => return signature.failed(error),
+                }
+            }
+            signature.check_string(image)
+        })
+        .collect()
+}
+
+/// Outcome of looking for a signature.
+#[derive(Debug, Clone)]
+pub struct SignatureCheck {
+    pub name: String,
+    pub result: Result<Offset, PatternSearchError>,
+    /// How often the pattern used matched. The first match is used, but with more than one it
+    /// may not be the right one.
+    pub matches: usize,
+    /// Index of the pattern used and where it first matched, before any steps. The index is
+    /// `patterns.len()` when the signature was found through its string.
+    pub found: Option<(usize, Offset)>,
+}
+
+impl SignatureCheck {
+    pub fn is_ambiguous(&self) -> bool {
+        self.result.is_ok() && self.matches > 1
+    }
+}
+
+/// Every loaded signature checked in one pass, see
+/// [Offsets::check](crate::mem::offsets::Offsets::check).
+#[derive(Debug, Clone, Default)]
+pub struct SignatureReport {
+    pub checks: Vec<SignatureCheck>,
+}
+
+impl SignatureReport {
+    pub fn get(&self, name: &str) -> Option<&SignatureCheck> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &SignatureCheck> {
+        self.checks.iter().filter(|check| check.result.is_err())
+    }
+
+    pub fn ambiguous(&self) -> impl Iterator<Item = &SignatureCheck> {
+        self.checks.iter().filter(|check| check.is_ambiguous())
+    }
+
+    /// The `names` that weren't found, or aren't in the database at all. Used to turn off
+    /// features that depend on them.
+    pub fn missing<'a>(&self, names: &[&'a str]) -> Vec<&'a str> {
+        names
+            .iter()
+            .copied()
+            .filter(|name| !self.get(name).is_some_and(|check| check.result.is_ok()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// Moves into the match, e.g. to the instruction of interest.
+    Offset(isize),
+    /// Follows the rel32 operand at the end of an instruction of this length.
+    RipRelative(usize),
+    /// Reads the pointer at the address.
+    Deref,
+    Adjust(isize),
+    /// Follows the RIP-relative operand or branch target of the instruction at the address,
+    /// whatever its encoding. Repeat it to follow a call through a jmp thunk.
+    Follow,
+    /// Moves this many instructions forward.
+    Skip(usize),
+    /// Moves to the start of the function containing the address, e.g. from the instruction
+    /// referencing a signature's string.
+    Function,
+}
+
+impl Step {
+    /// Applies the step to an address in the current process.
+    pub fn apply(&self, offset: Offset) -> Result<Offset, PatternSearchError> {
+        let address = offset.as_ptr::<u8>() as usize;
+        match *self {
+            Step::Offset(bytes) | Step::Adjust(bytes) => {
+                Ok(Offset::new(address.wrapping_add_signed(bytes)))
+            }
+            Step::RipRelative(instruction_length) => Ok(offset.as_relative(instruction_length)),
+            Step::Deref => Ok(Offset::new(unsafe {
+                read_unaligned(address as *const usize)
+            })),
+            Step::Follow => offset.as_target(),
+            Step::Skip(count) => offset.as_next(count),
+            Step::Function => self.apply_in(&Image::live()?, &default_section(), offset),
+        }
+    }
+
+    /// [Step::apply] for an offset in `section` of `image`, failing rather than reading outside
+    /// of it.
+    pub fn apply_in(
+        &self,
+        image: &Image,
+        section: &str,
+        offset: Offset,
+    ) -> Result<Offset, PatternSearchError> {
+        let address = offset.as_ptr::<u8>() as usize;
+        match *self {
+            Step::Offset(_) | Step::Adjust(_) => self.apply(offset),
+            Step::RipRelative(instruction_length) => {
+                let end = address.wrapping_add(instruction_length);
+                let displacement =
+                    image.read::<i32>(Offset::new(end.wrapping_sub(size_of::<i32>())))?;
+                Ok(Offset::new(end.wrapping_add_signed(displacement as isize)))
+            }
+            Step::Deref => image.deref(offset),
+            Step::Follow => Ok(Offset::new(target(&image.instruction(offset)?)?)),
+            Step::Skip(count) => {
+                let mut offset = offset;
+                for _ in 0..count {
+                    offset = Offset::new(image.instruction(offset)?.next_ip() as usize);
+                }
+                Ok(offset)
+            }
+            Step::Function => {
+                let rva = image.rva(offset).ok_or(PatternSearchError::OutOfRange)?;
+                Ok(image.at_rva(function_start(image, section, rva)?))
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Picking the database entries meant for a game version, see [select].
+//!
+//! Signature and patch databases can list several entries with the same name, each for some
+//! versions of the game. An entry's `versions` are alternatives, each either a prefix of the
+//! version (`1.5` matches `1.5.80.0` but not `1.50.0.0`) or a range of comma-separated
+//! comparisons (`>=1.5.80, <1.6`). Missing components compare as 0, so `>=1.5.80` matches
+//! `1.5.80.0`.
+
+use std::cmp::Ordering;
+
+/// A database entry that can be limited to some game versions.
+pub trait Versioned {
+    fn name(&self) -> &str;
+
+    /// Empty for every version.
+    fn versions(&self) -> &[String];
+
+    fn matches_version(&self, version: &str) -> bool {
+        matches_version(self.versions(), version)
+    }
+}
+
+/// Whether `version` matches one of `versions`, or `versions` is empty.
+pub fn matches_version(versions: &[String], version: &str) -> bool {
+    versions.is_empty() || versions.iter().any(|entry| matches_entry(entry, version))
+}
+
+fn matches_entry(entry: &str, version: &str) -> bool {
+    let entry = entry.trim();
+    if !entry.starts_with(['<', '>', '=']) {
+        return version == entry
+            || version
+                .strip_prefix(entry)
+                .is_some_and(|rest| rest.starts_with('.'));
+    }
+    entry.split(',').all(|comparison| {
+        let comparison = comparison.trim();
+        let (operator, bound) = ["<=", ">=", "<", ">", "="]
+            .iter()
+            .find_map(|operator| Some((*operator, comparison.strip_prefix(operator)?)))
+            .unwrap_or(("=", comparison));
+        let Some(ordering) = compare(version, bound.trim()) else {
+            return false;
+        };
+        match operator {
+            "<=" => ordering.is_le(),
+            ">=" => ordering.is_ge(),
+            "<" => ordering.is_lt(),
+            ">" => ordering.is_gt(),
+            _ => ordering.is_eq(),
+        }
+    })
+}
+
+/// Compares two dotted versions component by component, `None` if either isn't numeric.
+fn compare(a: &str, b: &str) -> Option<Ordering> {
+    let parse = |version: &str| {
+        version
+            .split('.')
+            .map(|component| component.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()
+    };
+    let (a, b) = (parse(a)?, parse(b)?);
+    let component = |parts: &[u64], index: usize| parts.get(index).copied().unwrap_or(0);
+    Some(
+        (0..a.len().max(b.len()))
+            .map(|index| component(&a, index).cmp(&component(&b, index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal),
+    )
+}
+
+/// The entries used for game `version`, one per name in the order they first appear. Entries
+/// listing a matching version win over entries without any, otherwise the first entry is used.
+pub fn select<'a, T: Versioned>(entries: &'a [T], version: &str) -> Vec<&'a T> {
+    let mut selected: Vec<&T> = Vec::new();
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.matches_version(version))
+    {
+        match selected
+            .iter_mut()
+            .find(|existing| existing.name() == entry.name())
+        {
+            Some(existing) if existing.versions().is_empty() && !entry.versions().is_empty() => {
+                *existing = entry;
+            }
+            Some(_) => {}
+            None => selected.push(entry),
+        }
+    }
+    selected
+}
@@ -0,0 +1,167 @@
+//! Finding code through the strings it references, see [find_string_functions].
+//!
+//! Log messages and assert strings rarely change between game updates, while the code using them
+//! is recompiled and moves around. A unique string in `.rdata`, the instructions loading its
+//! address and the functions they're in are often easier to find than a byte pattern that
+//! survives the next patch.
+
+use crate::mem::PatternSearchError;
+use crate::mem::functions::FunctionTable;
+use crate::mem::image::{Image, read_at};
+use iced_x86::{Decoder, DecoderOptions};
+use memchr::memmem;
+
+const INT3: u8 = 0xCC;
+/// Alignment MSVC gives functions, the `int3` padding before them fills the gap.
+const FUNCTION_ALIGNMENT: usize = 16;
+
+/// RVAs of every nul-terminated copy of `text` in `section` of `image`.
+pub fn find_string(
+    image: &Image,
+    section: &str,
+    text: &str,
+) -> Result<Vec<usize>, PatternSearchError> {
+    let data = image.search_range(section)?;
+    let start = data.as_ptr() as usize - image.base();
+    let needle = [text.as_bytes(), &[0]].concat();
+    Ok(memmem::find_iter(data, &needle)
+        .map(|position| start + position)
+        .collect())
+}
+
+/// RVAs of every instruction in `section` of `image` with a RIP-relative operand pointing at
+/// `rva`, e.g. `lea rcx, [rip+x]` or `mov rax, [rip+x]`.
+pub fn find_references(
+    image: &Image,
+    section: &str,
+    rva: usize,
+) -> Result<Vec<usize>, PatternSearchError> {
+    let code = image.search_range(section)?;
+    let start = code.as_ptr() as usize - image.base();
+    let mut references = Vec::new();
+    for position in 0..code.len().saturating_sub(size_of::<i32>() - 1) {
+        let Some(displacement) = read_at::<i32>(code, position) else {
+            break;
+        };
+        // the displacement is relative to the end of the instruction, which can still have an
+        // immediate after it.
+        let end = (start + position + size_of::<i32>()) as isize + displacement as isize;
+        if !matches!(rva as isize - end, 0 | 1 | 2 | 4) {
+            continue;
+        }
+        let Some(reference) = reference_at(image, code, start, position, rva) else {
+            continue;
+        };
+        if references.last() != Some(&reference) {
+            references.push(reference);
+        }
+    }
+    Ok(references)
+}
+
+/// The RVA of the instruction with its displacement at `position` in `code` if it points at
+/// `rva`. Decodes from the start of the function (or the part of it), as the bytes before a
+/// displacement can be read as more than one instruction.
+fn reference_at(
+    image: &Image,
+    code: &[u8],
+    start: usize,
+    position: usize,
+    rva: usize,
+) -> Option<usize> {
+    let function = match FunctionTable::new(image).entry_containing(start + position) {
+        Some(entry) => entry.start,
+        None => start + after_padding(code, start, position),
+    }
+    .clamp(start, start + position);
+    let mut decoder = Decoder::with_ip(
+        64,
+        &code[function - start..],
+        function as u64,
+        DecoderOptions::NONE,
+    );
+    let displacement = start + position;
+    while decoder.can_decode() {
+        let instruction = decoder.decode();
+        let ip = instruction.ip() as usize;
+        if ip > displacement {
+            return None;
+        }
+        if instruction.next_ip() as usize <= displacement {
+            continue;
+        }
+        let offsets = decoder.get_constant_offsets(&instruction);
+        let points_at = !instruction.is_invalid()
+            && instruction.is_ip_rel_memory_operand()
+            && instruction.ip_rel_memory_address() as usize == rva
+            && ip + offsets.displacement_offset() == displacement;
+        return points_at.then_some(ip);
+    }
+    None
+}
+
+/// RVA of the start of the function containing the instruction at `rva`: the one its `.pdata`
+/// entry gives, or for code without one (leaf functions, plain buffers) the first aligned byte
+/// after the `int3` padding before it in `section`. That's only trusted if decoding from there
+/// lands on `rva`, as the padding bytes can also be part of an instruction.
+pub fn function_start(
+    image: &Image,
+    section: &str,
+    rva: usize,
+) -> Result<usize, PatternSearchError> {
+    if rva >= image.bytes().len() {
+        return Err(PatternSearchError::OutOfRange);
+    }
+    if let Some(function) = FunctionTable::new(image).function_containing(rva) {
+        return Ok(function.start);
+    }
+    let code = image.search_range(section)?;
+    let start = code.as_ptr() as usize - image.base();
+    let position = rva
+        .checked_sub(start)
+        .filter(|position| *position < code.len())
+        .ok_or(PatternSearchError::OutOfRange)?;
+    let function = after_padding(code, start, position);
+    let mut decoder = Decoder::with_ip(
+        64,
+        &code[function..],
+        (start + function) as u64,
+        DecoderOptions::NONE,
+    );
+    while decoder.can_decode() {
+        let instruction = decoder.decode();
+        let ip = instruction.ip() as usize;
+        if instruction.is_invalid() || ip > rva {
+            break;
+        }
+        if ip == rva {
+            return Ok(start + function);
+        }
+    }
+    Err(PatternSearchError::NoFunctionStart(rva))
+}
+
+/// Position in `code` (at RVA `start`) of the first aligned byte after the `int3` padding before
+/// `position`, or of the start of `code`.
+fn after_padding(code: &[u8], start: usize, position: usize) -> usize {
+    (1..=position)
+        .rev()
+        .find(|candidate| {
+            code[candidate - 1] == INT3 && (start + candidate).is_multiple_of(FUNCTION_ALIGNMENT)
+        })
+        .unwrap_or(0)
+}
+
+/// RVAs of the start of every function in `.text` referencing `text` in `.rdata`, in address
+/// order and without duplicates.
+pub fn find_string_functions(image: &Image, text: &str) -> Result<Vec<usize>, PatternSearchError> {
+    let mut functions = Vec::new();
+    for string in find_string(image, ".rdata", text)? {
+        for reference in find_references(image, ".text", string)? {
+            functions.push(function_start(image, ".text", reference)?);
+        }
+    }
+    functions.sort();
+    functions.dedup();
+    Ok(functions)
+}
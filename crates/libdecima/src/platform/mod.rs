@@ -0,0 +1,37 @@
+//! OS specific services used by libdecima.
+//!
+//! Everything that has to talk to the operating system goes through [Platform] so the rest of
+//! the crate (pattern parsing, RTTI models, etc.) can be built and tested on any host.
+
+#[cfg(not(windows))]
+mod stub;
+#[cfg(windows)]
+mod win32;
+
+use std::ffi::c_void;
+
+#[cfg(not(windows))]
+pub use stub::{SRWLOCK, StubPlatform};
+#[cfg(windows)]
+pub use win32::{SRWLOCK, WindowsPlatform};
+
+pub trait Platform {
+    /// Returns the `(start, end)` address range of the main executable module.
+    fn main_module() -> Option<(usize, usize)>;
+
+    /// Writes `data` to `ptr`, temporarily lifting page protection if required.
+    ///
+    /// Returns `false` if the memory could not be made writable.
+    ///
+    /// # Safety
+    /// `ptr` must point to `data.len()` bytes of mapped memory owned by the current process.
+    unsafe fn write_memory(ptr: *mut c_void, data: &[u8]) -> bool;
+}
+
+/// The [Platform] implementation for the current target.
+#[cfg(windows)]
+pub type CurrentPlatform = WindowsPlatform;
+
+/// The [Platform] implementation for the current target.
+#[cfg(not(windows))]
+pub type CurrentPlatform = StubPlatform;
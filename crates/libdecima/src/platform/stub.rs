@@ -0,0 +1,40 @@
+use crate::platform::Platform;
+use std::ffi::c_void;
+use std::sync::Mutex;
+
+/// Layout compatible stand-in for the win32 `SRWLOCK`.
+#[allow(non_snake_case)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct SRWLOCK {
+    pub Ptr: *mut c_void,
+}
+
+/// Used on non-windows hosts (mainly for tests), there is no game module to find unless a test
+/// sets one, and memory is assumed to already be writable.
+pub struct StubPlatform;
+
+static MAIN_MODULE: Mutex<Option<(usize, usize)>> = Mutex::new(None);
+
+impl StubPlatform {
+    /// Sets what [Platform::main_module] returns, so lookups relative to the game module can be
+    /// tested. Anything scanning the module reads the whole range.
+    pub fn set_main_module(module: Option<(usize, usize)>) {
+        *MAIN_MODULE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = module;
+    }
+}
+
+impl Platform for StubPlatform {
+    fn main_module() -> Option<(usize, usize)> {
+        *MAIN_MODULE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    unsafe fn write_memory(ptr: *mut c_void, data: &[u8]) -> bool {
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len()) };
+        true
+    }
+}
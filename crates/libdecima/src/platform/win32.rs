@@ -0,0 +1,46 @@
+use crate::mem::pe::{ImageDosHeader, ImageNtHeaders64};
+use crate::platform::Platform;
+use std::ffi::c_void;
+use windows::Win32::System::Diagnostics::Debug::FlushInstructionCache;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Memory::{
+    PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS, VirtualProtect,
+};
+use windows::Win32::System::Threading::GetCurrentProcess;
+
+pub use windows::Win32::System::Threading::SRWLOCK;
+
+pub struct WindowsPlatform;
+
+impl Platform for WindowsPlatform {
+    fn main_module() -> Option<(usize, usize)> {
+        let base = unsafe { GetModuleHandleW(None).ok()? };
+        if base.0.is_null() {
+            return None;
+        }
+
+        let base = base.0 as usize;
+        let dos_header = unsafe { &*(base as *const ImageDosHeader) };
+        let nt_headers_ptr =
+            (base as isize).wrapping_add(dos_header.e_lfanew as isize) as *const ImageNtHeaders64;
+        if nt_headers_ptr.is_null() {
+            return None;
+        }
+        let nt_headers = unsafe { &*nt_headers_ptr };
+        let end = base + nt_headers.optional_header.size_of_image as usize;
+        Some((base, end))
+    }
+
+    unsafe fn write_memory(ptr: *mut c_void, data: &[u8]) -> bool {
+        unsafe {
+            let mut flags = PAGE_PROTECTION_FLAGS::default();
+            if VirtualProtect(ptr, data.len(), PAGE_EXECUTE_READWRITE, &mut flags).is_err() {
+                return false;
+            }
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len());
+            let _ = VirtualProtect(ptr, data.len(), flags, &mut flags);
+            let _ = FlushInstructionCache(GetCurrentProcess(), Some(ptr as *const _), data.len());
+        }
+        true
+    }
+}
@@ -1,4 +1,4 @@
-use crate::mem::offsets::Offset;
+use crate::mem::offsets::Offsets;
 use crate::types::decima::core::rtti::RTTI;
 use crate::types::decima::p_core::prelude::{Array, HashMap};
 use crate::{assert_size, gen_with_vtbl};
@@ -144,9 +144,8 @@ pub struct ExportedSymbols {
 
 impl ExportedSymbols {
     pub fn get() -> Option<&'static ExportedSymbols> {
-        let ptr = Offset::from_signature("48 63 05 ? ? ? ? 4D 8B 3E")
-            .unwrap()
-            .as_relative(7)
+        let ptr = Offsets::find("ExportedSymbols::Instance")
+            .ok()?
             .as_ptr::<ExportedSymbols>();
         if !ptr.is_null() {
             let instance = unsafe { &*ptr };
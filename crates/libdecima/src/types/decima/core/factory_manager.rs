@@ -38,7 +38,4 @@ gen_with_vtbl!(
     pub unk_60: SharedLockProtected<Array<*mut c_void>>,
 );
 
-impl_instance!(
-    FactoryManager,
-    "48 8B 0D ? ? ? ? 48 89 54 24 ? 8B 42 F8 89 44 24 28 8B 42 F4 48 8D 54 24 ? 89 44 24 2C E8 ? ? ? ? 48 85 C0 74 0D 48 8B C8 E8"
-);
+impl_instance!(FactoryManager, "FactoryManager::Instance");
@@ -1,8 +1,8 @@
 use std::{ffi::c_void, fmt::Debug};
 
 use crate::{
-    assert_offset, assert_size,
-    mem::offsets::Offset,
+    assert_offset, assert_size, decima_fn,
+    mem::PatternSearchError,
     types::decima::p_core::prelude::{Array, GGUUID},
 };
 
@@ -64,14 +64,8 @@ assert_offset!(Player, camera_stack, 0xf0);
 assert_size!(Player, 0x128);
 
 impl Player {
-    pub fn get_local_player(index: u32) -> *mut Player {
-        let func = Offset::from_signature("40 57 48 83 EC 30 48 63 F9 48 8B 0D ? ? ? ? 48 85 C9")
-            .unwrap()
-            .as_ptr::<c_void>();
-
-        let func: extern "fastcall" fn(u32) -> *mut Player = unsafe { std::mem::transmute(func) };
-
-        func(index)
+    pub fn get_local_player(index: u32) -> Result<*mut Player, PatternSearchError> {
+        unsafe { player_get_local_player(index) }
     }
 
     pub fn get_last_active_camera(&self) -> Option<*mut CameraEntity> {
@@ -83,6 +77,10 @@ impl Player {
     }
 }
 
+decima_fn! {
+    fn player_get_local_player(index: u32) -> *mut Player = Offsets["Player::GetLocalPlayer"];
+}
+
 impl Debug for Player {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Player")
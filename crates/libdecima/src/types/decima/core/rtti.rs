@@ -1,9 +1,9 @@
 use super::super::p_core::prelude::*;
-use crate::assert_size;
-use crate::mem::offsets::Offset;
+use crate::mem::PatternSearchError;
 use crate::types::decima::core::rtti_object::RTTIObject;
+use crate::{assert_size, decima_fn};
 use bitflags::bitflags;
-use std::ffi::{CStr, c_char, c_void};
+use std::ffi::{CStr, CString, c_char, c_void};
 use std::slice;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -178,37 +178,29 @@ impl RTTI {
         }
     }
 
-    pub fn get_core_binary_type_id(&self) -> u64 {
-        let func = unsafe {
-            &*Offset::from_signature("40 57 48 81 EC 30 08 00 00 0F B6 41 04 4C 8D")
-                .unwrap()
-                .as_ptr::<extern "C" fn(*const RTTI) -> u64>()
-        };
+    pub fn get_core_binary_type_id(&self) -> Result<u64, PatternSearchError> {
+        unsafe { rtti_get_core_binary_type_id(self) }
+    }
 
-        func(self as *const Self)
+    pub fn create_instance(&self) -> Result<*mut c_void, PatternSearchError> {
+        unsafe { rtti_create_instance(self) }
     }
 
-    pub fn create_instance(&self) -> *mut c_void {
-        let func = unsafe {
-            &*Offset::from_signature(
-                "48 89 6C 24 10 48 89 74 24 18 57 48 83 EC 20 48 8B F9 E8 ? ? ? ? 80 79 04 04",
-            )
-            .unwrap()
-            .as_ptr::<extern "C" fn(*const RTTI) -> *mut c_void>()
+    /// Returns `None` if the game has no type called `name`.
+    pub fn find_by_name<'a>(name: &str) -> Result<Option<&'a RTTI>, PatternSearchError> {
+        let Ok(name) = CString::new(name) else {
+            return Ok(None);
         };
-
-        func(self as *const Self)
+        Ok(unsafe { rtti_find_by_name(name.as_ptr())?.as_ref() })
     }
+}
 
-    pub fn find_by_name<'a>(name: &str) -> &'a RTTI {
-        let func = Offset::from_signature(
-            "48 83 EC 38 48 85 C9 74 37 48 89 4C 24 20 48 C7 C0 FF FF FF FF",
-        )
-        .unwrap()
-        .as_ptr::<extern "C" fn(*const c_char) -> *const RTTI>();
+decima_fn! {
+    fn rtti_get_core_binary_type_id(this: *const RTTI) -> u64 = Offsets["RTTI::GetCoreBinaryTypeId"];
 
-        unsafe { &*(&*func)(name.as_ptr() as *const c_char) }
-    }
+    fn rtti_create_instance(this: *const RTTI) -> *mut c_void = Offsets["RTTI::CreateInstance"];
+
+    fn rtti_find_by_name(name: *const c_char) -> *const RTTI = Offsets["RTTI::FindByName"];
 }
 
 #[derive(Debug)]
@@ -284,7 +276,7 @@ impl SerializableRTTI for RTTIAtom {
         if self.fn_to_string.is_null() {
             None
         } else {
-            let mut str = String::new();
+            let mut str = String::new().ok()?;
             let res = unsafe { (&*self.fn_to_string)(object, &mut str) };
             if res {
                 let cstr = unsafe { CStr::from_ptr(str.data) };
@@ -400,7 +392,7 @@ impl SerializableRTTI for RTTIContainer {
         } else if self.base.kind == RTTIKind::Container {
             let container = unsafe { &*(self.container_type as *mut RTTIContainerData) };
             if !container.fn_to_string.is_null() {
-                let mut string = String::new();
+                let mut string = String::new().ok()?;
                 if unsafe {
                     (&*container.fn_to_string)(
                         object,
@@ -688,7 +680,8 @@ impl SerializableRTTI for RTTICompound {
     fn serialize(&self, object: *const c_void) -> Option<std::string::String> {
         // todo: cache RTTIObject and GGUUID
 
-        if self.base.is_kind(RTTI::find_by_name("RTTIObject")) {
+        if matches!(RTTI::find_by_name("RTTIObject"), Ok(Some(rtti_object)) if self.base.is_kind(rtti_object))
+        {
             let downcast =
                 unsafe { &mut *(object as *const RTTIObject as *mut RTTIObject) }.GetRTTI();
             if downcast.is_exact_kind(&self.base) {
@@ -697,7 +690,7 @@ impl SerializableRTTI for RTTICompound {
         }
 
         if !self.fn_to_string.is_null() {
-            let mut string = String::new();
+            let mut string = String::new().ok()?;
             return if unsafe { (&*self.fn_to_string)(object, &mut string) } {
                 let cstr = unsafe { std::ffi::CStr::from_ptr(string.data) };
                 let string = cstr.to_string_lossy().into_owned();
@@ -705,7 +698,8 @@ impl SerializableRTTI for RTTICompound {
             } else {
                 None
             };
-        } else if self.base.is_exact_kind(RTTI::find_by_name("GGUUID")) {
+        } else if matches!(RTTI::find_by_name("GGUUID"), Ok(Some(gguuid)) if self.base.is_exact_kind(gguuid))
+        {
             // handle GGUUID
             let uuid = unsafe { &*(object as *const GGUUID) };
             return Some(format!("\"{uuid}\""));
@@ -1,4 +1,4 @@
-use windows::Win32::System::Threading::SRWLOCK;
+use crate::platform::SRWLOCK;
 
 #[derive(Debug, Copy, Clone)]
 #[repr(C)]
@@ -1,5 +1,5 @@
-use crate::assert_size;
-use crate::mem::offsets::Offset;
+use crate::mem::PatternSearchError;
+use crate::{assert_size, decima_fn};
 use std::ffi::c_char;
 
 #[derive(Debug, Clone)]
@@ -22,16 +22,6 @@ pub struct String {
 }
 
 impl String {
-    fn internal_init(&self, data: *const c_char, size: usize) {
-        let func = unsafe {
-            *Offset::from_signature("48 89 5C 24 08 48 89 6C 24 10 48 89 74 24 18 57 48 83 EC 20 48 8B 01 48 8B EA 49 63 F8 48 8B F1 45 85 C0")
-            .unwrap()
-            .as_ptr::<extern "C" fn(*mut String, *const c_char, usize /* size_t */)>()
-        };
-
-        func(self as *const Self as *mut Self, data, size);
-    }
-
     fn internal_data(&self) -> &StringData {
         unsafe {
             &*std::mem::transmute::<isize, *mut StringData>(
@@ -45,12 +35,12 @@ impl String {
         self.internal_data().length
     }
 
-    pub fn new() -> Self {
-        let string = Self {
+    pub fn new() -> Result<Self, PatternSearchError> {
+        let mut string = Self {
             data: std::ptr::null(),
         };
-        string.internal_init(std::ptr::null(), 0);
-        string
+        unsafe { string_init(&mut string, std::ptr::null(), 0)? };
+        Ok(string)
     }
 
     pub fn as_string(&self) -> std::string::String {
@@ -61,11 +51,13 @@ impl String {
 
 impl Drop for String {
     fn drop(&mut self) {
-        let func = Offset::from_signature(
-            "40 53 48 83 EC 20 48 8B 19 48 8D 05 ? ? ? ? 48 83 EB 10 48 3B D8",
-        )
-        .unwrap()
-        .as_ptr::<extern "C" fn(*mut String)>();
-        unsafe { (*func)(self as *const Self as *mut Self) };
+        // leaking is the better option if the destructor can't be found, drop can't fail.
+        let _ = unsafe { string_drop(self) };
     }
 }
+
+decima_fn! {
+    fn string_init(this: *mut String, data: *const c_char, size: usize) = Offsets["String::String"];
+
+    fn string_drop(this: *mut String) = Offsets["String::~String"];
+}
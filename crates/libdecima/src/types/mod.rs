@@ -1,3 +1,3 @@
 pub mod decima;
-#[cfg(feature = "nixxes")]
+#[cfg(all(feature = "nixxes", windows))]
 pub mod nixxes;
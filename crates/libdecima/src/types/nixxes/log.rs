@@ -33,7 +33,4 @@ gen_with_vtbl!(
     pub lock: CRITICAL_SECTION,
 );
 
-impl_instance!(
-    NxLogImpl,
-    "48 8B 1D ? ? ? ? 48 8B 03 48 8B 78 48 48 8B 0D ? ? ? ?"
-);
+impl_instance!(NxLogImpl, "nx::NxLogImpl::Instance");
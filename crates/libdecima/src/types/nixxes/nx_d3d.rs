@@ -73,7 +73,4 @@ assert_offset!(NxD3DImpl, driver, 0x180);
 
 assert_offset!(NxD3DImplVtbl, fn_get_command_queue, 264);
 
-impl_instance!(
-    NxD3DImpl,
-    "48 8B 0D ? ? ? ? 8B D3 4C 8B 01 41 FF 90 ? ? 00 00 48 81 C4 ? 01 00 00 5B C3"
-);
+impl_instance!(NxD3DImpl, "nx::NxD3DImpl::Instance");
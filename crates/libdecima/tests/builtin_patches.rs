@@ -0,0 +1,154 @@
+use libdecima::mem::PatternSearchError;
+use libdecima::mem::builtin_patches::{BuiltinPatchError, BuiltinPatchState, PatchDatabase};
+use libdecima::mem::patches::{PatchError, PatchRegistry};
+use libdecima::mem::signatures::SignatureDatabase;
+
+fn database() -> PatchDatabase {
+    PatchDatabase::parse(
+        r#"
+        game = "hfw"
+
+        [[patch]]
+        name = "Ret"
+        description = "Returns right away."
+        signature = "First"
+        bytes = "C3"
+
+        [[patch]]
+        name = "True"
+        signature = "Second"
+        bytes = "B0 01 90"
+        expected = "E8 00 00"
+
+        [[patch]]
+        name = "True"
+        versions = ["1.5"]
+        signature = "Second"
+        bytes = "B0 01"
+
+        [[patch]]
+        name = "Off"
+        signature = "First"
+        bytes = "90"
+        enabled = false
+
+        [[patch]]
+        name = "Missing"
+        signature = "Missing"
+        bytes = "90"
+        "#,
+    )
+    .unwrap()
+}
+
+fn resolve(signature: &str) -> Result<usize, PatternSearchError> {
+    match signature {
+        "First" => Ok(0),
+        "Second" => Ok(8),
+        _ => Err(PatternSearchError::UnknownSignature(signature.to_string())),
+    }
+}
+
+#[test]
+fn builtin_patches_are_applied() {
+    let database = database();
+    let mut registry = PatchRegistry::new(vec![0xCC; 0x10]);
+    let statuses =
+        unsafe { database.apply(&mut registry, "1.4.0.0", |patch| patch.enabled, resolve) };
+
+    let names = statuses
+        .iter()
+        .map(|status| status.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Ret", "True", "Off", "Missing"]);
+    assert!(matches!(statuses[0].state, BuiltinPatchState::Applied));
+    assert_eq!(statuses[0].description, "Returns right away.");
+    assert!(matches!(
+        statuses[1].state,
+        BuiltinPatchState::Failed(BuiltinPatchError::Patch(PatchError::UnexpectedBytes { .. }))
+    ));
+    assert!(matches!(statuses[2].state, BuiltinPatchState::Disabled));
+    assert!(matches!(
+        &statuses[3].state,
+        BuiltinPatchState::Failed(BuiltinPatchError::Signature { name, .. }) if name == "Missing"
+    ));
+
+    assert_eq!(registry.memory()[0], 0xC3);
+    assert_eq!(registry.memory()[8], 0xCC);
+    let entry = registry.get("Ret").unwrap();
+    assert_eq!(entry.patch.owner, "cauldron");
+    assert!(registry.get("Off").is_none());
+}
+
+#[test]
+fn builtin_patches_follow_the_config() {
+    let database = database();
+    let mut registry = PatchRegistry::new(vec![0xCC; 0x10]);
+    // the entry for the version wins, and the config can turn patches on and off.
+    let statuses = unsafe {
+        database.apply(
+            &mut registry,
+            "1.5.80.0",
+            |patch| patch.name != "Ret",
+            resolve,
+        )
+    };
+    assert!(matches!(statuses[0].state, BuiltinPatchState::Disabled));
+    assert!(matches!(statuses[1].state, BuiltinPatchState::Applied));
+    assert!(matches!(statuses[2].state, BuiltinPatchState::Applied));
+    assert_eq!(registry.memory()[..2], [0x90, 0xCC]);
+    assert_eq!(registry.memory()[8..10], [0xB0, 0x01]);
+}
+
+#[test]
+fn patch_databases_merge_by_name() {
+    let mut database = database();
+    database.merge(
+        PatchDatabase::parse(
+            r#"
+            game = "hfw"
+
+            [[patch]]
+            name = "Ret"
+            signature = "Second"
+            bytes = "C3 ?"
+
+            [[patch]]
+            name = "New"
+            signature = "First"
+            bytes = "CC"
+            "#,
+        )
+        .unwrap(),
+    );
+    let selected = database.select("1.4.0.0");
+    let names = selected
+        .iter()
+        .map(|patch| patch.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["True", "Off", "Missing", "Ret", "New"]);
+
+    // wildcards have nothing to write.
+    assert!(matches!(
+        selected[3].to_patch(0),
+        Err(PatternSearchError::Malformed { position: 1, .. })
+    ));
+}
+
+#[test]
+fn builtin_patches_use_builtin_signatures() {
+    let patches = PatchDatabase::builtin("hfw").unwrap();
+    let signatures = SignatureDatabase::builtin("hfw").unwrap();
+    assert!(!patches.patches.is_empty());
+    for patch in &patches.patches {
+        assert!(patch.to_patch(0).is_ok(), "{}", patch.name);
+        assert!(
+            signatures
+                .signatures
+                .iter()
+                .any(|signature| signature.name == patch.signature),
+            "{}",
+            patch.name
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use libdecima::mem::cache::OffsetCache;
+use libdecima::mem::image::Image;
+use libdecima::mem::signatures::{Signature, Step};
+use std::fs;
+
+fn signature(name: &str, pattern: &str) -> Signature {
+    Signature {
+        name: name.to_string(),
+        versions: Vec::new(),
+        patterns: vec!["DE AD".to_string(), pattern.to_string()],
+        string: None,
+        steps: vec![Step::Adjust(1)],
+        section: ".text".to_string(),
+    }
+}
+
+#[test]
+fn found_signatures_are_cached_and_reused() {
+    let mut bytes = [0x90u8, 0xAA, 0xBB, 0x90, 0xAA, 0xBB, 0xCC, 0x90];
+    let signatures = [
+        signature("Ambiguous", "AA BB"),
+        signature("Missing", "EE FF"),
+    ];
+
+    let mut cache = OffsetCache::new("executable");
+    {
+        let image = Image::new(&bytes);
+        let checks = cache.check(&signatures, &image);
+        assert_eq!(image.rva(*checks[0].result.as_ref().unwrap()), Some(2));
+        assert!(checks[1].result.is_err());
+    }
+    assert_eq!(cache.entries.len(), 1);
+    assert_eq!(cache.entries[0].name, "Ambiguous");
+    assert_eq!(cache.entries[0].pattern, 1);
+    assert_eq!(cache.entries[0].rva, 1);
+    assert_eq!(cache.entries[0].matches, 2);
+
+    // the second match is just as valid, a rescan would've found the first one instead.
+    cache.entries[0].rva = 4;
+    {
+        let image = Image::new(&bytes);
+        let checks = cache.check(&signatures, &image);
+        assert_eq!(image.rva(*checks[0].result.as_ref().unwrap()), Some(5));
+        assert_eq!(checks[0].matches, 2);
+    }
+
+    // entries whose pattern doesn't match anymore are scanned for again.
+    bytes[4] = 0x90;
+    let image = Image::new(&bytes);
+    let checks = cache.check(&signatures, &image);
+    assert_eq!(image.rva(*checks[0].result.as_ref().unwrap()), Some(2));
+    assert_eq!(checks[0].matches, 1);
+    assert_eq!(cache.entries[0].rva, 1);
+}
+
+#[test]
+fn edited_signatures_are_not_reused() {
+    let bytes = [0x90u8, 0xAA, 0xBB, 0x90, 0xAA, 0xBB, 0xCC];
+    let image = Image::new(&bytes);
+    let mut cache = OffsetCache::new("executable");
+    cache.check(&[signature("Example", "AA BB")], &image);
+    cache.entries[0].rva = 4;
+
+    let edited = signature("Example", "AA BB ?");
+    assert_ne!(
+        edited.fingerprint(),
+        signature("Example", "AA BB").fingerprint()
+    );
+    assert!(cache.get(&edited).is_none());
+    let checks = cache.check(&[edited], &image);
+    assert_eq!(image.rva(*checks[0].result.as_ref().unwrap()), Some(2));
+}
+
+#[test]
+fn caches_are_stored_per_executable() {
+    let dir = std::env::temp_dir().join("libdecima-test-offset-cache");
+    let _ = fs::remove_dir_all(&dir);
+
+    let old = OffsetCache::hash(b"old executable");
+    let new = OffsetCache::hash(b"new executable");
+    assert_ne!(old, new);
+    assert_eq!(old.len(), 64);
+
+    let bytes = [0x90u8, 0xAA, 0xBB];
+    let mut cache = OffsetCache::new(&old);
+    cache.check(&[signature("Example", "AA BB")], &Image::new(&bytes));
+    cache.save(&dir).unwrap();
+    assert_eq!(OffsetCache::load(&dir, &old), cache);
+
+    // after an update nothing is cached, and the old cache is removed when the new one is saved.
+    let updated = OffsetCache::load(&dir, &new);
+    assert_eq!(updated, OffsetCache::new(&new));
+    updated.save(&dir).unwrap();
+    assert!(!dir.join(OffsetCache::file_name(&old)).exists());
+    assert!(dir.join(OffsetCache::file_name(&new)).exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
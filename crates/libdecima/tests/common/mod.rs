@@ -0,0 +1,4 @@
+//! Fixtures shared by the tests, every test binary only uses some of them.
+#![allow(dead_code)]
+
+pub mod pe;
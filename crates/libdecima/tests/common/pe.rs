@@ -0,0 +1,113 @@
+//! A builder for the x64 PE images the tests search.
+
+pub const IMAGE_BASE: u64 = 0x1_4000_0000;
+/// Offset of the NT headers, right after the DOS header.
+pub const NT_HEADERS: usize = 0x80;
+const HEADERS_SIZE: usize = 0x400;
+const FILE_ALIGNMENT: usize = 0x200;
+
+pub fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+struct Section {
+    name: &'static str,
+    rva: usize,
+    size: usize,
+}
+
+/// The headers of an image, see [Pe::mapped] and [Pe::file] for the image itself.
+pub struct Pe {
+    size: usize,
+    sections: Vec<Section>,
+    exception_directory: Option<(usize, usize)>,
+}
+
+impl Pe {
+    /// An image of `size` bytes once mapped, based at [IMAGE_BASE].
+    pub fn new(size: usize) -> Self {
+        Pe {
+            size,
+            sections: Vec::new(),
+            exception_directory: None,
+        }
+    }
+
+    /// Adds a section of `size` bytes at `rva`.
+    pub fn section(mut self, name: &'static str, rva: usize, size: usize) -> Self {
+        self.sections.push(Section { name, rva, size });
+        self
+    }
+
+    /// Points the exception directory at `size` bytes of `.pdata` entries at `rva`.
+    pub fn exception_directory(mut self, rva: usize, size: usize) -> Self {
+        self.exception_directory = Some((rva, size));
+        self
+    }
+
+    /// The image as the loader maps it, with the sections at their RVAs to be filled in.
+    pub fn mapped(&self) -> Vec<u8> {
+        let mut data = vec![0u8; self.size];
+        let raw = self
+            .sections
+            .iter()
+            .map(|section| section.rva)
+            .collect::<Vec<_>>();
+        self.headers(&mut data, &raw);
+        data
+    }
+
+    /// The sections of `mapped` as a file on disk, stored one after the other behind the headers
+    /// rather than at their RVAs.
+    pub fn file(&self, mapped: &[u8]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        let mut end = HEADERS_SIZE;
+        for section in &self.sections {
+            raw.push(end);
+            end = (end + section.size).next_multiple_of(FILE_ALIGNMENT);
+        }
+        let mut file = vec![0u8; end];
+        self.headers(&mut file, &raw);
+        for (section, raw) in self.sections.iter().zip(raw) {
+            put(
+                &mut file,
+                raw,
+                &mapped[section.rva..section.rva + section.size],
+            );
+        }
+        file
+    }
+
+    /// Writes the headers, with the data of each section stored at `raw`.
+    fn headers(&self, data: &mut [u8], raw: &[usize]) {
+        put(data, 0, b"MZ");
+        put(data, 0x3C, &(NT_HEADERS as i32).to_le_bytes());
+
+        let nt = NT_HEADERS;
+        put(data, nt, b"PE\0\0");
+        put(data, nt + 4, &0x8664u16.to_le_bytes());
+        put(data, nt + 6, &(self.sections.len() as u16).to_le_bytes());
+        put(data, nt + 20, &0xF0u16.to_le_bytes());
+        let optional = nt + 24;
+        put(data, optional, &0x20Bu16.to_le_bytes());
+        put(data, optional + 24, &IMAGE_BASE.to_le_bytes());
+        put(data, optional + 56, &(self.size as u32).to_le_bytes());
+        put(data, optional + 60, &(HEADERS_SIZE as u32).to_le_bytes());
+        put(data, optional + 108, &16u32.to_le_bytes());
+        if let Some((rva, size)) = self.exception_directory {
+            let exception = optional + 112 + 3 * 8;
+            put(data, exception, &(rva as u32).to_le_bytes());
+            put(data, exception + 4, &(size as u32).to_le_bytes());
+        }
+
+        let sections = optional + 0xF0;
+        for (index, (section, raw)) in self.sections.iter().zip(raw).enumerate() {
+            let header = sections + index * 0x28;
+            put(data, header, section.name.as_bytes());
+            put(data, header + 8, &(section.size as u32).to_le_bytes());
+            put(data, header + 12, &(section.rva as u32).to_le_bytes());
+            put(data, header + 16, &(section.size as u32).to_le_bytes());
+            put(data, header + 20, &(*raw as u32).to_le_bytes());
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use libdecima::decima_fn;
+use libdecima::mem::PatternSearchError;
+
+decima_fn! {
+    fn missing(value: u32) -> u32 = "DE AD BE EF";
+
+    fn missing_relative(this: *mut u8) = "E8 ? ? ? ? 90" => as_relative(5).as_adjusted(2);
+
+    fn missing_target(this: *mut u8) = "E8 ? ? ? ? 90" => as_next(1).as_adjusted(1).as_target();
+}
+
+#[test]
+fn unresolved_binding_returns_error() {
+    // there is no game module outside of the game, so every scan fails.
+    for value in 0..2 {
+        assert!(matches!(
+            unsafe { missing(value) },
+            Err(PatternSearchError::OutOfRange)
+        ));
+    }
+    assert!(matches!(
+        unsafe { missing_relative(std::ptr::null_mut()) },
+        Err(PatternSearchError::OutOfRange)
+    ));
+    assert!(matches!(
+        unsafe { missing_target(std::ptr::null_mut()) },
+        Err(PatternSearchError::OutOfRange)
+    ));
+}
@@ -0,0 +1,35 @@
+//! In its own test binary, as it sets a fake game module that scans in other tests would read.
+#![cfg(not(windows))]
+
+use libdecima::decima_fn;
+use libdecima::mem::offsets::Offsets;
+use libdecima::platform::StubPlatform;
+
+extern "C" fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+decima_fn! {
+    fn bound_add(a: u32, b: u32) -> u32 = Offsets["Test::Add"];
+}
+
+#[test]
+fn bindings_resolve_once() {
+    // not found until there's a module, and the failure isn't cached.
+    assert!(unsafe { bound_add(1, 2) }.is_err());
+
+    let add = add as extern "C" fn(u32, u32) -> u32 as usize;
+    let module = 0x1000;
+    StubPlatform::set_main_module(Some((module, module + 1)));
+    Offsets::map_address("Test::Add", (add - module) as *const u8);
+    assert_eq!(unsafe { bound_add(1, 2) }.unwrap(), 3);
+
+    // the database now points elsewhere, but the binding keeps the address it found.
+    StubPlatform::set_main_module(Some((module * 2, module * 2 + 1)));
+    assert_ne!(
+        Offsets::find("Test::Add").unwrap().as_ptr::<u8>() as usize,
+        add
+    );
+    assert_eq!(unsafe { bound_add(2, 3) }.unwrap(), 5);
+    StubPlatform::set_main_module(None);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::pe::{Pe, put};
+use libdecima::mem::functions::{Function, FunctionTable};
+use libdecima::mem::image::Image;
+use libdecima::mem::xref::function_start;
+
+const TEXT_RVA: usize = 0x1000;
+const RDATA_RVA: usize = 0x2000;
+const PDATA_RVA: usize = 0x3000;
+
+/// A mapped PE with four `.pdata` entries:
+///
+/// ```text
+/// 1000..1010  a function
+/// 1010..1040  a function, split into
+/// 1080..10A0  a part chained to it through its unwind info
+/// 10A0..10B0  a part of the first function, through an odd unwind info address
+/// ```
+fn pe() -> Vec<u8> {
+    let mut data = Pe::new(0x4000)
+        .section(".text", TEXT_RVA, 0x100)
+        .section(".rdata", RDATA_RVA, 0x100)
+        .section(".pdata", PDATA_RVA, 0x100)
+        .exception_directory(PDATA_RVA, 4 * 12)
+        .mapped();
+
+    // version 1, no flags, one unwind code.
+    put(
+        &mut data,
+        RDATA_RVA,
+        &[0x01, 0x04, 0x01, 0x00, 0x04, 0x42, 0x00, 0x00],
+    );
+    // version 1, UNW_FLAG_CHAININFO, one unwind code padded to two, then the entry chained to.
+    put(
+        &mut data,
+        RDATA_RVA + 0x10,
+        &[0x21, 0x00, 0x01, 0x00, 0x04, 0x42, 0x00, 0x00],
+    );
+    put(&mut data, RDATA_RVA + 0x18, &0x1010u32.to_le_bytes());
+    put(&mut data, RDATA_RVA + 0x1C, &0x1040u32.to_le_bytes());
+    put(
+        &mut data,
+        RDATA_RVA + 0x20,
+        &(RDATA_RVA as u32).to_le_bytes(),
+    );
+
+    for (index, (begin, end, unwind_info)) in [
+        (0x1000, 0x1010, RDATA_RVA),
+        (0x1010, 0x1040, RDATA_RVA),
+        (0x1080, 0x10A0, RDATA_RVA + 0x10),
+        (0x10A0, 0x10B0, PDATA_RVA | 1),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let entry = PDATA_RVA + index * 12;
+        put(&mut data, entry, &(begin as u32).to_le_bytes());
+        put(&mut data, entry + 4, &(end as u32).to_le_bytes());
+        put(&mut data, entry + 8, &(unwind_info as u32).to_le_bytes());
+    }
+    data
+}
+
+fn function(start: usize, end: usize, unwind_info: usize) -> Function {
+    Function {
+        start,
+        end,
+        unwind_info,
+    }
+}
+
+#[test]
+fn entries_are_enumerated() {
+    let data = pe();
+    let image = Image::parse(&data).unwrap();
+    let table = FunctionTable::new(&image);
+
+    assert_eq!(table.len(), 4);
+    assert_eq!(
+        table.entries().map(|entry| entry.start).collect::<Vec<_>>(),
+        [0x1000, 0x1010, 0x1080, 0x10A0]
+    );
+    // the chained parts aren't functions of their own.
+    assert_eq!(
+        table.functions().collect::<Vec<_>>(),
+        [
+            function(0x1000, 0x1010, RDATA_RVA),
+            function(0x1010, 0x1040, RDATA_RVA)
+        ]
+    );
+    assert_eq!(table.get(1).unwrap().len(), 0x30);
+    assert!(table.get(4).is_none());
+
+    // plain buffers have no exception directory.
+    let buffer = [0xC3; 0x20];
+    let table = FunctionTable::new(&Image::new(&buffer));
+    assert!(table.is_empty());
+    assert!(table.function_containing(0x10).is_none());
+}
+
+#[test]
+fn functions_are_looked_up_by_address() {
+    let data = pe();
+    let image = Image::parse(&data).unwrap();
+    let table = FunctionTable::new(&image);
+    let start = |rva| table.entry_containing(rva).map(|entry| entry.start);
+
+    assert_eq!(start(0x1000), Some(0x1000));
+    assert_eq!(start(0x100F), Some(0x1000));
+    assert_eq!(start(0x1010), Some(0x1010));
+    assert_eq!(start(0x1090), Some(0x1080));
+    assert_eq!(start(0x10AF), Some(0x10A0));
+    // before the first, between and after the last entry.
+    assert_eq!(start(0x0FFF), None);
+    assert_eq!(start(0x1040), None);
+    assert_eq!(start(0x107F), None);
+    assert_eq!(start(0x10B0), None);
+
+    assert_eq!(
+        table.function_containing(0x1090),
+        Some(function(0x1010, 0x1040, RDATA_RVA))
+    );
+    assert_eq!(
+        table.function_containing(0x10A4),
+        Some(function(0x1000, 0x1010, RDATA_RVA))
+    );
+    assert!(table.is_function_start(0x1010));
+    assert!(!table.is_function_start(0x1011));
+    assert!(!table.is_function_start(0x1080));
+
+    assert_eq!(function_start(&image, ".text", 0x1090).unwrap(), 0x1010);
+}
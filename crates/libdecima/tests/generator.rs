@@ -0,0 +1,97 @@
+use libdecima::mem::generator::{GenerateError, generate_signature, instruction_pattern};
+use libdecima::mem::image::Image;
+use libdecima::mem::parse_pattern;
+use libdecima::mem::pattern::format_pattern;
+use libdecima::mem::scanner::Scanner;
+
+/// mov [rsp+8], rbx; mov rax, [rip+X]; call X; add rsp, 0x28, then something else in each copy.
+fn function(rip: u32, call: u32, tail: &[u8]) -> Vec<u8> {
+    let mut code = vec![0x48, 0x89, 0x5C, 0x24, 0x08, 0x48, 0x8B, 0x05];
+    code.extend(rip.to_le_bytes());
+    code.push(0xE8);
+    code.extend(call.to_le_bytes());
+    code.extend([0x48, 0x83, 0xC4, 0x28]);
+    code.extend(tail);
+    code
+}
+
+fn code(functions: &[Vec<u8>]) -> (Vec<u8>, Vec<usize>) {
+    let mut code = vec![0xCC; 0x10];
+    let mut starts = Vec::new();
+    for function in functions {
+        starts.push(code.len());
+        code.extend(function);
+        code.extend([0xCC; 0x0B]);
+    }
+    (code, starts)
+}
+
+#[test]
+fn relocatable_operands_are_wildcarded() {
+    // jmp rel8 stays, jmp rel32 and lea rcx, [rip+X] don't.
+    let code = [
+        0xEB, 0x02, 0xE9, 0x10, 0x00, 0x00, 0x00, 0x48, 0x8D, 0x0D, 0x20, 0x00, 0x00, 0x00, 0xC3,
+    ];
+    let pattern = instruction_pattern(&code, 0x1000, 0, 64).unwrap();
+    assert_eq!(
+        format_pattern(&pattern),
+        "EB 02 E9 ? ? ? ? 48 8D 0D ? ? ? ? C3"
+    );
+}
+
+#[test]
+fn shortest_unique_prefix_is_generated() {
+    let (code, starts) = code(&[
+        function(0x1111, 0x2222, &[0x33, 0xC0, 0xC3]),
+        function(0x3333, 0x4444, &[0xB0, 0x01, 0xC3]),
+    ]);
+    let image = Image::new(&code);
+
+    let first = generate_signature(&image, starts[0]).unwrap();
+    assert_eq!(
+        first,
+        "48 89 5C 24 08 48 8B 05 ? ? ? ? E8 ? ? ? ? 48 83 C4 28 33"
+    );
+    let second = generate_signature(&image, starts[1]).unwrap();
+    assert_eq!(
+        second,
+        "48 89 5C 24 08 48 8B 05 ? ? ? ? E8 ? ? ? ? 48 83 C4 28 B0"
+    );
+
+    for (signature, start) in [(first, starts[0]), (second, starts[1])] {
+        let found = Scanner::new(&parse_pattern(&signature).unwrap())
+            .find_iter(&code)
+            .collect::<Vec<_>>();
+        assert_eq!(found, [start]);
+    }
+
+    // a short prefix is enough when the code doesn't repeat.
+    assert_eq!(
+        generate_signature(&image, starts[0] + 17).unwrap(),
+        "48 83 C4 28 33"
+    );
+}
+
+#[test]
+fn failures_are_reported() {
+    let (code, starts) = code(&[
+        function(0x1111, 0x2222, &[0xC3]),
+        function(0x3333, 0x4444, &[0xC3]),
+    ]);
+    let image = Image::new(&code);
+    assert!(matches!(
+        generate_signature(&image, starts[0]),
+        Err(GenerateError::NotUnique { matches: 2 })
+    ));
+    assert_eq!(
+        generate_signature(&image, code.len()),
+        Err(GenerateError::OutOfRange)
+    );
+
+    // 0x06 (push es) doesn't exist in 64-bit code.
+    let invalid = [0x90, 0x06, 0x90];
+    assert_eq!(
+        generate_signature(&Image::new(&invalid), 1),
+        Err(GenerateError::InvalidInstruction { rva: 1 })
+    );
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::pe::{IMAGE_BASE, NT_HEADERS, Pe, put};
+use libdecima::mem::PatternSearchError;
+use libdecima::mem::image::Image;
+use libdecima::mem::signatures::{Signature, Step, check_all_image};
+use libdecima::pattern;
+
+const TEXT_RVA: usize = 0x1000;
+const DATA_RVA: usize = 0x2000;
+
+/// A PE file as it'd be on disk, with a `.text` and a `.data` section stored at different
+/// offsets than they're loaded at.
+///
+/// `.text` has `lea rcx, [rip + x]` pointing at the start of `.data`, which holds a pointer
+/// relative to the preferred image base back to the instruction.
+fn pe_file() -> Vec<u8> {
+    let pe = Pe::new(0x3000)
+        .section(".text", TEXT_RVA, 0x100)
+        .section(".data", DATA_RVA, 0x100);
+    let mut mapped = pe.mapped();
+
+    // lea rcx, [rip + (DATA_RVA - (TEXT_RVA + 0x17))] at .text+0x10
+    let displacement = (DATA_RVA - (TEXT_RVA + 0x17)) as u32;
+    put(&mut mapped, TEXT_RVA + 0x10, &[0x48, 0x8D, 0x0D]);
+    put(&mut mapped, TEXT_RVA + 0x13, &displacement.to_le_bytes());
+    put(
+        &mut mapped,
+        DATA_RVA,
+        &(IMAGE_BASE + TEXT_RVA as u64 + 0x10).to_le_bytes(),
+    );
+    pe.file(&mapped)
+}
+
+#[test]
+fn files_are_mapped_at_their_rvas() {
+    let mapped = Image::map(&pe_file()).unwrap();
+    let image = Image::parse(&mapped).unwrap();
+    assert_eq!(image.bytes().len(), 0x3000);
+    assert_eq!(
+        image
+            .sections()
+            .iter()
+            .map(|section| section.name())
+            .collect::<Vec<_>>(),
+        [".text", ".data"]
+    );
+
+    let text = image.section(".text").unwrap();
+    assert_eq!(text.len(), 0x100);
+    assert_eq!(text.as_ptr() as usize - image.base(), TEXT_RVA);
+    assert!(matches!(
+        image.section(".rdata"),
+        Err(PatternSearchError::OutOfRange)
+    ));
+
+    let found = image.find(".text", pattern!("48 8D 0D")).unwrap();
+    assert_eq!(image.rva(found), Some(TEXT_RVA + 0x10));
+    // the pattern isn't in .data.
+    assert!(matches!(
+        image.find(".data", pattern!("48 8D 0D")),
+        Err(PatternSearchError::NotFound)
+    ));
+}
+
+#[test]
+fn signatures_resolve_against_images() {
+    let mapped = Image::map(&pe_file()).unwrap();
+    let image = Image::parse(&mapped).unwrap();
+    let signature = |steps| Signature {
+        name: "Example".to_string(),
+        versions: Vec::new(),
+        patterns: vec!["48 8D 0D ? ? ? ?".to_string()],
+        string: None,
+        steps,
+        section: ".text".to_string(),
+    };
+
+    let data = signature(vec![Step::RipRelative(7)]);
+    assert_eq!(
+        image.rva(data.resolve_image(&image).unwrap()),
+        Some(DATA_RVA)
+    );
+
+    // the pointer in .data is relative to the preferred base and is moved into the mapping.
+    let back = signature(vec![Step::RipRelative(7), Step::Deref]);
+    assert_eq!(
+        image.rva(back.resolve_image(&image).unwrap()),
+        Some(TEXT_RVA + 0x10)
+    );
+
+    let outside = signature(vec![Step::Offset(0x10000), Step::Deref]);
+    assert!(matches!(
+        outside.resolve_image(&image),
+        Err(PatternSearchError::OutOfRange)
+    ));
+
+    let checks = check_all_image(&[data, back, outside], &image);
+    assert_eq!(checks.len(), 3);
+    assert_eq!(
+        image.rva(*checks[0].result.as_ref().unwrap()),
+        Some(DATA_RVA)
+    );
+    assert_eq!(checks[1].matches, 1);
+    assert!(checks[2].result.is_err());
+}
+
+#[test]
+fn invalid_images_are_rejected() {
+    assert!(matches!(
+        Image::parse(&[0u8; 0x10]),
+        Err(PatternSearchError::InvalidImage(_))
+    ));
+    let mut file = pe_file();
+    put(&mut file, NT_HEADERS, b"NE\0\0");
+    assert!(matches!(
+        Image::map(&file),
+        Err(PatternSearchError::InvalidImage(_))
+    ));
+    // plain buffers have no sections and are searched whole.
+    let bytes = [0x90, 0x48, 0x8D, 0x0D];
+    let image = Image::new(&bytes);
+    assert_eq!(
+        image.rva(image.find(".text", pattern!("48 8D")).unwrap()),
+        Some(1)
+    );
+}
@@ -0,0 +1,116 @@
+use libdecima::mem::PatternSearchError;
+use libdecima::mem::image::Image;
+use libdecima::mem::offsets::Offset;
+use libdecima::mem::signatures::{SignatureDatabase, Step};
+
+/// Hand-assembled, offsets on the left:
+///
+/// ```text
+/// 00: call 0x20
+/// 05: cmp byte ptr [rip+0x24], 5   ; 0x30, the displacement isn't the last 4 bytes
+/// 0C: nop ...
+/// 20: jmp 0x10                     ; backward rel32, a thunk
+/// 25: lea rcx, [rip-0x2C]          ; 0x00, backward displacement
+/// 2C: jmp 0x20                     ; rel8
+/// 2E: nop ...
+/// 3F: (bad)
+/// ```
+fn code() -> Vec<u8> {
+    let mut code = vec![0x90; 0x60];
+    code[0x00..0x05].copy_from_slice(&[0xE8, 0x1B, 0x00, 0x00, 0x00]);
+    code[0x05..0x0C].copy_from_slice(&[0x80, 0x3D, 0x24, 0x00, 0x00, 0x00, 0x05]);
+    code[0x20..0x25].copy_from_slice(&[0xE9, 0xEB, 0xFF, 0xFF, 0xFF]);
+    code[0x25..0x2C].copy_from_slice(&[0x48, 0x8D, 0x0D, 0xD4, 0xFF, 0xFF, 0xFF]);
+    code[0x2C..0x2E].copy_from_slice(&[0xEB, 0xF2]);
+    code[0x3F] = 0x06;
+    code
+}
+
+#[test]
+fn steps_follow_any_instruction() {
+    let code = code();
+    let image = Image::new(&code[..0x40]);
+    let apply = |start: usize, steps: &[Step]| {
+        steps
+            .iter()
+            .try_fold(image.at_rva(start), |offset, step| {
+                step.apply_in(&image, ".text", offset)
+            })
+            .map(|offset| image.rva(offset).unwrap())
+    };
+
+    assert_eq!(apply(0x00, &[Step::Follow]).unwrap(), 0x20);
+    // through the thunk to the function.
+    assert_eq!(apply(0x00, &[Step::Follow, Step::Follow]).unwrap(), 0x10);
+    assert_eq!(apply(0x00, &[Step::Skip(1)]).unwrap(), 0x05);
+    assert_eq!(apply(0x00, &[Step::Skip(1), Step::Follow]).unwrap(), 0x30);
+    assert_eq!(apply(0x25, &[Step::Follow]).unwrap(), 0x00);
+    assert_eq!(apply(0x2C, &[Step::Follow]).unwrap(), 0x20);
+    assert_eq!(apply(0x25, &[Step::RipRelative(7)]).unwrap(), 0x00);
+
+    assert!(matches!(
+        apply(0x0C, &[Step::Follow]),
+        Err(PatternSearchError::NoTarget(address)) if address == image.base() + 0x0C
+    ));
+    assert!(matches!(
+        apply(0x3F, &[Step::Follow]),
+        Err(PatternSearchError::InvalidInstruction(address)) if address == image.base() + 0x3F
+    ));
+    assert!(matches!(
+        apply(0x3C, &[Step::Skip(4)]),
+        Err(PatternSearchError::InvalidInstruction(_))
+    ));
+}
+
+#[test]
+fn offsets_follow_instructions_in_memory() {
+    let code = code();
+    let at = |position: usize| Offset::new(code.as_ptr() as usize + position);
+
+    assert_eq!(at(0x00).as_target().unwrap(), at(0x20));
+    assert_eq!(at(0x00).as_next(1).unwrap().as_target().unwrap(), at(0x30));
+    assert_eq!(at(0x20).as_target().unwrap(), at(0x10));
+    // negative displacements work without relying on wraparound.
+    assert_eq!(at(0x25).as_target().unwrap(), at(0x00));
+    assert_eq!(at(0x25).as_relative(7), at(0x00));
+    // the displacement of the cmp isn't at the end, so as_relative gets it wrong.
+    assert_ne!(at(0x05).as_relative(7), at(0x30));
+    assert_eq!(at(0x25).instruction().unwrap().len(), 7);
+
+    for step in [Step::Follow, Step::Skip(2)] {
+        let image = Image::new(&code);
+        assert_eq!(
+            step.apply(at(0x00)).unwrap(),
+            step.apply_in(&image, ".text", at(0x00)).unwrap()
+        );
+    }
+}
+
+#[test]
+fn databases_use_instruction_steps() {
+    let database = SignatureDatabase::parse(
+        r#"
+        game = "hfw"
+
+        [[signature]]
+        name = "Function"
+        patterns = ["E8 ? ? ? ? 80 3D"]
+        steps = ["follow", "follow"]
+
+        [[signature]]
+        name = "Flag"
+        patterns = ["E8 ? ? ? ? 80 3D"]
+        steps = [{ skip = 1 }, "follow"]
+        "#,
+    )
+    .unwrap();
+    let code = code();
+    let image = Image::new(&code[..0x40]);
+
+    let resolved = database
+        .signatures
+        .iter()
+        .map(|signature| image.rva(signature.resolve_image(&image).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(resolved, [Some(0x10), Some(0x30)]);
+}
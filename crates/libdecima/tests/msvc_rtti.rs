@@ -0,0 +1,222 @@
+mod common;
+
+use common::pe::{IMAGE_BASE, Pe, put};
+use libdecima::mem::image::Image;
+use libdecima::mem::msvc_rtti::{BaseClass, ClassTable, undecorate};
+
+const RDATA_RVA: usize = 0x1000;
+const DATA_RVA: usize = 0x2000;
+const SECTION_SIZE: usize = 0x400;
+
+fn pe() -> Pe {
+    Pe::new(0x3000)
+        .section(".rdata", RDATA_RVA, SECTION_SIZE)
+        .section(".data", DATA_RVA, SECTION_SIZE)
+}
+
+/// Lays out RTTI records the way MSVC does: type descriptors in `.data`, everything else in
+/// `.rdata`, all in a buffer at their RVAs.
+struct Records {
+    mapped: Vec<u8>,
+    rdata: usize,
+    data: usize,
+}
+
+impl Records {
+    fn new() -> Self {
+        Records {
+            mapped: pe().mapped(),
+            rdata: RDATA_RVA,
+            data: DATA_RVA,
+        }
+    }
+
+    fn rdata(&mut self, bytes: &[u8]) -> usize {
+        let rva = self.rdata;
+        put(&mut self.mapped, rva, bytes);
+        self.rdata = (rva + bytes.len()).next_multiple_of(8);
+        rva
+    }
+
+    fn type_descriptor(&mut self, name: &str) -> usize {
+        let rva = self.data;
+        put(&mut self.mapped, rva + 0x10, name.as_bytes());
+        self.data = (rva + 0x10 + name.len() + 1).next_multiple_of(8);
+        rva
+    }
+
+    /// A hierarchy of `(type descriptor, contained bases, offset)`, starting with the class.
+    fn hierarchy(&mut self, classes: &[(usize, u32, i32)]) -> usize {
+        let descriptors = classes
+            .iter()
+            .map(|(type_descriptor, contained, offset)| {
+                let mut descriptor = Vec::new();
+                descriptor.extend((*type_descriptor as u32).to_le_bytes());
+                descriptor.extend(contained.to_le_bytes());
+                descriptor.extend(offset.to_le_bytes());
+                descriptor.extend([0xFF; 4]);
+                descriptor.extend([0; 12]);
+                self.rdata(&descriptor) as u32
+            })
+            .collect::<Vec<_>>();
+        let array = self.rdata(
+            &descriptors
+                .iter()
+                .flat_map(|descriptor| descriptor.to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        let mut hierarchy = vec![0; 8];
+        hierarchy.extend((classes.len() as u32).to_le_bytes());
+        hierarchy.extend((array as u32).to_le_bytes());
+        self.rdata(&hierarchy)
+    }
+
+    fn locator(&mut self, type_descriptor: usize, hierarchy: usize, offset: u32) -> usize {
+        let rva = self.rdata;
+        let mut locator = Vec::new();
+        for field in [
+            1,
+            offset,
+            0,
+            type_descriptor as u32,
+            hierarchy as u32,
+            rva as u32,
+        ] {
+            locator.extend(field.to_le_bytes());
+        }
+        self.rdata(&locator)
+    }
+
+    /// A vtable of two functions using `locator`, returning its RVA.
+    fn vtable(&mut self, locator: usize) -> usize {
+        let mut vtable = Vec::new();
+        for pointer in [locator, 0x3000, 0x3010] {
+            vtable.extend((IMAGE_BASE + pointer as u64).to_le_bytes());
+        }
+        self.rdata(&vtable) + 8
+    }
+
+    /// The records as a PE file on disk, with sections stored at other offsets than their RVAs.
+    fn file(&self) -> Vec<u8> {
+        pe().file(&self.mapped)
+    }
+}
+
+/// `Entity`, `Player : Entity`, `nx::NxD3DImpl : Base, Other`, a struct, a template and a locator
+/// no vtable uses, with the RVAs of the vtables.
+fn records() -> (Records, Vec<usize>) {
+    let mut records = Records::new();
+    let entity = records.type_descriptor(".?AVEntity@@");
+    let player = records.type_descriptor(".?AVPlayer@@");
+    let d3d = records.type_descriptor(".?AVNxD3DImpl@nx@@");
+    let base = records.type_descriptor(".?AVBase@@");
+    let other = records.type_descriptor(".?AVOther@@");
+    let listener = records.type_descriptor(".?AUListener@@");
+    let array = records.type_descriptor(".?AV?$Array@H@@");
+    let unused = records.type_descriptor(".?AVUnused@@");
+
+    let mut vtables = Vec::new();
+    let hierarchy = records.hierarchy(&[(entity, 0, 0)]);
+    let locator = records.locator(entity, hierarchy, 0);
+    vtables.push(records.vtable(locator));
+
+    let hierarchy = records.hierarchy(&[(player, 1, 0), (entity, 0, 0)]);
+    let locator = records.locator(player, hierarchy, 0);
+    vtables.push(records.vtable(locator));
+
+    let hierarchy = records.hierarchy(&[(d3d, 2, 0), (base, 0, 0), (other, 0, 8)]);
+    // the vtable of the second base comes first.
+    let second = records.locator(d3d, hierarchy, 8);
+    vtables.push(records.vtable(second));
+    let first = records.locator(d3d, hierarchy, 0);
+    vtables.push(records.vtable(first));
+
+    for type_descriptor in [listener, array] {
+        let hierarchy = records.hierarchy(&[(type_descriptor, 0, 0)]);
+        let locator = records.locator(type_descriptor, hierarchy, 0);
+        vtables.push(records.vtable(locator));
+    }
+
+    let hierarchy = records.hierarchy(&[(unused, 0, 0)]);
+    records.locator(unused, hierarchy, 0);
+    (records, vtables)
+}
+
+#[test]
+fn classes_are_found_in_files() {
+    let (records, vtables) = records();
+    let mapped = Image::map(&records.file()).unwrap();
+    let image = Image::parse(&mapped).unwrap();
+    let table = ClassTable::parse(&image).unwrap();
+
+    assert_eq!(
+        table
+            .classes()
+            .iter()
+            .map(|class| class.name.as_str())
+            .collect::<Vec<_>>(),
+        [
+            ".?AV?$Array@H@@",
+            "Entity",
+            "Listener",
+            "Player",
+            "nx::NxD3DImpl"
+        ]
+    );
+    assert_eq!(table.vtable("Entity"), Some(vtables[0]));
+    assert_eq!(table.vtable(".?AVPlayer@@"), Some(vtables[1]));
+    assert_eq!(table.vtable("Listener"), Some(vtables[4]));
+    assert_eq!(table.vtable("Unused"), None);
+
+    let d3d = table.get("nx::NxD3DImpl").unwrap();
+    assert_eq!(d3d.vtable(), Some(vtables[3]));
+    assert_eq!(
+        d3d.vtables
+            .iter()
+            .map(|vtable| (vtable.offset, vtable.rva))
+            .collect::<Vec<_>>(),
+        [(0, vtables[3]), (8, vtables[2])]
+    );
+    // the vtable pointers are relative to the preferred base, the vtables themselves aren't.
+    assert_eq!(
+        image
+            .read::<u64>(image.at_rva(d3d.vtables[0].rva - 8))
+            .unwrap(),
+        IMAGE_BASE + d3d.vtables[0].locator as u64
+    );
+}
+
+#[test]
+fn base_classes_are_listed() {
+    let (records, _) = records();
+    let mapped = Image::map(&records.file()).unwrap();
+    let image = Image::parse(&mapped).unwrap();
+    let table = ClassTable::parse(&image).unwrap();
+
+    let base = |name: &str, offset, contained_bases| BaseClass {
+        name: name.to_string(),
+        offset,
+        contained_bases,
+    };
+    assert_eq!(table.get("Player").unwrap().bases, [base("Entity", 0, 0)]);
+    assert!(table.get("Entity").unwrap().bases.is_empty());
+    let d3d = table.get("nx::NxD3DImpl").unwrap();
+    assert_eq!(d3d.bases, [base("Base", 0, 0), base("Other", 8, 0)]);
+    assert_eq!(d3d.direct_bases().len(), 2);
+    assert_eq!(
+        table
+            .derived("Entity")
+            .map(|class| class.name.as_str())
+            .collect::<Vec<_>>(),
+        ["Player"]
+    );
+}
+
+#[test]
+fn names_are_undecorated() {
+    assert_eq!(undecorate(".?AVPlayer@@"), "Player");
+    assert_eq!(undecorate(".?AUListener@@"), "Listener");
+    assert_eq!(undecorate(".?AVNxD3DImpl@nx@@"), "nx::NxD3DImpl");
+    assert_eq!(undecorate(".?AV?$Array@H@@"), ".?AV?$Array@H@@");
+    assert_eq!(undecorate("Player"), "Player");
+}
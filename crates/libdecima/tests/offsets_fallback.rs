@@ -0,0 +1,53 @@
+//! In its own test binary, as nothing may load a database before it and it sets a fake game
+//! module that scans in other tests would read.
+#![cfg(not(windows))]
+
+mod common;
+
+use common::pe::{Pe, put};
+use libdecima::mem::PatternSearchError;
+use libdecima::mem::offsets::Offsets;
+use libdecima::platform::StubPlatform;
+
+#[test]
+fn builtin_signatures_resolve_without_loading() {
+    // `movsxd rax, [rip + 0x20]; mov r15, [r14]` at 0x1010.
+    let mut module = Pe::new(0x2000).section(".text", 0x1000, 0x100).mapped();
+    put(
+        &mut module,
+        0x1010,
+        &[0x48, 0x63, 0x05, 0x20, 0x00, 0x00, 0x00, 0x4D, 0x8B, 0x3E],
+    );
+    let start = module.as_ptr() as usize;
+    StubPlatform::set_main_module(Some((start, start + module.len())));
+    Offsets::map_address("Test::Mapped", 0x8 as *const u8);
+
+    assert!(!Offsets::is_loaded());
+    let instance = Offsets::find("ExportedSymbols::Instance").unwrap();
+    assert_eq!(instance.as_ptr::<u8>() as usize, start + 0x1017 + 0x20);
+    assert!(Offsets::is_loaded());
+    // what was mapped before stays.
+    assert_eq!(
+        Offsets::find("Test::Mapped").unwrap().as_ptr::<u8>() as usize,
+        start + 0x8
+    );
+    assert!(matches!(
+        Offsets::find("Test::Unknown"),
+        Err(PatternSearchError::UnknownSignature(_))
+    ));
+    assert!(matches!(
+        Offsets::find("String::String"),
+        Err(PatternSearchError::NotFound)
+    ));
+
+    Offsets::map_pattern("Test::Pattern", "4D 8B 3E").unwrap();
+    assert_eq!(
+        Offsets::find("Test::Pattern").unwrap().as_ptr::<u8>() as usize,
+        start + 0x1017
+    );
+    assert!(matches!(
+        Offsets::map_pattern("Test::Missing", "CC CC CC CC"),
+        Err(PatternSearchError::NotFound)
+    ));
+    StubPlatform::set_main_module(None);
+}
@@ -0,0 +1,137 @@
+use libdecima::mem::patches::{Patch, PatchError, PatchRegistry};
+
+fn patch(name: &str, owner: &str, address: usize, bytes: &[u8]) -> Patch {
+    Patch {
+        name: name.to_string(),
+        owner: owner.to_string(),
+        address,
+        bytes: bytes.to_vec(),
+        expected: None,
+    }
+}
+
+/// `mov [rsp+8], rbx; sub rsp, 0x20; ...` followed by padding.
+fn memory() -> Vec<u8> {
+    let mut memory = vec![0x48, 0x89, 0x5C, 0x24, 0x08, 0x48, 0x83, 0xEC, 0x20];
+    memory.resize(0x20, 0xCC);
+    memory
+}
+
+#[test]
+fn patches_are_reversible() {
+    let mut registry = PatchRegistry::new(memory());
+    unsafe { registry.apply(patch("Ret", "cauldron", 0, &[0xC3])) }.unwrap();
+    assert_eq!(registry.memory()[0], 0xC3);
+    let entry = registry.get("Ret").unwrap();
+    assert!(entry.enabled);
+    assert_eq!(entry.original, [0x48]);
+
+    registry.disable("Ret").unwrap();
+    assert_eq!(*registry.memory(), memory());
+    // disabling twice does nothing.
+    registry.disable("Ret").unwrap();
+    unsafe { registry.enable("Ret") }.unwrap();
+    assert_eq!(registry.memory()[0], 0xC3);
+
+    registry.revert("Ret").unwrap();
+    assert_eq!(*registry.memory(), memory());
+    assert!(registry.entries().is_empty());
+    assert_eq!(
+        registry.revert("Ret"),
+        Err(PatchError::Unknown("Ret".to_string()))
+    );
+}
+
+#[test]
+fn expected_bytes_are_verified() {
+    let mut registry = PatchRegistry::new(memory());
+    let checked = Patch {
+        expected: Some(vec![0x48, 0x83, 0xEC, 0x20]),
+        ..patch("Stack", "plugin", 5, &[0x90; 4])
+    };
+    unsafe { registry.apply(checked.clone()) }.unwrap();
+    registry.revert("Stack").unwrap();
+
+    let wrong = Patch {
+        expected: Some(vec![0x48, 0x83, 0xEC, 0x28]),
+        ..checked
+    };
+    let error = unsafe { registry.apply(wrong) }.unwrap_err();
+    assert!(
+        matches!(&error, PatchError::UnexpectedBytes { found, .. } if found == &[0x48, 0x83, 0xEC, 0x20])
+    );
+    // nothing is written, and the failure stays around for the status report.
+    assert_eq!(*registry.memory(), memory());
+    let entry = registry.get("Stack").unwrap();
+    assert!(!entry.enabled);
+    assert_eq!(entry.error, Some(error));
+
+    assert!(matches!(
+        unsafe { registry.apply(patch("Outside", "plugin", 0x1F, &[0x90, 0x90])) },
+        Err(PatchError::Unreadable { address: 0x1F, .. })
+    ));
+    // garbage addresses wrapping around the address space are never registered.
+    assert_eq!(
+        registry.register(patch("Wrapped", "plugin", usize::MAX, &[0x90, 0x90])),
+        Err(PatchError::OutOfRange {
+            name: "Wrapped".to_string(),
+            address: usize::MAX,
+        })
+    );
+    assert!(registry.get("Wrapped").is_none());
+}
+
+#[test]
+fn overlapping_patches_are_refused() {
+    let mut registry = PatchRegistry::new(memory());
+    unsafe { registry.apply(patch("Ret", "first", 0, &[0xC3, 0x90])) }.unwrap();
+
+    assert_eq!(
+        registry.register(patch("Nop", "second", 1, &[0x90])),
+        Err(PatchError::Conflict {
+            name: "Nop".to_string(),
+            other: "Ret".to_string(),
+            owner: "first".to_string(),
+        })
+    );
+    assert!(registry.get("Nop").is_none());
+    assert_eq!(
+        registry.register(patch("Ret", "first", 0x10, &[0x90])),
+        Err(PatchError::AlreadyRegistered("Ret".to_string()))
+    );
+
+    // an owner can have alternatives for the same place, but only one enabled at a time.
+    registry
+        .register(patch("RetTrue", "first", 0, &[0xB0, 0x01, 0xC3]))
+        .unwrap();
+    assert!(matches!(
+        unsafe { registry.enable("RetTrue") },
+        Err(PatchError::Conflict { .. })
+    ));
+    registry.disable("Ret").unwrap();
+    unsafe { registry.enable("RetTrue") }.unwrap();
+    assert_eq!(registry.memory()[..3], [0xB0, 0x01, 0xC3]);
+    assert!(registry.get("RetTrue").unwrap().error.is_none());
+    unsafe { registry.apply(patch("Other", "second", 0x10, &[0x90])) }.unwrap();
+
+    registry.disable("RetTrue").unwrap();
+    unsafe { registry.enable("Ret") }.unwrap();
+    assert!(registry.revert_owner("second").is_empty());
+    assert_eq!(registry.entries().len(), 2);
+    assert!(registry.revert_all().is_empty());
+    assert_eq!(*registry.memory(), memory());
+}
+
+#[test]
+fn changed_patches_are_not_reverted() {
+    let mut registry = PatchRegistry::new(memory());
+    unsafe { registry.apply(patch("Ret", "cauldron", 0, &[0xC3])) }.unwrap();
+    // overwritten behind the registry's back, restoring the original would undo that.
+    registry.memory_mut()[0] = 0xCC;
+    assert!(matches!(
+        registry.disable("Ret"),
+        Err(PatchError::UnexpectedBytes { found, .. }) if found == [0xCC]
+    ));
+    assert!(registry.get("Ret").unwrap().enabled);
+    assert_eq!(registry.revert_all().len(), 1);
+}
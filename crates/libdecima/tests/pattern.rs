@@ -0,0 +1,98 @@
+use libdecima::mem::pattern::{PatternByte, format_pattern, pattern_len};
+use libdecima::mem::{PatternSearchError, find_pattern, find_pattern_bytes, parse_pattern};
+use libdecima::pattern;
+
+#[test]
+fn wildcards_are_single_bytes() {
+    assert_eq!(
+        parse_pattern("48 ? ?? 8B").unwrap(),
+        [
+            PatternByte::exact(0x48),
+            PatternByte::ANY,
+            PatternByte::ANY,
+            PatternByte::exact(0x8B),
+        ]
+    );
+}
+
+#[test]
+fn nibble_wildcards_match_one_half() {
+    let pattern = parse_pattern("4? ?b").unwrap();
+    assert_eq!(
+        pattern,
+        [
+            PatternByte {
+                value: 0x40,
+                mask: 0xF0
+            },
+            PatternByte {
+                value: 0x0B,
+                mask: 0x0F
+            },
+        ]
+    );
+    assert!(pattern[0].matches(0x48) && pattern[0].matches(0x4F));
+    assert!(!pattern[0].matches(0x58));
+    assert!(pattern[1].matches(0xFB) && !pattern[1].matches(0xFC));
+}
+
+#[test]
+fn whitespace_is_flexible() {
+    assert_eq!(
+        parse_pattern("  48\t8B\n?  ").unwrap(),
+        parse_pattern("48 8B ?").unwrap()
+    );
+}
+
+#[test]
+fn errors_have_positions() {
+    assert!(matches!(
+        parse_pattern("48 8G"),
+        Err(PatternSearchError::ParseInt { position: 4, .. })
+    ));
+    assert!(matches!(
+        parse_pattern("48 +1"),
+        Err(PatternSearchError::Malformed { position: 3, .. })
+    ));
+    // odd-length input used to slice out of bounds.
+    assert!(matches!(
+        parse_pattern("48 8"),
+        Err(PatternSearchError::Malformed { position: 3, .. })
+    ));
+    assert!(matches!(
+        parse_pattern("488B"),
+        Err(PatternSearchError::Malformed { position: 0, .. })
+    ));
+    assert!(matches!(
+        parse_pattern("48 ???"),
+        Err(PatternSearchError::Malformed { position: 3, .. })
+    ));
+    assert!(matches!(
+        parse_pattern("  "),
+        Err(PatternSearchError::Malformed { position: 0, .. })
+    ));
+    assert_eq!(pattern_len("48 8G").unwrap_err().position(), 4);
+}
+
+#[test]
+fn compiled_patterns_match_parsed_ones() {
+    const PATTERN: &[PatternByte] = pattern!("48 8B 0D ? ? ? ? 4? ?C");
+    assert_eq!(PATTERN, parse_pattern("48 8B 0D ? ? ? ? 4? ?C").unwrap());
+}
+
+#[test]
+fn nibble_wildcards_are_searched() {
+    let bytes = [0x90u8, 0x48, 0x8B, 0x4C, 0x48, 0x8B, 0x0D];
+    let start = bytes.as_ptr() as *mut u8;
+    let found = unsafe { find_pattern_bytes(start, bytes.len(), pattern!("48 8B 0?")) }.unwrap();
+    assert_eq!(found as usize - start as usize, 4);
+    let found = unsafe { find_pattern(start, bytes.len(), "48 8B ?C") }.unwrap();
+    assert_eq!(found as usize - start as usize, 1);
+}
+
+#[test]
+fn formatted_patterns_parse_back() {
+    let pattern = "48 8B 0D ? ? ? ? 4? ?C FF";
+    assert_eq!(format_pattern(&parse_pattern(pattern).unwrap()), pattern);
+    assert_eq!(format_pattern(&parse_pattern("?? 0a").unwrap()), "? 0A");
+}
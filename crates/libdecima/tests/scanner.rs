@@ -0,0 +1,96 @@
+use libdecima::mem::pattern::{PatternByte, parse_pattern};
+use libdecima::mem::scanner::{MultiScanner, Scanner, find_naive};
+use libdecima::pattern;
+
+/// Deterministic bytes from a small alphabet, so short patterns match often and overlap.
+fn haystack(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            [0x48, 0x8B, 0x0D, 0xFF, 0x00, 0x4C][(state % 6) as usize]
+        })
+        .collect()
+}
+
+fn naive_all(pattern: &[PatternByte], haystack: &[u8]) -> Vec<usize> {
+    haystack
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| find_naive(pattern, window) == Some(0))
+        .map(|(start, _)| start)
+        .collect()
+}
+
+const PATTERNS: [&str; 10] = [
+    "48 8B",
+    "48 ? 0D",
+    "? ? FF",
+    "4? 8B ?D",
+    "FF FF",
+    "00 ? ? 48 8B 0D 4C",
+    "? ?",
+    "0D ? 4C ? ? 48",
+    // no exact byte to anchor on.
+    "4? ?D",
+    "? 8? ?",
+];
+
+#[test]
+fn scanner_agrees_with_naive_search() {
+    for seed in 1..20 {
+        let haystack = haystack(seed, 2048);
+        for pattern in PATTERNS {
+            let pattern = parse_pattern(pattern).unwrap();
+            let scanner = Scanner::new(&pattern);
+            assert_eq!(
+                scanner.find(&haystack),
+                find_naive(&pattern, &haystack),
+                "{pattern:?}"
+            );
+            assert_eq!(
+                scanner.find_iter(&haystack).collect::<Vec<_>>(),
+                naive_all(&pattern, &haystack),
+                "{pattern:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn multi_scanner_agrees_with_single_scans() {
+    let patterns = PATTERNS
+        .iter()
+        .map(|pattern| parse_pattern(pattern).unwrap())
+        .collect::<Vec<_>>();
+    let scanner = MultiScanner::new(&patterns);
+    for seed in 1..20 {
+        let haystack = haystack(seed, 2048);
+        let found = scanner.find_all(&haystack);
+        for (pattern, found) in patterns.iter().zip(found) {
+            assert_eq!(found, naive_all(pattern, &haystack), "{pattern:?}");
+        }
+    }
+}
+
+#[test]
+fn matches_at_the_edges() {
+    let haystack = [0x48, 0x8B, 0x90, 0x90, 0x48, 0x8B];
+    let scanner = Scanner::new(pattern!("48 8B"));
+    assert_eq!(scanner.find_iter(&haystack).collect::<Vec<_>>(), [0, 4]);
+    // the anchor fits at the end, the rest of the pattern doesn't.
+    assert_eq!(Scanner::new(pattern!("48 8B ?")).find(&haystack[2..]), None);
+    assert_eq!(Scanner::new(pattern!("? 48 8B")).find(&haystack), Some(3));
+    assert_eq!(Scanner::new(pattern!("48 8B")).find(&[]), None);
+
+    // without an anchor the nibbles are still compared.
+    let haystack = [0x90, 0x90, 0x90, 0x48, 0x0D];
+    let scanner = Scanner::new(pattern!("4? ?D"));
+    assert_eq!(scanner.find(&haystack), Some(3));
+    assert_eq!(
+        MultiScanner::new(&[scanner.pattern().to_vec()]).find_all(&haystack),
+        [vec![3]]
+    );
+}
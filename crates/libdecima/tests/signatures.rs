@@ -0,0 +1,246 @@
+use libdecima::mem::image::Image;
+use libdecima::mem::offsets::Offsets;
+use libdecima::mem::signatures::{
+    Signature, SignatureDatabase, SignatureReport, Step, check_all_image,
+};
+use libdecima::mem::{PatternSearchError, parse_pattern};
+
+#[test]
+fn builtin_database_is_valid() {
+    let database = SignatureDatabase::builtin("hfw").unwrap();
+    assert_eq!(database.game, "hfw");
+    assert!(!database.signatures.is_empty());
+    for signature in &database.signatures {
+        assert!(!signature.patterns.is_empty(), "{}", signature.name);
+        for pattern in &signature.patterns {
+            assert!(
+                parse_pattern(pattern).is_ok(),
+                "{}: {}",
+                signature.name,
+                pattern
+            );
+        }
+    }
+    assert!(SignatureDatabase::builtin("unknown").is_none());
+}
+
+#[test]
+fn version_specific_entries_take_priority() {
+    let database = SignatureDatabase::parse(
+        r#"
+        game = "hfw"
+
+        [[signature]]
+        name = "Example"
+        patterns = ["AA BB"]
+
+        [[signature]]
+        name = "Example"
+        versions = ["1.5"]
+        patterns = ["CC DD"]
+        steps = [{ offset = 1 }, "deref"]
+        "#,
+    )
+    .unwrap();
+
+    let patterns = |version| database.select(version)[0].patterns[0].clone();
+    assert_eq!(patterns("1.5.80.0"), "CC DD");
+    assert_eq!(patterns("1.5"), "CC DD");
+    assert_eq!(patterns("1.50.1.0"), "AA BB");
+    assert_eq!(patterns("1.4.0.0"), "AA BB");
+    assert_eq!(
+        database.select("1.5.80.0")[0].steps,
+        [Step::Offset(1), Step::Deref]
+    );
+}
+
+#[test]
+fn overrides_replace_entries_by_name() {
+    let mut database = SignatureDatabase::parse(
+        r#"
+        game = "hfw"
+
+        [[signature]]
+        name = "Kept"
+        patterns = ["AA BB"]
+
+        [[signature]]
+        name = "Fixed"
+        patterns = ["CC DD"]
+
+        [[signature]]
+        name = "Fixed"
+        versions = ["1.5"]
+        patterns = ["CC EE"]
+        "#,
+    )
+    .unwrap();
+    database.merge(
+        SignatureDatabase::parse(
+            r#"
+            game = "hfw"
+
+            [[signature]]
+            name = "Fixed"
+            patterns = ["CC FF"]
+
+            [[signature]]
+            name = "New"
+            patterns = ["EE FF"]
+            "#,
+        )
+        .unwrap(),
+    );
+
+    // every entry of an overridden name goes, version specific ones included.
+    let selected = database.select("1.5.80.0");
+    let patterns = selected
+        .iter()
+        .map(|signature| (signature.name.as_str(), signature.patterns[0].as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        patterns,
+        [("Kept", "AA BB"), ("Fixed", "CC FF"), ("New", "EE FF")]
+    );
+}
+
+#[test]
+fn steps_are_applied_to_the_first_pattern_found() {
+    // lea rcx, [rip + 0x10]; then a pointer at the target 0x10 bytes after the instruction.
+    let mut bytes = vec![0x90u8; 0x30];
+    bytes[4..11].copy_from_slice(&[0x48, 0x8D, 0x0D, 0x10, 0x00, 0x00, 0x00]);
+    let pointer = 0x1234_5678usize;
+    bytes[0x1B..0x1B + size_of::<usize>()].copy_from_slice(&pointer.to_ne_bytes());
+
+    let signature = Signature {
+        name: "Example".to_string(),
+        versions: Vec::new(),
+        patterns: vec!["DE AD BE EF".to_string(), "48 8D 0D ? ? ? ?".to_string()],
+        string: None,
+        steps: vec![Step::RipRelative(7), Step::Deref, Step::Adjust(-8)],
+        section: ".text".to_string(),
+    };
+    let image = Image::new(&bytes);
+    let offset = signature.resolve_image(&image).unwrap();
+    assert_eq!(offset.as_ptr::<u8>() as usize, pointer - 8);
+
+    let missing = Signature {
+        patterns: vec!["DE AD BE EF".to_string()],
+        ..signature
+    };
+    assert!(matches!(
+        missing.resolve_image(&image),
+        Err(PatternSearchError::NotFound)
+    ));
+}
+
+#[test]
+fn unknown_signatures_are_reported_by_name() {
+    Offsets::load(&SignatureDatabase::builtin("hfw").unwrap(), "1.5.80.0");
+    assert!(Offsets::is_loaded());
+    assert!(matches!(
+        Offsets::find("Nothing::Here"),
+        Err(PatternSearchError::UnknownSignature(name)) if name == "Nothing::Here"
+    ));
+    // known, but there is no game module to scan outside of the game.
+    assert!(matches!(
+        Offsets::find("RTTI::FindByName"),
+        Err(PatternSearchError::OutOfRange)
+    ));
+}
+
+#[test]
+fn checks_report_ambiguous_and_missing_signatures() {
+    let bytes = [0x90u8, 0xAA, 0xBB, 0x90, 0xAA, 0xBB, 0xCC, 0x90];
+    let signature = |name: &str, pattern: &str| Signature {
+        name: name.to_string(),
+        versions: Vec::new(),
+        patterns: vec![pattern.to_string()],
+        string: None,
+        steps: vec![Step::Adjust(1)],
+        section: ".text".to_string(),
+    };
+    let report = SignatureReport {
+        checks: [
+            signature("Unique", "AA BB CC"),
+            signature("Ambiguous", "AA BB"),
+            signature("Missing", "DE AD"),
+        ]
+        .iter()
+        .map(|signature| signature.check_image(&Image::new(&bytes)))
+        .collect(),
+    };
+
+    let unique = report.get("Unique").unwrap();
+    assert_eq!(unique.matches, 1);
+    assert_eq!(
+        unique.result.as_ref().unwrap().as_ptr::<u8>() as usize,
+        bytes.as_ptr() as usize + 5
+    );
+
+    let ambiguous = report.ambiguous().collect::<Vec<_>>();
+    assert_eq!(ambiguous.len(), 1);
+    assert_eq!(ambiguous[0].name, "Ambiguous");
+    assert_eq!(ambiguous[0].matches, 2);
+
+    let failed = report.failed().collect::<Vec<_>>();
+    assert_eq!(failed.len(), 1);
+    assert!(matches!(
+        failed[0].result,
+        Err(PatternSearchError::NotFound)
+    ));
+
+    assert_eq!(
+        report.missing(&["Unique", "Ambiguous", "Missing", "NotInDatabase"]),
+        ["Missing", "NotInDatabase"]
+    );
+}
+
+#[test]
+fn check_without_a_game_module_fails_every_signature() {
+    Offsets::load(&SignatureDatabase::builtin("hfw").unwrap(), "1.5.80.0");
+    let report = Offsets::check();
+    assert!(!report.checks.is_empty());
+    assert_eq!(report.failed().count(), report.checks.len());
+    assert!(
+        report
+            .checks
+            .iter()
+            .all(|check| matches!(check.result, Err(PatternSearchError::OutOfRange)))
+    );
+}
+
+#[test]
+fn checking_all_at_once_agrees_with_checking_each() {
+    let bytes = [0x90u8, 0xAA, 0xBB, 0x90, 0xAA, 0xBB, 0xCC, 0x90, 0xDD];
+    let signatures = [
+        ("Unique", vec!["AA BB CC"]),
+        ("Ambiguous", vec!["AA BB"]),
+        ("Fallback", vec!["DE AD", "BB ? 90"]),
+        ("Missing", vec!["DE AD"]),
+        ("Invalid", vec!["AA BG", "AA BB"]),
+        ("Wildcards", vec!["? ?"]),
+    ]
+    .map(|(name, patterns)| Signature {
+        name: name.to_string(),
+        versions: Vec::new(),
+        patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+        string: None,
+        steps: Vec::new(),
+        section: ".text".to_string(),
+    });
+
+    let image = Image::new(&bytes);
+    let all = check_all_image(&signatures, &image);
+    for (signature, check) in signatures.iter().zip(all) {
+        let single = signature.check_image(&image);
+        assert_eq!(check.name, single.name);
+        assert_eq!(check.matches, single.matches, "{}", check.name);
+        assert_eq!(
+            format!("{:?}", check.result),
+            format!("{:?}", single.result),
+            "{}",
+            check.name
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use libdecima::mem::versions::{Versioned, matches_version, select};
+
+fn versions(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|entry| entry.to_string()).collect()
+}
+
+#[test]
+fn prefixes_match_whole_components() {
+    let prefix = versions(&["1.5"]);
+    assert!(matches_version(&prefix, "1.5"));
+    assert!(matches_version(&prefix, "1.5.80.0"));
+    assert!(!matches_version(&prefix, "1.50.0.0"));
+    assert!(!matches_version(&prefix, "1.4.0.0"));
+    assert!(matches_version(&[], "1.4.0.0"));
+}
+
+#[test]
+fn ranges_compare_components() {
+    let range = versions(&[">=1.5.80, <1.6"]);
+    assert!(matches_version(&range, "1.5.80.0"));
+    assert!(matches_version(&range, "1.5.100.2"));
+    assert!(!matches_version(&range, "1.5.79.9"));
+    assert!(!matches_version(&range, "1.6.0.0"));
+
+    assert!(matches_version(&versions(&["<=1.4"]), "1.4.0.0"));
+    assert!(!matches_version(&versions(&["<=1.4"]), "1.4.0.1"));
+    assert!(matches_version(&versions(&[">1.4"]), "1.4.0.1"));
+    assert!(matches_version(&versions(&["=1.4"]), "1.4.0.0"));
+    // versions that aren't numbers never match a range.
+    assert!(!matches_version(&versions(&[">=1.5"]), "1.5-beta"));
+    assert!(matches_version(&versions(&["1.3", ">=1.5"]), "1.3.2.0"));
+}
+
+struct Entry(&'static str, Vec<String>);
+
+impl Versioned for Entry {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn versions(&self) -> &[String] {
+        &self.1
+    }
+}
+
+#[test]
+fn version_specific_entries_are_selected_first() {
+    let entries = [
+        Entry("A", Vec::new()),
+        Entry("B", versions(&["<1.5"])),
+        Entry("A", versions(&[">=1.5.80"])),
+        Entry("A", versions(&["1.5"])),
+    ];
+    let selected = |version| {
+        select(&entries, version)
+            .into_iter()
+            .map(|entry| (entry.0, entry.1.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        selected("1.4.0.0"),
+        [("A", Vec::new()), ("B", versions(&["<1.5"]))]
+    );
+    assert_eq!(selected("1.5.80.0"), [("A", versions(&[">=1.5.80"]))]);
+    assert_eq!(selected("1.5.10.0"), [("A", versions(&["1.5"]))]);
+}
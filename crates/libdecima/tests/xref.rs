@@ -0,0 +1,219 @@
+mod common;
+
+use common::pe::{Pe, put};
+use libdecima::mem::PatternSearchError;
+use libdecima::mem::cache::OffsetCache;
+use libdecima::mem::image::Image;
+use libdecima::mem::signatures::{SignatureDatabase, Step};
+use libdecima::mem::xref::{find_references, find_string, find_string_functions, function_start};
+
+/// `bytes` followed by the displacement from `end`, the end of the instruction, to `target`.
+fn rip_relative(bytes: &[u8], end: usize, target: usize) -> Vec<u8> {
+    let displacement = (target as isize - end as isize) as i32;
+    [bytes, &displacement.to_le_bytes()].concat()
+}
+
+/// Two functions between `int3` padding, both referencing a string after them:
+///
+/// ```text
+/// 10: sub rsp, 0x28
+/// 14: lea rcx, [rip+x]             ; "Crash Logger"
+/// 1B: add rsp, 0x28
+/// 1F: ret
+/// 30: mov [rsp+8], rbx
+/// 35: cmp byte ptr [rip+x], 0      ; "Crash Logger", with an immediate after the displacement
+/// 3C: ret
+/// 40: mov eax, 0xCCCCCCCC
+/// 45: ret
+/// 80: "Crash Logger\0"
+/// ```
+fn buffer() -> Vec<u8> {
+    let mut data = vec![0xCC; 0xA0];
+    put(&mut data, 0x10, &[0x48, 0x83, 0xEC, 0x28]);
+    put(
+        &mut data,
+        0x14,
+        &rip_relative(&[0x48, 0x8D, 0x0D], 0x1B, 0x80),
+    );
+    put(&mut data, 0x1B, &[0x48, 0x83, 0xC4, 0x28, 0xC3]);
+    put(&mut data, 0x30, &[0x48, 0x89, 0x5C, 0x24, 0x08]);
+    put(&mut data, 0x35, &rip_relative(&[0x80, 0x3D], 0x3C, 0x80));
+    put(&mut data, 0x3B, &[0x00, 0xC3]);
+    put(&mut data, 0x40, &[0xB8, 0xCC, 0xCC, 0xCC, 0xCC, 0xC3]);
+    put(&mut data, 0x80, b"Crash Logger\0");
+    data
+}
+
+const TEXT_RVA: usize = 0x1000;
+const RDATA_RVA: usize = 0x2000;
+const PDATA_RVA: usize = 0x3000;
+
+/// A mapped PE with `.text`, `.rdata` and `.pdata` sections:
+///
+/// ```text
+/// 1000: xor eax, eax
+/// 1002: ret
+/// 1010: mov eax, 0xCCCCCCCC        ; covered by .pdata, which the padding scan would stop in
+/// 1015: lea rdx, [rip+x]           ; "Crash Logger"
+/// 101C: ret
+/// 1020: mov rax, [rip+x]           ; "Crash Logger", a leaf function without a .pdata entry
+/// 1027: ret
+/// ```
+fn pe() -> Vec<u8> {
+    // two .pdata entries.
+    let mut data = Pe::new(0x4000)
+        .section(".text", TEXT_RVA, 0x100)
+        .section(".rdata", RDATA_RVA, 0x100)
+        .section(".pdata", PDATA_RVA, 0x100)
+        .exception_directory(PDATA_RVA, 24)
+        .mapped();
+
+    put(&mut data, 0x1000, &[0x33, 0xC0, 0xC3]);
+    put(&mut data, 0x1003, &[0x90; 0x0D]);
+    put(&mut data, 0x1010, &[0xB8, 0xCC, 0xCC, 0xCC, 0xCC]);
+    put(
+        &mut data,
+        0x1015,
+        &rip_relative(&[0x48, 0x8D, 0x15], 0x101C, RDATA_RVA),
+    );
+    put(&mut data, 0x101C, &[0xC3, 0xCC, 0xCC, 0xCC]);
+    put(
+        &mut data,
+        0x1020,
+        &rip_relative(&[0x48, 0x8B, 0x05], 0x1027, RDATA_RVA),
+    );
+    put(&mut data, 0x1027, &[0xC3]);
+    put(&mut data, 0x1028, &[0xCC; 0xD8]);
+    put(&mut data, RDATA_RVA, b"Crash Logger\0");
+    for (index, (begin, end)) in [(0x1000u32, 0x1003u32), (0x1010, 0x101D)]
+        .into_iter()
+        .enumerate()
+    {
+        let entry = PDATA_RVA + index * 12;
+        put(&mut data, entry, &begin.to_le_bytes());
+        put(&mut data, entry + 4, &end.to_le_bytes());
+        put(&mut data, entry + 8, &0x3100u32.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn references_are_found_in_buffers() {
+    let data = buffer();
+    let image = Image::new(&data);
+
+    assert_eq!(
+        find_string(&image, ".rdata", "Crash Logger").unwrap(),
+        [0x80]
+    );
+    // only whole strings, "Logger" is the end of one but never referenced on its own.
+    assert_eq!(find_string(&image, ".rdata", "Logger").unwrap(), [0x86]);
+    assert!(find_string(&image, ".rdata", "Crash").unwrap().is_empty());
+
+    assert_eq!(
+        find_references(&image, ".text", 0x80).unwrap(),
+        [0x14, 0x35]
+    );
+    assert!(find_references(&image, ".text", 0x86).unwrap().is_empty());
+
+    assert_eq!(function_start(&image, ".text", 0x1B).unwrap(), 0x10);
+    // the immediate isn't padding, functions start aligned.
+    assert_eq!(function_start(&image, ".text", 0x45).unwrap(), 0x40);
+    // not the start of an instruction when decoding from the padding.
+    assert!(matches!(
+        function_start(&image, ".text", 0x42),
+        Err(PatternSearchError::NoFunctionStart(0x42))
+    ));
+    assert!(matches!(
+        function_start(&image, ".text", 0x16),
+        Err(PatternSearchError::NoFunctionStart(0x16))
+    ));
+    assert_eq!(
+        find_string_functions(&image, "Crash Logger").unwrap(),
+        [0x10, 0x30]
+    );
+    assert!(find_string_functions(&image, "Logger").unwrap().is_empty());
+}
+
+#[test]
+fn function_starts_come_from_pdata() {
+    let data = pe();
+    let image = Image::parse(&data).unwrap();
+
+    assert!(image.directory(3).is_some());
+    assert_eq!(
+        find_references(&image, ".text", RDATA_RVA).unwrap(),
+        [0x1015, 0x1020]
+    );
+    // scanning back for padding would stop inside the mov.
+    assert_eq!(function_start(&image, ".text", 0x1015).unwrap(), 0x1010);
+    assert_eq!(function_start(&image, ".text", 0x1002).unwrap(), 0x1000);
+    // no entry for the leaf function, so padding it is.
+    assert_eq!(function_start(&image, ".text", 0x1027).unwrap(), 0x1020);
+    assert_eq!(
+        find_string_functions(&image, "Crash Logger").unwrap(),
+        [0x1010, 0x1020]
+    );
+}
+
+#[test]
+fn signatures_are_found_through_strings() {
+    let database = SignatureDatabase::parse(
+        r#"
+        game = "hfw"
+
+        [[signature]]
+        name = "CrashLogger"
+        string = "Crash Logger"
+        steps = ["function"]
+
+        [[signature]]
+        name = "Fallback"
+        patterns = ["DE AD BE EF"]
+        string = "Crash Logger"
+
+        [[signature]]
+        name = "Missing"
+        string = "Telemetry"
+        "#,
+    )
+    .unwrap();
+    let signatures = &database.signatures;
+    assert_eq!(signatures[0].steps, [Step::Function]);
+
+    let data = pe();
+    let image = Image::parse(&data).unwrap();
+    let check = signatures[0].check_image(&image);
+    assert_eq!(image.rva(check.result.unwrap()), Some(0x1010));
+    assert_eq!(check.matches, 2);
+    assert_eq!(check.found.map(|(index, _)| index), Some(0));
+
+    // the patterns are tried first, the string after them.
+    let fallback = signatures[1].check_image(&image);
+    assert_eq!(fallback.found.map(|(index, _)| index), Some(1));
+    assert_eq!(image.rva(fallback.result.unwrap()), Some(0x1015));
+    assert_eq!(
+        signatures[1]
+            .find_all(&image, 1)
+            .unwrap()
+            .into_iter()
+            .map(|found| image.rva(found).unwrap())
+            .collect::<Vec<_>>(),
+        [0x1015, 0x1020]
+    );
+    assert!(signatures[2].check_image(&image).result.is_err());
+
+    // cached entries for strings are verified like patterns.
+    let mut cache = OffsetCache::new("exe");
+    cache.check(signatures, &image);
+    assert_eq!(cache.entries.len(), 2);
+    assert!(
+        signatures[..2]
+            .iter()
+            .all(|signature| cache.verify(signature, &image).is_some())
+    );
+    let mut moved = data.clone();
+    put(&mut moved, RDATA_RVA, b"Crash Looger\0");
+    let moved = Image::parse(&moved).unwrap();
+    assert!(cache.verify(&signatures[0], &moved).is_none());
+}
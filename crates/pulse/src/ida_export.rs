@@ -9,9 +9,10 @@ use libdecima::util::ReadCStr;
 use std::ffi::CStr;
 use std::fs::File;
 use std::io::Write as _;
+use std::path::Path;
 
-pub fn ida_export(types: Vec<&RTTI>) -> anyhow::Result<()> {
-    let mut file = File::create("cauldron/plugins/pulse/output/hfw_ida.idc")?;
+pub fn ida_export(types: Vec<&RTTI>, output: &Path) -> anyhow::Result<()> {
+    let mut file = File::create(output.join("hfw_ida.idc"))?;
 
     writeln!(
         file,
@@ -52,7 +53,7 @@ static main() {{"#
 
     #[cfg(debug_assertions)]
     {
-        let mut dump = File::create("cauldron/plugins/pulse/output/dump.txt")?;
+        let mut dump = File::create(output.join("dump.txt"))?;
         for group in symbols.groups.as_slice() {
             let group = unsafe { &*(*group) };
             dump_symbols(&mut dump, group)?;
@@ -2,12 +2,14 @@
 #![allow(static_mut_refs)]
 
 use crate::ida_export::ida_export;
-use cauldron::{CauldronLoader, CauldronPlugin, define_cauldron_plugin};
+use cauldron::context::PluginContext;
+use cauldron::{CauldronPlugin, cauldron_plugin};
 use libdecima::log;
 use libdecima::types::decima::core::factory_manager::FactoryManager;
 
 mod ida_export;
 
+#[cauldron_plugin(name = "Pulse", supported_games(hfw))]
 pub struct PulsePlugin {}
 
 impl CauldronPlugin for PulsePlugin {
@@ -15,7 +17,18 @@ impl CauldronPlugin for PulsePlugin {
         PulsePlugin {}
     }
 
-    fn on_init(&self, _loader: &CauldronLoader) {
+    fn on_init(&self, context: &PluginContext) {
+        let output = match context.data_dir() {
+            Ok(output) => output,
+            Err(error) => {
+                log!(
+                    "pulse",
+                    "error: failed to create output directory: {}",
+                    error
+                );
+                return;
+            }
+        };
         let Some(factory) = FactoryManager::get_instance() else {
             log!("error: failed to get FactoryManager instance");
             return;
@@ -30,11 +43,9 @@ impl CauldronPlugin for PulsePlugin {
             }
         }
 
-        ida_export(new_types).unwrap()
+        ida_export(new_types, &output).unwrap()
     }
 }
 
 unsafe impl Sync for PulsePlugin {}
 unsafe impl Send for PulsePlugin {}
-
-define_cauldron_plugin!(PulsePlugin, include_str!("../pulse.cauldron.toml"));
@@ -0,0 +1,3 @@
+//! The logic behind the tasks in `main.rs` that's worth testing on its own.
+
+pub mod sigcheck;
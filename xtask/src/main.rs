@@ -1,6 +1,16 @@
+use cauldron::package;
+use libdecima::mem::cache::OffsetCache;
+use libdecima::mem::functions::FunctionTable;
+use libdecima::mem::generator::generate_signature;
+use libdecima::mem::image::Image;
+use libdecima::mem::msvc_rtti::ClassTable;
+use libdecima::mem::signatures::{Signature, SignatureDatabase};
+use std::collections::BTreeMap;
+use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{env, fs};
+use xtask::sigcheck::{self, SigcheckResults};
 
 fn main() {
     if let Err(e) = try_main() {
@@ -15,6 +25,11 @@ fn try_main() -> Result<(), DynError> {
     let task = env::args().nth(1);
     match task.as_deref() {
         Some("hfw") => hfw_task()?,
+        Some("schemas") => schemas_task()?,
+        Some("package") => package_task()?,
+        Some("sigcheck") => sigcheck_task()?,
+        Some("sigmake") => sigmake_task()?,
+        Some("classes") => classes_task()?,
         _ => print_help(),
     }
     Ok(())
@@ -24,6 +39,16 @@ fn print_help() {
     eprintln!("
 Tasks:
 \thfw - build cauldron and copy it to the hfw directory along with dev plugins. (you may need to change the dir this copies to in `xtask/src/main.rs`)
+\tschemas - regenerate the plugin metadata json schemas in `schemas/` from the rust types.
+\tpackage <plugin.dll> [assets dir] - bundle a built plugin and its assets into a `.cauldron` package in `target/packages/`.
+\tsigcheck <game.exe> [options] - check every signature against an executable on disk, failing if any aren't found.
+\t\t--game <id> - game whose built-in signatures are checked, `hfw` by default.
+\t\t--version <a.b.c.d> - game version used to pick version specific signatures.
+\t\t--signatures <file.toml> - also check the signatures in a plugin's (or an override) database, can be repeated.
+\t\t--save <results.toml> - write where each signature matched, for a later `--diff`.
+\t\t--diff <results.toml> - report signatures that broke, became ambiguous or moved since a saved run.
+\tsigmake <game.exe> <rva> - print the shortest unique signature for the code at an RVA in `.text`.
+\tclasses <game.exe> [name] - list every C++ class with a vtable and its bases, or only a class and those deriving from it.
     ");
 }
 
@@ -81,6 +106,234 @@ fn hfw_task() -> Result<(), DynError> {
     Ok(())
 }
 
+fn schemas_task() -> Result<(), DynError> {
+    let schemas_dir = project_root().join("schemas");
+    for (name, schema) in cauldron::metadata::schema::schemas() {
+        fs::write(
+            schemas_dir.join(name),
+            cauldron::metadata::schema::to_file_contents(&schema),
+        )?;
+        println!("wrote schemas/{}", name);
+    }
+
+    Ok(())
+}
+
+fn package_task() -> Result<(), DynError> {
+    let Some(library) = env::args().nth(2).map(PathBuf::from) else {
+        Err("usage: cargo xtask package <plugin.dll> [assets dir]")?
+    };
+    let library_name = library.file_name().unwrap().to_str().unwrap().to_string();
+
+    // only the metadata export is called, nothing in the plugin gets initialized.
+    let metadata = unsafe {
+        let handle = libloading::Library::new(&library)?;
+        let metadata =
+            handle.get::<extern "C" fn() -> &'static str>(b"__cauldron_plugin__metadata\0")?;
+        cauldron::metadata::parse_plugin_metadata(metadata())?
+    };
+
+    let mut files = BTreeMap::new();
+    files.insert(library_name.clone(), fs::read(&library)?);
+    if let Some(assets) = env::args().nth(3) {
+        add_assets(Path::new(&assets), "", &mut files)?;
+    }
+
+    let out_dir = project_root().join("target/packages");
+    fs::create_dir_all(&out_dir)?;
+    let out = out_dir.join(format!(
+        "{}-{}.{}",
+        metadata.cauldron.id,
+        metadata.cauldron.version,
+        package::EXTENSION
+    ));
+    package::write(
+        File::create(&out)?,
+        &metadata.cauldron.id,
+        &metadata.cauldron.version,
+        &library_name,
+        &files,
+    )?;
+    println!("wrote {}", out.display());
+
+    Ok(())
+}
+
+fn sigcheck_task() -> Result<(), DynError> {
+    let usage = "usage: cargo xtask sigcheck <game.exe> [--game <id>] [--version <a.b.c.d>] \
+                 [--signatures <file.toml>]... [--save <results.toml>] [--diff <results.toml>]";
+    let mut args = env::args().skip(2);
+    let Some(executable) = args.next().map(PathBuf::from) else {
+        Err(usage)?
+    };
+    let (mut game, mut version) = (String::from("hfw"), String::new());
+    let (mut databases, mut save, mut diff) = (Vec::new(), None, None);
+    while let Some(flag) = args.next() {
+        let Some(value) = args.next() else {
+            Err(usage)?
+        };
+        match flag.as_str() {
+            "--game" => game = value,
+            "--version" => version = value,
+            "--signatures" => databases.push(PathBuf::from(value)),
+            "--save" => save = Some(PathBuf::from(value)),
+            "--diff" => diff = Some(PathBuf::from(value)),
+            _ => Err(usage)?,
+        }
+    }
+
+    let mut signatures: Vec<Signature> = Vec::new();
+    let builtin = SignatureDatabase::builtin(&game).ok_or(format!("no signatures for {}", game))?;
+    for database in databases
+        .iter()
+        .try_fold(vec![builtin], |mut loaded, path| {
+            loaded.push(SignatureDatabase::parse(&fs::read_to_string(path)?)?);
+            Ok::<_, DynError>(loaded)
+        })?
+    {
+        // later databases override signatures of the same name.
+        for signature in database.select(&version) {
+            match signatures.iter_mut().find(|s| s.name == signature.name) {
+                Some(existing) => *existing = signature.clone(),
+                None => signatures.push(signature.clone()),
+            }
+        }
+    }
+
+    let file = fs::read(&executable)?;
+    let mapped = Image::map(&file)?;
+    let image = Image::parse(&mapped)?;
+    let results = SigcheckResults {
+        executable: OffsetCache::hash(&file),
+        signatures: signatures
+            .iter()
+            .map(|signature| sigcheck::sigcheck(signature, &image))
+            .collect(),
+    };
+
+    for result in &results.signatures {
+        let status = match result.matches.len() {
+            _ if result.failed() => "FAILED",
+            1 => "ok",
+            _ => "AMBIGUOUS",
+        };
+        let matches = result
+            .matches
+            .iter()
+            .map(|rva| format!("{:#x}", rva))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{:<10}{:<48}{:<24}{}",
+            status,
+            result.name,
+            result.describe(),
+            matches
+        );
+    }
+
+    if let Some(previous) = diff {
+        let previous: SigcheckResults = toml::from_str(&fs::read_to_string(previous)?)?;
+        if previous.executable == results.executable {
+            println!("\nsame executable as the previous run.");
+        } else {
+            println!("\nchanges since the previous run:");
+        }
+        for change in sigcheck::diff(&previous, &results) {
+            println!("{}", change);
+        }
+    }
+    if let Some(save) = save {
+        fs::write(&save, toml::to_string(&results)?)?;
+        println!("wrote {}", save.display());
+    }
+
+    results.outcome()?;
+    Ok(())
+}
+
+fn sigmake_task() -> Result<(), DynError> {
+    let usage = "usage: cargo xtask sigmake <game.exe> <rva>";
+    let (Some(executable), Some(rva)) = (env::args().nth(2), env::args().nth(3)) else {
+        Err(usage)?
+    };
+    let rva = usize::from_str_radix(rva.trim_start_matches("0x"), 16)?;
+
+    let mapped = Image::map(&fs::read(executable)?)?;
+    let image = Image::parse(&mapped)?;
+    println!("{}", generate_signature(&image, rva)?);
+    // stderr, so the signature can still be piped on its own.
+    match FunctionTable::new(&image).function_containing(rva) {
+        Some(function) if function.start == rva => {}
+        Some(function) => eprintln!(
+            "note: {:#x} is {:#x} bytes into the function at {:#x}",
+            rva,
+            rva - function.start,
+            function.start
+        ),
+        None => eprintln!("note: {:#x} isn't in a function listed in .pdata", rva),
+    }
+    Ok(())
+}
+
+fn classes_task() -> Result<(), DynError> {
+    let usage = "usage: cargo xtask classes <game.exe> [name]";
+    let Some(executable) = env::args().nth(2) else {
+        Err(usage)?
+    };
+    let filter = env::args().nth(3);
+
+    let mapped = Image::map(&fs::read(executable)?)?;
+    let table = ClassTable::parse(&Image::parse(&mapped)?)?;
+    for class in table.classes() {
+        let bases = class
+            .bases
+            .iter()
+            .map(|base| base.name.as_str())
+            .collect::<Vec<_>>();
+        // the class itself and everything deriving from it.
+        let listed = match &filter {
+            Some(filter) => class.name == *filter || bases.contains(&filter.as_str()),
+            None => true,
+        };
+        if !listed {
+            continue;
+        }
+        let vtables = class
+            .vtables
+            .iter()
+            .map(|vtable| match vtable.offset {
+                0 => format!("{:#x}", vtable.rva),
+                offset => format!("{:#x}@{:#x}", vtable.rva, offset),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        match bases.is_empty() {
+            true => println!("{:<24}{}", vtables, class.name),
+            false => println!("{:<24}{} : {}", vtables, class.name, bases.join(", ")),
+        }
+    }
+    Ok(())
+}
+
+/// Adds every file under `dir` to `files`, keyed by its `/` separated path in the package.
+fn add_assets(
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, Vec<u8>>,
+) -> Result<(), DynError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = format!("{}{}", prefix, path.file_name().unwrap().to_str().unwrap());
+        if path.is_dir() {
+            add_assets(&path, &format!("{}/", name), files)?;
+        } else {
+            files.insert(name, fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
 fn project_root() -> PathBuf {
     Path::new(&env!("CARGO_MANIFEST_DIR"))
         .ancestors()
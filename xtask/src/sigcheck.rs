@@ -0,0 +1,183 @@
+//! Checking signatures against an executable on disk and comparing with a previous run, see
+//! `cargo xtask sigcheck`.
+
+use libdecima::mem::PatternSearchError;
+use libdecima::mem::image::Image;
+use libdecima::mem::signatures::Signature;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Where each signature matched in an executable, written by `sigcheck --save`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SigcheckResults {
+    /// SHA-256 of the executable.
+    pub executable: String,
+    #[serde(default, rename = "signature")]
+    pub signatures: Vec<SigcheckResult>,
+}
+
+impl SigcheckResults {
+    /// Fails if any signature didn't resolve, which makes the task exit with an error.
+    pub fn outcome(&self) -> Result<(), String> {
+        let failed = self.signatures.iter().filter(|r| r.failed()).count();
+        match failed {
+            0 => Ok(()),
+            _ => Err(format!(
+                "{}/{} signatures failed",
+                failed,
+                self.signatures.len()
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigcheckResult {
+    pub name: String,
+    /// RVAs of every match of the pattern used, empty if none of the patterns matched.
+    pub matches: Vec<usize>,
+    /// RVA the signature resolves to after its steps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SigcheckResult {
+    pub fn failed(&self) -> bool {
+        self.resolved.is_none()
+    }
+
+    pub fn describe(&self) -> String {
+        match (&self.error, self.resolved) {
+            (Some(error), _) => error.clone(),
+            (None, Some(resolved)) if self.matches.len() > 1 => {
+                format!("{:#x} ({} matches)", resolved, self.matches.len())
+            }
+            (None, Some(resolved)) => format!("{:#x}", resolved),
+            (None, None) => String::from("not found"),
+        }
+    }
+}
+
+/// Checks `signature` against `image`.
+pub fn sigcheck(signature: &Signature, image: &Image) -> SigcheckResult {
+    let check = signature.check_image(image);
+    // the check only counts matches, scan again for where the pattern used matched.
+    let matches = match check.found {
+        Some((index, _)) => signature
+            .find_all(image, index)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|found| image.rva(found))
+            .collect(),
+        None => Vec::new(),
+    };
+    let (resolved, error) = match check.result {
+        Ok(offset) => match image.rva(offset) {
+            Some(rva) => (Some(rva), None),
+            None => (None, Some(String::from("resolved outside of the image"))),
+        },
+        Err(PatternSearchError::NotFound) => (None, None),
+        Err(error) => (None, Some(error.to_string())),
+    };
+    SigcheckResult {
+        name: signature.name.clone(),
+        matches,
+        resolved,
+        error,
+    }
+}
+
+/// How a signature's result differs from the previous run, the first that applies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Resolved before, doesn't now.
+    Broke,
+    /// Didn't resolve before, does now.
+    Fixed,
+    /// Matches more than once now, at most once before.
+    Ambiguous,
+    /// Matches once now, more than once before.
+    Unique,
+    /// Resolves somewhere else.
+    Moved,
+    /// Anything else, e.g. another error or the other matches moved.
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn between(old: &SigcheckResult, new: &SigcheckResult) -> Self {
+        match (old.failed(), new.failed()) {
+            (false, true) => ChangeKind::Broke,
+            (true, false) => ChangeKind::Fixed,
+            _ if old.matches.len() <= 1 && new.matches.len() > 1 => ChangeKind::Ambiguous,
+            _ if old.matches.len() > 1 && new.matches.len() == 1 => ChangeKind::Unique,
+            _ if old.resolved != new.resolved => ChangeKind::Moved,
+            _ => ChangeKind::Changed,
+        }
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Broke => "broke",
+            ChangeKind::Fixed => "fixed",
+            ChangeKind::Ambiguous => "ambiguous",
+            ChangeKind::Unique => "unique",
+            ChangeKind::Moved => "moved",
+            ChangeKind::Changed => "changed",
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    Added(&'a SigcheckResult),
+    Removed(&'a SigcheckResult),
+    Changed {
+        kind: ChangeKind,
+        old: &'a SigcheckResult,
+        new: &'a SigcheckResult,
+    },
+}
+
+impl Display for Change<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added(new) => write!(f, "+ {}: {}", new.name, new.describe()),
+            Change::Removed(old) => write!(f, "- {}: {}", old.name, old.describe()),
+            Change::Changed { kind, old, new } => write!(
+                f,
+                "~ {} {}: {} -> {}",
+                new.name,
+                kind,
+                old.describe(),
+                new.describe()
+            ),
+        }
+    }
+}
+
+/// What changed since `previous`, signatures in `current` first and removed ones after.
+pub fn diff<'a>(previous: &'a SigcheckResults, current: &'a SigcheckResults) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    for new in &current.signatures {
+        match previous.signatures.iter().find(|r| r.name == new.name) {
+            None => changes.push(Change::Added(new)),
+            Some(old) if old != new => changes.push(Change::Changed {
+                kind: ChangeKind::between(old, new),
+                old,
+                new,
+            }),
+            Some(_) => {}
+        }
+    }
+    for old in &previous.signatures {
+        if !current.signatures.iter().any(|r| r.name == old.name) {
+            changes.push(Change::Removed(old));
+        }
+    }
+    changes
+}
@@ -0,0 +1,108 @@
+use xtask::sigcheck::{Change, ChangeKind, SigcheckResult, SigcheckResults, diff};
+
+fn result(name: &str, matches: &[usize], resolved: Option<usize>) -> SigcheckResult {
+    SigcheckResult {
+        name: name.to_string(),
+        matches: matches.to_vec(),
+        resolved,
+        error: None,
+    }
+}
+
+fn results(signatures: Vec<SigcheckResult>) -> SigcheckResults {
+    SigcheckResults {
+        executable: String::from("exe"),
+        signatures,
+    }
+}
+
+#[test]
+fn changes_are_classified() {
+    let found = result("Example", &[0x10], Some(0x10));
+    let kind = |old: &SigcheckResult, new: &SigcheckResult| ChangeKind::between(old, new);
+
+    assert_eq!(
+        kind(&found, &result("Example", &[], None)),
+        ChangeKind::Broke
+    );
+    assert_eq!(
+        kind(&result("Example", &[], None), &found),
+        ChangeKind::Fixed
+    );
+    assert_eq!(
+        kind(&found, &result("Example", &[0x10, 0x20], Some(0x10))),
+        ChangeKind::Ambiguous
+    );
+    assert_eq!(
+        kind(&result("Example", &[0x10, 0x20], Some(0x10)), &found),
+        ChangeKind::Unique
+    );
+    assert_eq!(
+        kind(&found, &result("Example", &[0x20], Some(0x20))),
+        ChangeKind::Moved
+    );
+    // the pattern still matches where it did, the steps after it fail now.
+    let mut errored = result("Example", &[0x10], None);
+    errored.error = Some(String::from("out of range"));
+    assert_eq!(kind(&found, &errored), ChangeKind::Broke);
+    assert_eq!(
+        kind(&result("Example", &[], None), &errored),
+        ChangeKind::Changed
+    );
+    // breaking wins over becoming ambiguous.
+    assert_eq!(
+        kind(&found, &result("Example", &[0x10, 0x20], None)),
+        ChangeKind::Broke
+    );
+}
+
+#[test]
+fn runs_are_compared_by_name() {
+    let previous = results(vec![
+        result("Same", &[0x10], Some(0x10)),
+        result("Moved", &[0x20], Some(0x20)),
+        result("Removed", &[0x30], Some(0x30)),
+    ]);
+    let current = results(vec![
+        result("Added", &[0x40, 0x50], Some(0x40)),
+        result("Moved", &[0x28], Some(0x28)),
+        result("Same", &[0x10], Some(0x10)),
+    ]);
+
+    let changes = diff(&previous, &current);
+    assert_eq!(
+        changes,
+        [
+            Change::Added(&current.signatures[0]),
+            Change::Changed {
+                kind: ChangeKind::Moved,
+                old: &previous.signatures[1],
+                new: &current.signatures[1],
+            },
+            Change::Removed(&previous.signatures[2]),
+        ]
+    );
+    assert_eq!(
+        changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        [
+            "+ Added: 0x40 (2 matches)",
+            "~ Moved moved: 0x20 -> 0x28",
+            "- Removed: 0x30",
+        ]
+    );
+    assert!(diff(&current, &current).is_empty());
+}
+
+#[test]
+fn failed_signatures_fail_the_task() {
+    let mut run = results(vec![
+        result("Found", &[0x10], Some(0x10)),
+        // ambiguous signatures still resolve to the first match.
+        result("Ambiguous", &[0x10, 0x20], Some(0x10)),
+    ]);
+    assert_eq!(run.outcome(), Ok(()));
+
+    run.signatures.push(result("Missing", &[], None));
+    assert_eq!(run.outcome(), Err(String::from("1/3 signatures failed")));
+    assert_eq!(results(Vec::new()).outcome(), Ok(()));
+}